@@ -0,0 +1,157 @@
+use std::f64::consts;
+
+use super::{
+    token::Token,
+    AsmError,
+};
+
+/// Evaluate a `:calc` expression.
+///
+/// Like Octo, operators have no precedence and apply right to left, so
+/// `2 * 3 + 1` is 8. Parentheses group. `lookup` gives the values of
+/// constants and labels.
+pub fn evaluate(tokens: &[Token], lookup: impl Fn(&str) -> Option<f64>) -> Result<f64, AsmError> {
+    let mut calc = Calc {
+        tokens,
+        position: 0,
+        lookup: &lookup,
+    };
+    let value = calc.expression()?;
+    if let Some(token) = tokens.get(calc.position) {
+        return Err(AsmError::at(
+            token,
+            format!("unexpected `{token}` in expression"),
+        ));
+    }
+
+    Ok(value)
+}
+
+struct Calc<'a> {
+    tokens: &'a [Token],
+    position: usize,
+    lookup: &'a dyn Fn(&str) -> Option<f64>,
+}
+
+impl Calc<'_> {
+    fn next(&mut self) -> Result<&Token, AsmError> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .ok_or_else(|| match self.tokens.last() {
+                Some(last) => AsmError::at(last, "unfinished expression"),
+                None => AsmError::new(0, 0, "empty expression"),
+            })?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn expression(&mut self) -> Result<f64, AsmError> {
+        let left = self.term()?;
+
+        let Some(token) = self.tokens.get(self.position) else {
+            return Ok(left);
+        };
+        if token.text == ")" {
+            return Ok(left);
+        }
+        self.position += 1;
+        let right = self.expression()?;
+
+        let (a, b) = (left as i64, right as i64);
+        let value = match token.text.as_str() {
+            "+" => left + right,
+            "-" => left - right,
+            "*" => left * right,
+            "/" => left / right,
+            "%" => left % right,
+            "pow" => left.powf(right),
+            "min" => left.min(right),
+            "max" => left.max(right),
+            "&" => (a & b) as f64,
+            "|" => (a | b) as f64,
+            "^" => (a ^ b) as f64,
+            "<<" | ">>" => {
+                let shifted = u32::try_from(b)
+                    .ok()
+                    .and_then(|b| match token.text.as_str() {
+                        "<<" => a.checked_shl(b),
+                        _ => a.checked_shr(b),
+                    });
+                let shifted = shifted.ok_or_else(|| {
+                    AsmError::at(token, format!("can't shift by {b}, expected 0 to 63"))
+                })?;
+                shifted as f64
+            }
+            "<" => (left < right) as u8 as f64,
+            ">" => (left > right) as u8 as f64,
+            "<=" => (left <= right) as u8 as f64,
+            ">=" => (left >= right) as u8 as f64,
+            "==" => (left == right) as u8 as f64,
+            "!=" => (left != right) as u8 as f64,
+            _ => return Err(AsmError::at(token, format!("unknown operator `{token}`"))),
+        };
+
+        Ok(value)
+    }
+
+    fn term(&mut self) -> Result<f64, AsmError> {
+        let token = self.next()?.clone();
+
+        let unary: Option<fn(f64) -> f64> = match token.text.as_str() {
+            "-" => Some(|x| -x),
+            "~" => Some(|x| !(x as i64) as f64),
+            "!" => Some(|x| (x == 0.0) as u8 as f64),
+            "sin" => Some(f64::sin),
+            "cos" => Some(f64::cos),
+            "tan" => Some(f64::tan),
+            "exp" => Some(f64::exp),
+            "log" => Some(f64::ln),
+            "abs" => Some(f64::abs),
+            "sqrt" => Some(f64::sqrt),
+            "sign" => Some(f64::signum),
+            "ceil" => Some(f64::ceil),
+            "floor" => Some(f64::floor),
+            _ => None,
+        };
+        if let Some(unary) = unary {
+            return Ok(unary(self.term()?));
+        }
+
+        match token.text.as_str() {
+            "(" => {
+                let value = self.expression()?;
+                match self.next()? {
+                    token if token.text == ")" => Ok(value),
+                    token => Err(AsmError::at(token, "expected `)`")),
+                }
+            }
+            "PI" => Ok(consts::PI),
+            "E" => Ok(consts::E),
+            text => parse_number(text)
+                .map(|n| n as f64)
+                .or_else(|| (self.lookup)(text))
+                .ok_or_else(|| AsmError::at(&token, format!("unknown value `{token}`"))),
+        }
+    }
+}
+
+/// Decimal, `0x` hex or `0b` binary number, optionally negative
+pub fn parse_number(text: &str) -> Option<i64> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text),
+    };
+
+    let value = if let Some(hex) = digits.strip_prefix("0x") {
+        i64::from_str_radix(hex, 16).ok()?
+    } else if let Some(binary) = digits.strip_prefix("0b") {
+        i64::from_str_radix(binary, 2).ok()?
+    } else if digits.starts_with(|c: char| c.is_ascii_digit()) {
+        digits.parse().ok()?
+    } else {
+        return None;
+    };
+
+    Some(if negative { -value } else { value })
+}
@@ -0,0 +1,947 @@
+use std::{
+    collections::{
+        HashMap,
+        VecDeque,
+    },
+    error::Error,
+    fmt,
+};
+
+use crate::chip8::{
+    Instruction,
+    PROGRAM_START,
+};
+
+use self::{
+    calc::parse_number,
+    token::{
+        tokenize,
+        Token,
+    },
+};
+
+mod calc;
+mod tests;
+mod token;
+
+/// Highest address a program can reach
+const ADDRESS_END: usize = 0x10000;
+
+/// Macro expansions before the assembler gives up on recursion
+const MAX_EXPANSIONS: usize = 10_000;
+
+/// Assemble Octo source into a ROM image loaded at 0x200.
+///
+/// Supported are labels, `:const`, `:alias`, `:macro`, `:calc`, `:byte`,
+/// `:org`, `:call`, `:unpack`, `:next`, the `if ... then`, `if ... begin
+/// ... else ... end` and `loop ... while ... again` forms, and the
+/// CHIP-8, SUPER-CHIP and XO-CHIP instructions. `:breakpoint` and
+/// `:monitor` are accepted and ignored.
+///
+/// Labels may be used before they are defined. If there is a `main` label
+/// and it isn't the first thing in the program, a jump to it is put at
+/// 0x200.
+pub fn assemble(source: &str) -> Result<Vec<u8>, AsmError> {
+    Assembler::new(source).assemble()
+}
+
+/// Error at a line and column of the source
+#[derive(Debug, PartialEq, Eq)]
+pub struct AsmError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+struct Assembler {
+    tokens: VecDeque<Token>,
+    /// Last token taken, where errors at the end of the source point
+    last: Token,
+    rom: Vec<u8>,
+    here: usize,
+    labels: HashMap<String, usize>,
+    constants: HashMap<String, f64>,
+    aliases: HashMap<String, usize>,
+    macros: HashMap<String, Macro>,
+    expansions: usize,
+    fixups: Vec<Fixup>,
+    /// Open `if` and `loop` blocks, innermost last
+    blocks: Vec<Block>,
+}
+
+struct Macro {
+    params: Vec<String>,
+    body: Vec<Token>,
+}
+
+/// Address operand to fill in once its label is defined
+struct Fixup {
+    address: usize,
+    kind: FixupKind,
+    label: Token,
+}
+
+enum FixupKind {
+    /// Low 12 bits of the opcode at the address
+    Nnn,
+    /// Word at the address
+    Long,
+    /// `:unpack` pair of `LD Vx, byte` with the nibble on top of the
+    /// address
+    Unpack(u8),
+}
+
+enum Block {
+    /// Jump over the `if` body
+    If { jump: usize, token: Token },
+    /// Jump over the `else` body
+    Else { jump: usize, token: Token },
+    /// Start of a loop and the jumps of its `while`s
+    Loop {
+        start: usize,
+        exits: Vec<usize>,
+        token: Token,
+    },
+}
+
+/// `if` and `while` condition
+enum Condition {
+    /// Skip opcode that skips when the condition holds, and the one that
+    /// skips when it doesn't
+    Skip(u16, u16),
+    /// Opcodes setting VF to 1 or 0, and the VF value meaning true
+    Flag(Vec<u16>, u8),
+}
+
+impl AsmError {
+    pub fn new(line: usize, column: usize, message: impl Into<String>) -> Self {
+        let message = message.into();
+
+        Self {
+            line,
+            column,
+            message,
+        }
+    }
+
+    fn at(token: &Token, message: impl Into<String>) -> Self {
+        Self::new(token.line, token.column, message)
+    }
+}
+
+impl Assembler {
+    fn new(source: &str) -> Self {
+        let tokens: VecDeque<Token> = tokenize(source).into();
+        let last = Token {
+            text: String::new(),
+            line: 1,
+            column: 1,
+        };
+        let rom = vec![];
+        let here = PROGRAM_START;
+        let labels = HashMap::new();
+        let constants = HashMap::new();
+        let aliases = HashMap::new();
+        let macros = HashMap::new();
+        let expansions = 0;
+        let fixups = vec![];
+        let blocks = vec![];
+
+        Self {
+            tokens,
+            last,
+            rom,
+            here,
+            labels,
+            constants,
+            aliases,
+            macros,
+            expansions,
+            fixups,
+            blocks,
+        }
+    }
+
+    fn assemble(mut self) -> Result<Vec<u8>, AsmError> {
+        let defines_main = self
+            .tokens
+            .iter()
+            .zip(self.tokens.iter().skip(1))
+            .any(|(colon, name)| colon.text == ":" && name.text == "main");
+        let main_first = self.tokens.front().is_some_and(|colon| colon.text == ":")
+            && self.tokens.get(1).is_some_and(|name| name.text == "main");
+        let main_jump = defines_main && !main_first;
+        if main_jump {
+            self.here += 2;
+            self.rom.resize(2, 0);
+        }
+
+        while let Some(token) = self.tokens.pop_front() {
+            self.last = token.clone();
+            self.statement(token)?;
+        }
+
+        if let Some(block) = self.blocks.last() {
+            let (Block::If { token, .. } | Block::Else { token, .. } | Block::Loop { token, .. }) =
+                block;
+            let end = if matches!(block, Block::Loop { .. }) {
+                "again"
+            } else {
+                "end"
+            };
+            return Err(AsmError::at(token, format!("`{token}` without `{end}`")));
+        }
+        if main_jump {
+            let main = self.labels["main"];
+            self.patch_jump(PROGRAM_START, main);
+        }
+        self.resolve_fixups()?;
+
+        Ok(self.rom)
+    }
+
+    fn statement(&mut self, token: Token) -> Result<(), AsmError> {
+        match token.text.as_str() {
+            ":" => {
+                let name = self.name()?;
+                self.define_label(&name, self.here)?;
+            }
+            ":next" => {
+                // Label on the second byte of the next instruction, for
+                // self-modifying code
+                let name = self.name()?;
+                self.define_label(&name, self.here + 1)?;
+            }
+            ":const" => {
+                let name = self.name()?;
+                let value = self.value()?;
+                self.define_constant(&name, value)?;
+            }
+            ":calc" => {
+                let name = self.name()?;
+                let open = self.next()?;
+                if open.text != "{" {
+                    return Err(AsmError::at(&open, "expected `{` after the :calc name"));
+                }
+                let value = self.calc()?;
+                self.define_constant(&name, value)?;
+            }
+            ":alias" => {
+                let name = self.name()?;
+                let register = self.register()?;
+                self.aliases.insert(name.text, register);
+            }
+            ":macro" => self.define_macro()?,
+            ":byte" => {
+                let token = self.peek()?;
+                let byte = self.byte()?;
+                self.emit_bytes(&[byte], &token)?;
+            }
+            ":org" => {
+                let token = self.peek()?;
+                let address = self.value()?;
+                if !(PROGRAM_START as f64..ADDRESS_END as f64).contains(&address) {
+                    return Err(AsmError::at(&token, "address out of range"));
+                }
+                self.here = address as usize;
+            }
+            ":call" => {
+                let opcode = self.address_opcode(0x2000)?;
+                self.emit(opcode, &token)?;
+            }
+            ":unpack" => {
+                let nibble = self.nibble()?;
+                let label = self.next()?;
+                let address = self.here;
+                match self.address_of(&label, 0xFFF)? {
+                    Some(value) => self.emit_unpack(nibble, value, &token)?,
+                    None => {
+                        self.emit_unpack(nibble, 0, &token)?;
+                        self.fixups.push(Fixup {
+                            address,
+                            kind: FixupKind::Unpack(nibble),
+                            label,
+                        });
+                    }
+                }
+            }
+            ":breakpoint" => {
+                self.next()?;
+            }
+            ":monitor" => {
+                self.next()?;
+                self.next()?;
+            }
+            "if" => self.if_block(&token)?,
+            "else" => match self.blocks.pop() {
+                Some(Block::If { jump, .. }) => {
+                    let else_jump = self.here;
+                    self.emit(0x1000, &token)?;
+                    self.patch_jump(jump, self.here);
+                    self.blocks.push(Block::Else {
+                        jump: else_jump,
+                        token,
+                    });
+                }
+                _ => return Err(AsmError::at(&token, "`else` without `if ... begin`")),
+            },
+            "end" => match self.blocks.pop() {
+                Some(Block::If { jump, .. } | Block::Else { jump, .. }) => {
+                    self.patch_jump(jump, self.here)
+                }
+                _ => return Err(AsmError::at(&token, "`end` without `if ... begin`")),
+            },
+            "loop" => self.blocks.push(Block::Loop {
+                start: self.here,
+                exits: vec![],
+                token,
+            }),
+            "while" => {
+                let condition = self.condition()?;
+                self.emit_skip(condition, true, &token)?;
+                let jump = self.here;
+                self.emit(0x1000, &token)?;
+                let Some(Block::Loop { exits, .. }) = self
+                    .blocks
+                    .iter_mut()
+                    .rev()
+                    .find(|block| matches!(block, Block::Loop { .. }))
+                else {
+                    return Err(AsmError::at(&token, "`while` outside of a loop"));
+                };
+                exits.push(jump);
+            }
+            "again" => match self.blocks.pop() {
+                Some(Block::Loop { start, exits, .. }) => {
+                    self.emit(0x1000 | start as u16, &token)?;
+                    for exit in exits {
+                        self.patch_jump(exit, self.here);
+                    }
+                }
+                _ => return Err(AsmError::at(&token, "`again` without `loop`")),
+            },
+            _ => {
+                let opcodes = self.instruction(&token)?;
+                for opcode in opcodes {
+                    self.emit(opcode, &token)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Opcodes of an instruction starting with `token`
+    fn instruction(&mut self, token: &Token) -> Result<Vec<u16>, AsmError> {
+        let instruction = match token.text.as_str() {
+            "clear" => Instruction::Cls,
+            "return" | ";" => Instruction::Ret,
+            "exit" => Instruction::Exit,
+            "lores" => Instruction::Low,
+            "hires" => Instruction::High,
+            "scroll-left" => Instruction::Scl,
+            "scroll-right" => Instruction::Scr,
+            "scroll-down" => Instruction::Scd(self.nibble()?),
+            "scroll-up" => Instruction::Scu(self.nibble()?),
+            "audio" => Instruction::Audio,
+            "plane" => {
+                let token = self.peek()?;
+                let n = self.nibble()?;
+                if n > 3 {
+                    return Err(AsmError::at(&token, "plane must be 0 to 3"));
+                }
+                Instruction::Plane(n)
+            }
+            "jump" => return Ok(vec![self.address_opcode(0x1000)?]),
+            "jump0" => return Ok(vec![self.address_opcode(0xB000)?]),
+            "sprite" => Instruction::Drw(self.register()?, self.register()?, self.nibble()?),
+            "bcd" => Instruction::LdB(self.register()?),
+            "saveflags" => Instruction::LdRVx(self.register()?),
+            "loadflags" => Instruction::LdVxR(self.register()?),
+            "save" | "load" => {
+                let save = token.text == "save";
+                let x = self.register()?;
+                if self.tokens.front().is_some_and(|token| token.text == "-") {
+                    self.next()?;
+                    let y = self.register()?;
+                    if save {
+                        Instruction::Save(x, y)
+                    } else {
+                        Instruction::Load(x, y)
+                    }
+                } else if save {
+                    Instruction::LdIVx(x)
+                } else {
+                    Instruction::LdVxI(x)
+                }
+            }
+            "i" => return self.i_instruction(),
+            "delay" | "buzzer" | "pitch" => {
+                self.expect(":=")?;
+                let x = self.register()?;
+                match token.text.as_str() {
+                    "delay" => Instruction::LdDtVx(x),
+                    "buzzer" => Instruction::LdStVx(x),
+                    _ => Instruction::LdPitch(x),
+                }
+            }
+            text if self.register_index(text).is_some() => {
+                return self.register_instruction(self.register_index(text).unwrap_or_default())
+            }
+            text if self.macros.contains_key(text) => {
+                self.expand_macro(token)?;
+                return Ok(vec![]);
+            }
+            text => {
+                if let Some(value) = parse_number(text).or_else(|| self.constant(text)) {
+                    let byte = byte(value, token)?;
+                    self.emit_bytes(&[byte], token)?;
+                    return Ok(vec![]);
+                }
+                if text.starts_with(':') {
+                    return Err(AsmError::at(token, format!("unknown directive `{token}`")));
+                }
+                if matches!(text, "{" | "}" | "(" | ")") {
+                    return Err(AsmError::at(token, format!("unexpected `{token}`")));
+                }
+                // Anything else is a subroutine call
+                self.tokens.push_front(token.clone());
+                return Ok(vec![self.address_opcode(0x2000)?]);
+            }
+        };
+
+        Ok(vec![code(instruction)])
+    }
+
+    fn i_instruction(&mut self) -> Result<Vec<u16>, AsmError> {
+        let operator = self.next()?;
+
+        match operator.text.as_str() {
+            "+=" => Ok(vec![code(Instruction::AddI(self.register()?))]),
+            ":=" => match self.peek()?.text.as_str() {
+                "hex" => {
+                    self.next()?;
+                    Ok(vec![code(Instruction::LdF(self.register()?))])
+                }
+                "bighex" => {
+                    self.next()?;
+                    Ok(vec![code(Instruction::LdHf(self.register()?))])
+                }
+                "long" => {
+                    self.next()?;
+                    let label = self.next()?;
+                    let address = self.here + 2;
+                    let nnnn = match self.address_of(&label, 0xFFFF)? {
+                        Some(nnnn) => nnnn,
+                        None => {
+                            self.fixups.push(Fixup {
+                                address,
+                                kind: FixupKind::Long,
+                                label,
+                            });
+                            0
+                        }
+                    };
+                    Ok(vec![0xF000, nnnn as u16])
+                }
+                _ => Ok(vec![self.address_opcode(0xA000)?]),
+            },
+            _ => Err(AsmError::at(
+                &operator,
+                format!("expected `:=` or `+=` after `i`, found `{operator}`"),
+            )),
+        }
+    }
+
+    fn register_instruction(&mut self, x: usize) -> Result<Vec<u16>, AsmError> {
+        let operator = self.next()?;
+        let operand = self.peek()?;
+        let y = self.register_index(&operand.text);
+
+        let instruction = match (operator.text.as_str(), operand.text.as_str(), y) {
+            (":=", "key", _) => Instruction::LdVxK(x),
+            (":=", "delay", _) => Instruction::LdVxDt(x),
+            (":=", "random", _) => {
+                self.next()?;
+                return Ok(vec![code(Instruction::Rnd(x, self.byte()?))]);
+            }
+            (":=", _, Some(y)) => Instruction::LdReg(x, y),
+            (":=", _, None) => return Ok(vec![code(Instruction::LdByte(x, self.byte()?))]),
+            ("+=", _, Some(y)) => Instruction::AddReg(x, y),
+            ("+=", _, None) => return Ok(vec![code(Instruction::AddByte(x, self.byte()?))]),
+            ("-=", _, Some(y)) => Instruction::Sub(x, y),
+            ("-=", _, None) => {
+                let byte = self.byte()?;
+                return Ok(vec![code(Instruction::AddByte(x, byte.wrapping_neg()))]);
+            }
+            ("=-", _, Some(y)) => Instruction::Subn(x, y),
+            ("|=", _, Some(y)) => Instruction::Or(x, y),
+            ("&=", _, Some(y)) => Instruction::And(x, y),
+            ("^=", _, Some(y)) => Instruction::Xor(x, y),
+            (">>=", _, Some(y)) => Instruction::Shr(x, y),
+            ("<<=", _, Some(y)) => Instruction::Shl(x, y),
+            ("=-" | "|=" | "&=" | "^=" | ">>=" | "<<=", _, None) => {
+                return Err(AsmError::at(
+                    &operand,
+                    format!("expected a register after `{operator}`, found `{operand}`"),
+                ))
+            }
+            _ => {
+                return Err(AsmError::at(
+                    &operator,
+                    format!("unknown register operator `{operator}`"),
+                ))
+            }
+        };
+        self.next()?;
+
+        Ok(vec![code(instruction)])
+    }
+
+    fn if_block(&mut self, token: &Token) -> Result<(), AsmError> {
+        let condition = self.condition()?;
+        let form = self.next()?;
+
+        match form.text.as_str() {
+            // The next instruction runs only when the condition holds
+            "then" => self.emit_skip(condition, false, token),
+            "begin" => {
+                self.emit_skip(condition, true, token)?;
+                let jump = self.here;
+                self.emit(0x1000, token)?;
+                self.blocks.push(Block::If {
+                    jump,
+                    token: token.clone(),
+                });
+                Ok(())
+            }
+            _ => Err(AsmError::at(
+                &form,
+                format!("expected `then` or `begin`, found `{form}`"),
+            )),
+        }
+    }
+
+    /// `vx key`, `vx -key` or `vx OP vy|byte` with `==`, `!=`, `<`, `>`,
+    /// `<=` and `>=`, the last four use VF
+    fn condition(&mut self) -> Result<Condition, AsmError> {
+        let x = self.register()?;
+        let operator = self.next()?;
+
+        if operator.text == "key" || operator.text == "-key" {
+            let (pressed, released) = (code(Instruction::Skp(x)), code(Instruction::Sknp(x)));
+            return Ok(if operator.text == "key" {
+                Condition::Skip(pressed, released)
+            } else {
+                Condition::Skip(released, pressed)
+            });
+        }
+
+        let operand = self.peek()?;
+        let y = self.register_index(&operand.text);
+        let value = match y {
+            Some(_) => {
+                self.next()?;
+                None
+            }
+            None => Some(self.byte()?),
+        };
+
+        let (equal, not_equal) = match (y, value) {
+            (Some(y), _) => (
+                code(Instruction::SeReg(x, y)),
+                code(Instruction::SneReg(x, y)),
+            ),
+            (None, Some(nn)) => (
+                code(Instruction::SeByte(x, nn)),
+                code(Instruction::SneByte(x, nn)),
+            ),
+            (None, None) => unreachable!("either a register or a byte"),
+        };
+        // VF is 1 when `a >= b`, from VF := a, VF -= b, or VF := b, VF =- a
+        // when b is a byte
+        let at_least = |a: Option<usize>, b: Option<usize>| match (a, b, value) {
+            (Some(a), Some(b), _) => vec![
+                code(Instruction::LdReg(0xF, a)),
+                code(Instruction::Sub(0xF, b)),
+            ],
+            (Some(a), None, Some(nn)) => vec![
+                code(Instruction::LdByte(0xF, nn)),
+                code(Instruction::Subn(0xF, a)),
+            ],
+            (None, Some(b), Some(nn)) => vec![
+                code(Instruction::LdByte(0xF, nn)),
+                code(Instruction::Sub(0xF, b)),
+            ],
+            _ => unreachable!("x is a register"),
+        };
+
+        let condition = match operator.text.as_str() {
+            "==" => Condition::Skip(equal, not_equal),
+            "!=" => Condition::Skip(not_equal, equal),
+            "<" => Condition::Flag(at_least(Some(x), y), 0),
+            ">=" => Condition::Flag(at_least(Some(x), y), 1),
+            ">" => Condition::Flag(at_least(y, Some(x)), 0),
+            "<=" => Condition::Flag(at_least(y, Some(x)), 1),
+            _ => {
+                return Err(AsmError::at(
+                    &operator,
+                    format!("unknown comparison `{operator}`"),
+                ))
+            }
+        };
+
+        Ok(condition)
+    }
+
+    /// Skip the next instruction when the condition is `holds`
+    fn emit_skip(
+        &mut self,
+        condition: Condition,
+        holds: bool,
+        token: &Token,
+    ) -> Result<(), AsmError> {
+        match condition {
+            Condition::Skip(when_true, when_false) => {
+                self.emit(if holds { when_true } else { when_false }, token)
+            }
+            Condition::Flag(opcodes, truth) => {
+                for opcode in opcodes {
+                    self.emit(opcode, token)?;
+                }
+                let skip = if holds {
+                    Instruction::SeByte(0xF, truth)
+                } else {
+                    Instruction::SneByte(0xF, truth)
+                };
+                self.emit(code(skip), token)
+            }
+        }
+    }
+
+    fn define_macro(&mut self) -> Result<(), AsmError> {
+        let name = self.name()?;
+        let mut params = vec![];
+        loop {
+            let token = self.next()?;
+            if token.text == "{" {
+                break;
+            }
+            params.push(token.text);
+        }
+
+        let mut body = vec![];
+        let mut depth = 0;
+        loop {
+            let token = self.next()?;
+            match token.text.as_str() {
+                "{" => depth += 1,
+                "}" if depth == 0 => break,
+                "}" => depth -= 1,
+                _ => {}
+            }
+            body.push(token);
+        }
+
+        self.macros.insert(name.text, Macro { params, body });
+        Ok(())
+    }
+
+    fn expand_macro(&mut self, token: &Token) -> Result<(), AsmError> {
+        self.expansions += 1;
+        if self.expansions > MAX_EXPANSIONS {
+            return Err(AsmError::at(
+                token,
+                "too many macro expansions, is a macro recursive?",
+            ));
+        }
+
+        let count = self.macros[&token.text].params.len();
+        let args = (0..count)
+            .map(|_| self.next())
+            .collect::<Result<Vec<Token>, AsmError>>()?;
+
+        let definition = &self.macros[&token.text];
+        let expanded: Vec<Token> = definition
+            .body
+            .iter()
+            .map(|body_token| {
+                let arg = definition
+                    .params
+                    .iter()
+                    .position(|param| *param == body_token.text);
+                let text =
+                    arg.map_or_else(|| body_token.text.clone(), |arg| args[arg].text.clone());
+                Token {
+                    text,
+                    ..body_token.clone()
+                }
+            })
+            .collect();
+        for token in expanded.into_iter().rev() {
+            self.tokens.push_front(token);
+        }
+
+        Ok(())
+    }
+
+    /// Tokens up to the closing `}` as a `:calc` expression
+    fn calc(&mut self) -> Result<f64, AsmError> {
+        let mut tokens = vec![];
+        loop {
+            let token = self.next()?;
+            if token.text == "}" {
+                break;
+            }
+            tokens.push(token);
+        }
+
+        let here = self.here as f64;
+        calc::evaluate(&tokens, |name| match name {
+            "HERE" => Some(here),
+            _ => self
+                .constants
+                .get(name)
+                .copied()
+                .or_else(|| self.labels.get(name).map(|&address| address as f64)),
+        })
+    }
+
+    fn next(&mut self) -> Result<Token, AsmError> {
+        let token = self
+            .tokens
+            .pop_front()
+            .ok_or_else(|| AsmError::at(&self.last, "unexpected end of source"))?;
+        self.last = token.clone();
+        Ok(token)
+    }
+
+    fn peek(&self) -> Result<Token, AsmError> {
+        self.tokens
+            .front()
+            .cloned()
+            .ok_or_else(|| AsmError::at(&self.last, "unexpected end of source"))
+    }
+
+    fn expect(&mut self, text: &str) -> Result<(), AsmError> {
+        let token = self.next()?;
+        if token.text != text {
+            return Err(AsmError::at(
+                &token,
+                format!("expected `{text}`, found `{token}`"),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Name of a new label, constant, alias or macro
+    fn name(&mut self) -> Result<Token, AsmError> {
+        let token = self.next()?;
+        let reserved = parse_number(&token.text).is_some()
+            || self.register_index(&token.text).is_some()
+            || token.text.starts_with(':')
+            || matches!(token.text.as_str(), "{" | "}" | "(" | ")");
+        if reserved {
+            return Err(AsmError::at(
+                &token,
+                format!("`{token}` can't be used as a name"),
+            ));
+        }
+        Ok(token)
+    }
+
+    fn define_label(&mut self, name: &Token, address: usize) -> Result<(), AsmError> {
+        if self.labels.contains_key(&name.text) || self.constants.contains_key(&name.text) {
+            return Err(AsmError::at(name, format!("`{name}` is already defined")));
+        }
+        self.labels.insert(name.text.clone(), address);
+        Ok(())
+    }
+
+    fn define_constant(&mut self, name: &Token, value: f64) -> Result<(), AsmError> {
+        if self.labels.contains_key(&name.text) {
+            return Err(AsmError::at(name, format!("`{name}` is already a label")));
+        }
+        self.constants.insert(name.text.clone(), value);
+        Ok(())
+    }
+
+    /// Integer value of a constant, fractions round down like in Octo
+    fn constant(&self, name: &str) -> Option<i64> {
+        self.constants.get(name).map(|value| value.floor() as i64)
+    }
+
+    fn register_index(&self, text: &str) -> Option<usize> {
+        if let Some(&x) = self.aliases.get(text) {
+            return Some(x);
+        }
+        let digit = text.strip_prefix(['v', 'V'])?;
+        if digit.len() != 1 {
+            return None;
+        }
+        usize::from_str_radix(digit, 16).ok()
+    }
+
+    fn register(&mut self) -> Result<usize, AsmError> {
+        let token = self.next()?;
+        self.register_index(&token.text)
+            .ok_or_else(|| AsmError::at(&token, format!("expected a register, found `{token}`")))
+    }
+
+    /// Number, constant, defined label or `{ calc }`
+    fn value(&mut self) -> Result<f64, AsmError> {
+        let token = self.next()?;
+        if token.text == "{" {
+            return self.calc();
+        }
+
+        parse_number(&token.text)
+            .map(|n| n as f64)
+            .or_else(|| self.constants.get(&token.text).copied())
+            .or_else(|| self.labels.get(&token.text).map(|&address| address as f64))
+            .ok_or_else(|| AsmError::at(&token, format!("expected a value, found `{token}`")))
+    }
+
+    fn byte(&mut self) -> Result<u8, AsmError> {
+        let token = self.peek()?;
+        let value = self.value()?;
+        byte(value.floor() as i64, &token)
+    }
+
+    fn nibble(&mut self) -> Result<u8, AsmError> {
+        let token = self.peek()?;
+        let value = self.value()?.floor() as i64;
+        if !(0..=0xF).contains(&value) {
+            return Err(AsmError::at(
+                &token,
+                format!("{value} doesn't fit in a nibble"),
+            ));
+        }
+        Ok(value as u8)
+    }
+
+    /// Value of an address operand up to `max`, `None` for a label that
+    /// isn't defined yet
+    fn address_of(&mut self, token: &Token, max: usize) -> Result<Option<usize>, AsmError> {
+        let value = if token.text == "{" {
+            self.calc()?.floor() as i64
+        } else if let Some(value) = parse_number(&token.text).or_else(|| self.constant(&token.text))
+        {
+            value
+        } else if let Some(&address) = self.labels.get(&token.text) {
+            address as i64
+        } else {
+            return Ok(None);
+        };
+
+        if !(0..=max as i64).contains(&value) {
+            return Err(AsmError::at(
+                token,
+                format!("address {value:#X} out of range"),
+            ));
+        }
+        Ok(Some(value as usize))
+    }
+
+    /// `high` with the next token as its 12 bit address
+    fn address_opcode(&mut self, high: u16) -> Result<u16, AsmError> {
+        let label = self.next()?;
+        match self.address_of(&label, 0xFFF)? {
+            Some(nnn) => Ok(high | nnn as u16),
+            None => {
+                self.fixups.push(Fixup {
+                    address: self.here,
+                    kind: FixupKind::Nnn,
+                    label,
+                });
+                Ok(high)
+            }
+        }
+    }
+
+    fn emit(&mut self, opcode: u16, token: &Token) -> Result<(), AsmError> {
+        self.emit_bytes(&opcode.to_be_bytes(), token)
+    }
+
+    fn emit_bytes(&mut self, bytes: &[u8], token: &Token) -> Result<(), AsmError> {
+        if self.here + bytes.len() > ADDRESS_END {
+            return Err(AsmError::at(token, "program doesn't fit in memory"));
+        }
+
+        let offset = self.here - PROGRAM_START;
+        if self.rom.len() < offset + bytes.len() {
+            self.rom.resize(offset + bytes.len(), 0);
+        }
+        self.rom[offset..offset + bytes.len()].copy_from_slice(bytes);
+        self.here += bytes.len();
+        Ok(())
+    }
+
+    /// V0 and V1 loaded with the nibble and a 12 bit address
+    fn emit_unpack(&mut self, nibble: u8, address: usize, token: &Token) -> Result<(), AsmError> {
+        let high = (nibble << 4) | (address >> 8) as u8;
+        self.emit(code(Instruction::LdByte(0, high)), token)?;
+        self.emit(code(Instruction::LdByte(1, address as u8)), token)
+    }
+
+    /// Point the jump at `at` to `target`
+    fn patch_jump(&mut self, at: usize, target: usize) {
+        let offset = at - PROGRAM_START;
+        let opcode = 0x1000 | (target as u16 & 0xFFF);
+        self.rom[offset..offset + 2].copy_from_slice(&opcode.to_be_bytes());
+    }
+
+    fn resolve_fixups(&mut self) -> Result<(), AsmError> {
+        for fixup in &self.fixups {
+            let address = *self.labels.get(&fixup.label.text).ok_or_else(|| {
+                AsmError::at(&fixup.label, format!("undefined label `{}`", fixup.label))
+            })?;
+            let offset = fixup.address - PROGRAM_START;
+
+            match fixup.kind {
+                FixupKind::Nnn | FixupKind::Unpack(_) if address > 0xFFF => {
+                    return Err(AsmError::at(
+                        &fixup.label,
+                        format!("`{}` at {address:#X} is out of 12 bit range", fixup.label),
+                    ))
+                }
+                FixupKind::Nnn => {
+                    self.rom[offset] |= (address >> 8) as u8;
+                    self.rom[offset + 1] = address as u8;
+                }
+                FixupKind::Long => {
+                    self.rom[offset..offset + 2].copy_from_slice(&(address as u16).to_be_bytes());
+                }
+                FixupKind::Unpack(nibble) => {
+                    self.rom[offset + 1] = (nibble << 4) | (address >> 8) as u8;
+                    self.rom[offset + 3] = address as u8;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn code(instruction: Instruction) -> u16 {
+    instruction.encode().code()
+}
+
+/// Byte of a value from -128 to 255
+fn byte(value: i64, token: &Token) -> Result<u8, AsmError> {
+    if !(-128..=255).contains(&value) {
+        return Err(AsmError::at(
+            token,
+            format!("{value} doesn't fit in a byte"),
+        ));
+    }
+    Ok(value as u8)
+}
+
+impl fmt::Display for AsmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl Error for AsmError {}
@@ -0,0 +1,305 @@
+#[cfg(test)]
+mod assembler {
+    use crate::{
+        asm::{
+            assemble,
+            AsmError,
+        },
+        chip8::PROGRAM_START,
+        disasm::{
+            Disassembly,
+            Syntax,
+        },
+        Chip8,
+    };
+
+    #[test]
+    fn instructions() {
+        let source = "
+            clear  v1 := 0x2A  v1 += 1  v1 -= 1  v2 := v1  v2 |= v1  v2 &= v1
+            v2 ^= v1  v2 += v1  v2 -= v1  v2 >>= v1  v2 =- v1  v2 <<= v1
+            i := 0x300  i += v3  i := hex v4  i := bighex v4  v5 := random 0x0F
+            sprite v6 v7 15  bcd v8  save v9  load v9  v0 := delay  v0 := key
+            delay := v0  buzzer := v0  jump0 0x400  return
+        ";
+
+        assert_eq!(
+            assemble(source).unwrap(),
+            [
+                0x00, 0xE0, 0x61, 0x2A, 0x71, 0x01, 0x71, 0xFF, 0x82, 0x10, 0x82, 0x11, 0x82, 0x12,
+                0x82, 0x13, 0x82, 0x14, 0x82, 0x15, 0x82, 0x16, 0x82, 0x17, 0x82, 0x1E, 0xA3, 0x00,
+                0xF3, 0x1E, 0xF4, 0x29, 0xF4, 0x30, 0xC5, 0x0F, 0xD6, 0x7F, 0xF8, 0x33, 0xF9, 0x55,
+                0xF9, 0x65, 0xF0, 0x07, 0xF0, 0x0A, 0xF0, 0x15, 0xF0, 0x18, 0xB4, 0x00, 0x00, 0xEE,
+            ]
+        );
+    }
+
+    #[test]
+    fn super_chip_and_xo_chip() {
+        let source = "
+            hires lores scroll-down 4 scroll-up 2 scroll-left scroll-right exit
+            saveflags v3 loadflags v3 plane 3 audio pitch := v1
+            save v1 - v4 load v2 - v3 i := long 0x1234
+        ";
+
+        assert_eq!(
+            assemble(source).unwrap(),
+            [
+                0x00, 0xFF, 0x00, 0xFE, 0x00, 0xC4, 0x00, 0xD2, 0x00, 0xFC, 0x00, 0xFB, 0x00, 0xFD,
+                0xF3, 0x75, 0xF3, 0x85, 0xF3, 0x01, 0xF0, 0x02, 0xF1, 0x3A, 0x51, 0x42, 0x52, 0x33,
+                0xF0, 0x00, 0x12, 0x34,
+            ]
+        );
+    }
+
+    #[test]
+    fn labels_and_calls() {
+        let source = "
+            : main
+                draw
+                jump main
+            : draw
+                i := sprite
+                sprite v0 v0 1
+            ;
+            : sprite 0xFF
+        ";
+
+        assert_eq!(
+            assemble(source).unwrap(),
+            [0x22, 0x04, 0x12, 0x00, 0xA2, 0x0A, 0xD0, 0x01, 0x00, 0xEE, 0xFF]
+        );
+    }
+
+    #[test]
+    fn main_not_first() {
+        let source = ": data 0x01 0x02 : main jump main";
+
+        assert_eq!(
+            assemble(source).unwrap(),
+            [0x12, 0x04, 0x01, 0x02, 0x12, 0x04]
+        );
+    }
+
+    #[test]
+    fn constants_aliases_and_calc() {
+        let source = "
+            :const SPEED 3
+            :alias x v4
+            :calc DOUBLE { SPEED * 2 + 1 }
+            :calc ROW { 8 * ( 1 + 1 ) }
+            x := SPEED
+            x += DOUBLE
+            x := ROW
+            :byte { 0xF0 >> 4 }
+            :byte -1
+        ";
+
+        // No precedence, right to left: 3 * (2 + 1)
+        assert_eq!(
+            assemble(source).unwrap(),
+            [0x64, 0x03, 0x74, 0x09, 0x64, 0x10, 0x0F, 0xFF]
+        );
+    }
+
+    #[test]
+    fn macros() {
+        let source = "
+            :macro swap A B { vf := A A := B B := vf }
+            swap v1 v2
+        ";
+
+        assert_eq!(
+            assemble(source).unwrap(),
+            [0x8F, 0x10, 0x81, 0x20, 0x82, 0xF0]
+        );
+    }
+
+    #[test]
+    fn if_then() {
+        let source = "
+            if v0 == 5 then v1 := 1
+            if v0 != v2 then v1 := 2
+            if v3 key then v1 := 3
+            if v3 -key then v1 := 4
+        ";
+
+        assert_eq!(
+            assemble(source).unwrap(),
+            [
+                0x40, 0x05, 0x61, 0x01, 0x50, 0x20, 0x61, 0x02, 0xE3, 0xA1, 0x61, 0x03, 0xE3, 0x9E,
+                0x61, 0x04,
+            ]
+        );
+    }
+
+    #[test]
+    fn comparisons_use_vf() {
+        let source = "if v1 < v2 then v0 := 1  if v1 >= 10 then v0 := 2";
+
+        assert_eq!(
+            assemble(source).unwrap(),
+            [
+                // vf := v1, vf -= v2, skip unless vf == 0
+                0x8F, 0x10, 0x8F, 0x25, 0x4F, 0x00, 0x60, 0x01,
+                // vf := 10, vf =- v1, skip unless vf == 1
+                0x6F, 0x0A, 0x8F, 0x17, 0x4F, 0x01, 0x60, 0x02,
+            ]
+        );
+    }
+
+    /// V2 after running `if v0 <operator> <right> then v2 := 1` with V0 and
+    /// V1 set to `a` and `b`
+    fn comparison_result(a: u8, operator: &str, right: &str, b: u8) -> u8 {
+        let source = format!("v0 := {a} v1 := {b} if v0 {operator} {right} then v2 := 1");
+        let rom = assemble(&source).unwrap();
+        let mut chip8 = Chip8::new();
+        chip8.load_rom(&rom).unwrap();
+        while chip8.pc() < PROGRAM_START + rom.len() {
+            chip8.step().unwrap();
+        }
+
+        chip8.v()[2]
+    }
+
+    #[test]
+    fn comparisons_run() {
+        for operator in ["==", "!=", "<", ">", "<=", ">="] {
+            for (a, b) in [(3, 5), (5, 5), (5, 3), (0, 255), (255, 0)] {
+                let expected = match operator {
+                    "==" => a == b,
+                    "!=" => a != b,
+                    "<" => a < b,
+                    ">" => a > b,
+                    "<=" => a <= b,
+                    _ => a >= b,
+                } as u8;
+                let b_text = b.to_string();
+                assert_eq!(
+                    comparison_result(a, operator, "v1", b),
+                    expected,
+                    "{a} {operator} {b}"
+                );
+                assert_eq!(
+                    comparison_result(a, operator, &b_text, b),
+                    expected,
+                    "{a} {operator} {b} as a byte"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn if_begin_else_end() {
+        let source = "
+            if v0 == 0 begin
+                v1 := 1
+            else
+                v1 := 2
+            end
+        ";
+
+        assert_eq!(
+            assemble(source).unwrap(),
+            [0x30, 0x00, 0x12, 0x08, 0x61, 0x01, 0x12, 0x0A, 0x61, 0x02]
+        );
+    }
+
+    #[test]
+    fn loops() {
+        let source = "
+            loop
+                v0 += 1
+                while v0 != 10
+            again
+        ";
+
+        assert_eq!(
+            assemble(source).unwrap(),
+            [0x70, 0x01, 0x40, 0x0A, 0x12, 0x08, 0x12, 0x00]
+        );
+    }
+
+    #[test]
+    fn unpack_org_and_next() {
+        let source = "
+            :unpack 0xA target
+            : patch
+            :next operand
+            v2 := 0
+            :org 0x210
+            : target 0xFF
+        ";
+
+        let rom = assemble(source).unwrap();
+
+        assert_eq!(rom[..6], [0x60, 0xA2, 0x61, 0x10, 0x62, 0x00]);
+        assert_eq!(rom.len(), 0x11);
+    }
+
+    #[test]
+    fn round_trip_through_the_disassembler() {
+        let rom = [
+            0x22, 0x08, 0x61, 0x02, 0xA2, 0x0E, 0x12, 0x04, 0x30, 0x00, 0x00, 0xEE, 0xF0, 0x00,
+            0x02, 0x0E, 0xFF, 0x81,
+        ];
+        let source = Disassembly::new(&rom).render(Syntax::Octo);
+
+        assert_eq!(assemble(&source).unwrap(), rom);
+    }
+
+    #[test]
+    fn errors() {
+        let error = |source: &str| assemble(source).unwrap_err();
+
+        assert_eq!(
+            error("clear\n  v1 := 256"),
+            AsmError::new(2, 9, "256 doesn't fit in a byte")
+        );
+        assert_eq!(
+            error("jump nowhere"),
+            AsmError::new(1, 6, "undefined label `nowhere`")
+        );
+        assert_eq!(
+            error("  loop\nv0 += 1"),
+            AsmError::new(1, 3, "`loop` without `again`")
+        );
+        assert_eq!(
+            error("v0 := 1 # comment\nv0 :="),
+            AsmError::new(2, 4, "unexpected end of source")
+        );
+        assert_eq!(
+            error("v1 ?= v2"),
+            AsmError::new(1, 4, "unknown register operator `?=`")
+        );
+        assert_eq!(
+            error(":const 1 2"),
+            AsmError::new(1, 8, "`1` can't be used as a name")
+        );
+        assert_eq!(
+            error(": a : a"),
+            AsmError::new(1, 7, "`a` is already defined")
+        );
+        assert_eq!(
+            error(":include x"),
+            AsmError::new(1, 1, "unknown directive `:include`")
+        );
+        assert_eq!(
+            error(":calc X { 1 + }"),
+            AsmError::new(1, 13, "unfinished expression")
+        );
+        assert_eq!(
+            error(":calc big { 1 << 70 }"),
+            AsmError::new(1, 15, "can't shift by 70, expected 0 to 63")
+        );
+        assert_eq!(
+            error(":calc X { 256 >> -1 }"),
+            AsmError::new(1, 15, "can't shift by -1, expected 0 to 63")
+        );
+        assert_eq!(
+            error("else"),
+            AsmError::new(1, 1, "`else` without `if ... begin`")
+        );
+        assert_eq!(AsmError::new(3, 7, "oops").to_string(), "3:7: oops");
+    }
+}
@@ -0,0 +1,56 @@
+use std::fmt;
+
+/// Word of Octo source with its 1 based position
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Token {
+    pub text: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// Split source into whitespace separated words.
+///
+/// `#` starts a comment up to the end of the line. Braces and parentheses
+/// are words on their own so `{1 + 2}` needs no spaces.
+pub fn tokenize(source: &str) -> Vec<Token> {
+    let mut tokens = vec![];
+
+    for (line, text) in source.lines().enumerate() {
+        let text = text.split('#').next().unwrap_or_default();
+        let mut start = None;
+
+        for (column, c) in text.chars().enumerate() {
+            let single = matches!(c, '{' | '}' | '(' | ')');
+            if c.is_whitespace() || single {
+                if let Some(start) = start.take() {
+                    tokens.push(token(text, line, start, column));
+                }
+                if single {
+                    tokens.push(token(text, line, column, column + 1));
+                }
+            } else if start.is_none() {
+                start = Some(column);
+            }
+        }
+        if let Some(start) = start {
+            tokens.push(token(text, line, start, text.chars().count()));
+        }
+    }
+
+    tokens
+}
+
+/// Token of the characters `start..end` of `text`
+fn token(text: &str, line: usize, start: usize, end: usize) -> Token {
+    Token {
+        text: text.chars().skip(start).take(end - start).collect(),
+        line: line + 1,
+        column: start + 1,
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
@@ -0,0 +1,72 @@
+use std::{
+    error::Error,
+    fmt,
+    io,
+};
+
+use super::registers::Registers;
+
+/// Everything that can go wrong while loading or running a program
+#[derive(Debug)]
+pub enum Chip8Error {
+    /// ROM doesn't fit into memory after the program start
+    RomTooLarge { size: usize, max: usize },
+    /// Reading the ROM failed
+    Io(io::Error),
+    /// `2NNN` was called with every stack level in use
+    StackOverflow { pc: usize },
+    /// `00EE` was executed with an empty stack
+    StackUnderflow { pc: usize },
+    /// Instruction at `pc` accessed memory past its end
+    MemoryOutOfRange { pc: usize, address: usize },
+    /// Program counter left memory
+    InvalidPc { pc: usize },
+    /// Opcode without a known instruction under
+    /// [`UnknownOpcodePolicy::Halt`](super::UnknownOpcodePolicy::Halt)
+    UnknownOpcode {
+        pc: usize,
+        opcode: u16,
+        registers: Box<Registers>,
+    },
+}
+
+impl fmt::Display for Chip8Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RomTooLarge { size, max } => {
+                write!(
+                    f,
+                    "ROM is {size} bytes, at most {max} bytes fit into memory"
+                )
+            }
+            Self::Io(err) => write!(f, "Failed to read ROM: {err}"),
+            Self::StackOverflow { pc } => write!(f, "Stack overflow at {pc:#05X}"),
+            Self::StackUnderflow { pc } => write!(f, "Stack underflow at {pc:#05X}"),
+            Self::MemoryOutOfRange { pc, address } => write!(
+                f,
+                "Instruction at {pc:#05X} accessed memory out of range at {address:#X}"
+            ),
+            Self::InvalidPc { pc } => write!(f, "Program counter out of memory: {pc:#X}"),
+            Self::UnknownOpcode {
+                pc,
+                opcode,
+                registers,
+            } => write!(f, "Unknown opcode {opcode:04X} at {pc:#05X}\n{registers}"),
+        }
+    }
+}
+
+impl Error for Chip8Error {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Chip8Error {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
@@ -1,4 +1,38 @@
-pub fn load_font(buf: &mut [u8]) {
+use std::{
+    fmt,
+    str::FromStr,
+};
+
+/// Address of the 4x5 hex digit sprites
+pub const FONT_START: usize = 0x000;
+/// Bytes per small font sprite
+pub const FONT_SPRITE_LEN: usize = 5;
+/// Address of the SUPER-CHIP 8x10 hex digit sprites
+pub const BIG_FONT_START: usize = 0x050;
+/// Bytes per big font sprite
+pub const BIG_FONT_SPRITE_LEN: usize = 10;
+
+/// Built-in hex digit sprites, the glyphs differ between interpreters
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Font {
+    /// Small digits of the COSMAC VIP interpreter ROM
+    CosmacVip,
+    /// Small digits of CHIP-48, big digits 0 to 9 only like SUPER-CHIP
+    SuperChip,
+    /// CHIP-48 small digits and big digits 0 to F, as in Octo
+    #[default]
+    Octo,
+}
+
+pub fn load_font(buf: &mut [u8], font: Font) {
+    const VIP_FONT: [u8; 80] = [
+        0xF0, 0x90, 0x90, 0x90, 0xF0, 0x60, 0x20, 0x20, 0x20, 0x70, 0xF0, 0x10, 0xF0, 0x80, 0xF0,
+        0xF0, 0x10, 0x70, 0x10, 0xF0, 0xA0, 0xA0, 0xF0, 0x20, 0x20, 0xF0, 0x80, 0xF0, 0x10, 0xF0,
+        0xF0, 0x80, 0xF0, 0x90, 0xF0, 0xF0, 0x10, 0x10, 0x10, 0x10, 0xF0, 0x90, 0xF0, 0x90, 0xF0,
+        0xF0, 0x90, 0xF0, 0x10, 0xF0, 0xF0, 0x90, 0xF0, 0x90, 0x90, 0xF0, 0x50, 0x70, 0x50, 0xF0,
+        0xF0, 0x80, 0x80, 0x80, 0xF0, 0xF0, 0x50, 0x50, 0x50, 0xF0, 0xF0, 0x80, 0xF0, 0x80, 0xF0,
+        0xF0, 0x80, 0xF0, 0x80, 0x80,
+    ];
     const FONT: [u8; 80] = [
         0xF0, 0x90, 0x90, 0x90, 0xF0, 0x20, 0x60, 0x20, 0x20, 0x70, 0xF0, 0x10, 0xF0, 0x80, 0xF0,
         0xF0, 0x10, 0xF0, 0x10, 0xF0, 0x90, 0x90, 0xF0, 0x10, 0x10, 0xF0, 0x80, 0xF0, 0x10, 0xF0,
@@ -7,6 +41,59 @@ pub fn load_font(buf: &mut [u8]) {
         0xF0, 0x80, 0x80, 0x80, 0xF0, 0xE0, 0x90, 0x90, 0x90, 0xE0, 0xF0, 0x80, 0xF0, 0x80, 0xF0,
         0xF0, 0x80, 0xF0, 0x80, 0x80,
     ];
+    const BIG_FONT: [u8; 160] = [
+        0x3C, 0x7E, 0xE7, 0xC3, 0xC3, 0xC3, 0xC3, 0xE7, 0x7E, 0x3C, // 0
+        0x18, 0x38, 0x58, 0x18, 0x18, 0x18, 0x18, 0x18, 0x18, 0x3C, // 1
+        0x3E, 0x7F, 0xC3, 0x06, 0x0C, 0x18, 0x30, 0x60, 0xFF, 0xFF, // 2
+        0x3C, 0x7E, 0xC3, 0x03, 0x0E, 0x0E, 0x03, 0xC3, 0x7E, 0x3C, // 3
+        0x06, 0x0E, 0x1E, 0x36, 0x66, 0xC6, 0xFF, 0xFF, 0x06, 0x06, // 4
+        0xFF, 0xFF, 0xC0, 0xC0, 0xFC, 0xFE, 0x03, 0xC3, 0x7E, 0x3C, // 5
+        0x3E, 0x7C, 0xE0, 0xC0, 0xFC, 0xFE, 0xC3, 0xC3, 0x7E, 0x3C, // 6
+        0xFF, 0xFF, 0x03, 0x06, 0x0C, 0x18, 0x30, 0x60, 0x60, 0x60, // 7
+        0x3C, 0x7E, 0xC3, 0xC3, 0x7E, 0x7E, 0xC3, 0xC3, 0x7E, 0x3C, // 8
+        0x3C, 0x7E, 0xC3, 0xC3, 0x7F, 0x3F, 0x03, 0x03, 0x3E, 0x7C, // 9
+        0xFF, 0xFF, 0xC3, 0xC3, 0xFF, 0xFF, 0xC3, 0xC3, 0xC3, 0xC3, // A
+        0xFC, 0xFC, 0xC3, 0xC3, 0xFC, 0xFC, 0xC3, 0xC3, 0xFC, 0xFC, // B
+        0x3C, 0xFF, 0xC3, 0xC0, 0xC0, 0xC0, 0xC0, 0xC3, 0xFF, 0x3C, // C
+        0xFC, 0xFE, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xC3, 0xFE, 0xFC, // D
+        0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, // E
+        0xFF, 0xFF, 0xC0, 0xC0, 0xFF, 0xFF, 0xC0, 0xC0, 0xC0, 0xC0, // F
+    ];
+
+    let (small, big_len) = match font {
+        Font::CosmacVip => (&VIP_FONT, BIG_FONT.len()),
+        Font::SuperChip => (&FONT, 10 * BIG_FONT_SPRITE_LEN),
+        Font::Octo => (&FONT, BIG_FONT.len()),
+    };
+
+    buf[FONT_START..FONT_START + small.len()].copy_from_slice(small);
+    let big = &mut buf[BIG_FONT_START..BIG_FONT_START + BIG_FONT.len()];
+    big.fill(0);
+    big[..big_len].copy_from_slice(&BIG_FONT[..big_len]);
+}
+
+impl FromStr for Font {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cosmac-vip" => Ok(Self::CosmacVip),
+            "superchip" => Ok(Self::SuperChip),
+            "octo" => Ok(Self::Octo),
+            _ => Err(format!(
+                "unknown font `{s}`, expected cosmac-vip, superchip or octo"
+            )),
+        }
+    }
+}
 
-    buf[0..80].copy_from_slice(&FONT);
+impl fmt::Display for Font {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::CosmacVip => "cosmac-vip",
+            Self::SuperChip => "superchip",
+            Self::Octo => "octo",
+        };
+        write!(f, "{name}")
+    }
 }
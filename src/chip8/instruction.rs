@@ -0,0 +1,334 @@
+use std::fmt;
+
+use super::opcode::Opcode;
+
+/// Decoded Chip8 instruction.
+///
+/// Register operands are register numbers 0x0 to 0xF, addresses are 12 bit.
+/// The 16 bit address of [`Instruction::LdILong`] is the word following the
+/// opcode.
+/// Opcodes that don't decode to anything are kept as [`Instruction::Unknown`],
+/// so every opcode encodes back to itself.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Instruction {
+    /// `0nnn` - SYS addr
+    Sys(usize),
+    /// `00E0` - CLS
+    Cls,
+    /// `00EE` - RET
+    Ret,
+    /// `00Cn` - SCD nibble (SUPER-CHIP)
+    Scd(u8),
+    /// `00Dn` - SCU nibble (XO-CHIP)
+    Scu(u8),
+    /// `00FB` - SCR (SUPER-CHIP)
+    Scr,
+    /// `00FC` - SCL (SUPER-CHIP)
+    Scl,
+    /// `00FD` - EXIT (SUPER-CHIP)
+    Exit,
+    /// `00FE` - LOW (SUPER-CHIP)
+    Low,
+    /// `00FF` - HIGH (SUPER-CHIP)
+    High,
+    /// `1nnn` - JP addr
+    Jp(usize),
+    /// `2nnn` - CALL addr
+    Call(usize),
+    /// `3xkk` - SE Vx, byte
+    SeByte(usize, u8),
+    /// `4xkk` - SNE Vx, byte
+    SneByte(usize, u8),
+    /// `5xy0` - SE Vx, Vy
+    SeReg(usize, usize),
+    /// `5xy2` - SAVE Vx, Vy (XO-CHIP)
+    Save(usize, usize),
+    /// `5xy3` - LOAD Vx, Vy (XO-CHIP)
+    Load(usize, usize),
+    /// `6xkk` - LD Vx, byte
+    LdByte(usize, u8),
+    /// `7xkk` - ADD Vx, byte
+    AddByte(usize, u8),
+    /// `8xy0` - LD Vx, Vy
+    LdReg(usize, usize),
+    /// `8xy1` - OR Vx, Vy
+    Or(usize, usize),
+    /// `8xy2` - AND Vx, Vy
+    And(usize, usize),
+    /// `8xy3` - XOR Vx, Vy
+    Xor(usize, usize),
+    /// `8xy4` - ADD Vx, Vy
+    AddReg(usize, usize),
+    /// `8xy5` - SUB Vx, Vy
+    Sub(usize, usize),
+    /// `8xy6` - SHR Vx {, Vy}
+    Shr(usize, usize),
+    /// `8xy7` - SUBN Vx, Vy
+    Subn(usize, usize),
+    /// `8xyE` - SHL Vx {, Vy}
+    Shl(usize, usize),
+    /// `9xy0` - SNE Vx, Vy
+    SneReg(usize, usize),
+    /// `Annn` - LD I, addr
+    LdI(usize),
+    /// `Bnnn` - JP V0, addr
+    JpV0(usize),
+    /// `Cxkk` - RND Vx, byte
+    Rnd(usize, u8),
+    /// `Dxyn` - DRW Vx, Vy, nibble
+    Drw(usize, usize, u8),
+    /// `F000 nnnn` - LD I, long (XO-CHIP), the address is the next word
+    LdILong,
+    /// `Fn01` - PLANE n (XO-CHIP)
+    Plane(u8),
+    /// `F002` - AUDIO (XO-CHIP)
+    Audio,
+    /// `Ex9E` - SKP Vx
+    Skp(usize),
+    /// `ExA1` - SKNP Vx
+    Sknp(usize),
+    /// `Fx07` - LD Vx, DT
+    LdVxDt(usize),
+    /// `Fx0A` - LD Vx, K
+    LdVxK(usize),
+    /// `Fx15` - LD DT, Vx
+    LdDtVx(usize),
+    /// `Fx18` - LD ST, Vx
+    LdStVx(usize),
+    /// `Fx1E` - ADD I, Vx
+    AddI(usize),
+    /// `Fx29` - LD F, Vx
+    LdF(usize),
+    /// `Fx30` - LD HF, Vx (SUPER-CHIP)
+    LdHf(usize),
+    /// `Fx3A` - LD PITCH, Vx (XO-CHIP)
+    LdPitch(usize),
+    /// `Fx33` - LD B, Vx
+    LdB(usize),
+    /// `Fx55` - LD [I], Vx
+    LdIVx(usize),
+    /// `Fx65` - LD Vx, [I]
+    LdVxI(usize),
+    /// `Fx75` - LD R, Vx (SUPER-CHIP)
+    LdRVx(usize),
+    /// `Fx85` - LD Vx, R (SUPER-CHIP)
+    LdVxR(usize),
+    /// Opcode without a known instruction
+    Unknown(u16),
+}
+
+impl Instruction {
+    pub fn decode(opcode: Opcode) -> Self {
+        let nnn = opcode.nnn();
+        let nn = opcode.nn();
+        let n = opcode.n();
+        let x = opcode.x();
+        let y = opcode.y();
+
+        match opcode.code() & 0xF000 {
+            0x0000 => match opcode.code() {
+                0x00E0 => Self::Cls,
+                0x00EE => Self::Ret,
+                0x00C0..=0x00CF => Self::Scd(n),
+                0x00D0..=0x00DF => Self::Scu(n),
+                0x00FB => Self::Scr,
+                0x00FC => Self::Scl,
+                0x00FD => Self::Exit,
+                0x00FE => Self::Low,
+                0x00FF => Self::High,
+                _ => Self::Sys(nnn),
+            },
+            0x1000 => Self::Jp(nnn),
+            0x2000 => Self::Call(nnn),
+            0x3000 => Self::SeByte(x, nn),
+            0x4000 => Self::SneByte(x, nn),
+            0x5000 => match n {
+                0x0 => Self::SeReg(x, y),
+                0x2 => Self::Save(x, y),
+                0x3 => Self::Load(x, y),
+                _ => Self::Unknown(opcode.code()),
+            },
+            0x6000 => Self::LdByte(x, nn),
+            0x7000 => Self::AddByte(x, nn),
+            0x8000 => match n {
+                0x0 => Self::LdReg(x, y),
+                0x1 => Self::Or(x, y),
+                0x2 => Self::And(x, y),
+                0x3 => Self::Xor(x, y),
+                0x4 => Self::AddReg(x, y),
+                0x5 => Self::Sub(x, y),
+                0x6 => Self::Shr(x, y),
+                0x7 => Self::Subn(x, y),
+                0xE => Self::Shl(x, y),
+                _ => Self::Unknown(opcode.code()),
+            },
+            0x9000 if n == 0x0 => Self::SneReg(x, y),
+            0xA000 => Self::LdI(nnn),
+            0xB000 => Self::JpV0(nnn),
+            0xC000 => Self::Rnd(x, nn),
+            0xD000 => Self::Drw(x, y, n),
+            0xE000 => match nn {
+                0x9E => Self::Skp(x),
+                0xA1 => Self::Sknp(x),
+                _ => Self::Unknown(opcode.code()),
+            },
+            0xF000 => match nn {
+                0x00 if x == 0x0 => Self::LdILong,
+                0x01 => Self::Plane(x as u8),
+                0x02 if x == 0x0 => Self::Audio,
+                0x07 => Self::LdVxDt(x),
+                0x0A => Self::LdVxK(x),
+                0x15 => Self::LdDtVx(x),
+                0x18 => Self::LdStVx(x),
+                0x1E => Self::AddI(x),
+                0x29 => Self::LdF(x),
+                0x30 => Self::LdHf(x),
+                0x3A => Self::LdPitch(x),
+                0x33 => Self::LdB(x),
+                0x55 => Self::LdIVx(x),
+                0x65 => Self::LdVxI(x),
+                0x75 => Self::LdRVx(x),
+                0x85 => Self::LdVxR(x),
+                _ => Self::Unknown(opcode.code()),
+            },
+            _ => Self::Unknown(opcode.code()),
+        }
+    }
+
+    pub fn encode(&self) -> Opcode {
+        let nnn = |high: u16, nnn: usize| high | (nnn as u16 & 0x0FFF);
+        let xnn = |high: u16, x: usize, nn: u8| high | ((x as u16 & 0xF) << 8) | nn as u16;
+        let xyn = |high: u16, x: usize, y: usize, n: u8| {
+            high | ((x as u16 & 0xF) << 8) | ((y as u16 & 0xF) << 4) | (n as u16 & 0xF)
+        };
+
+        let code = match *self {
+            Self::Sys(addr) => nnn(0x0000, addr),
+            Self::Cls => 0x00E0,
+            Self::Ret => 0x00EE,
+            Self::Scd(n) => 0x00C0 | (n as u16 & 0xF),
+            Self::Scu(n) => 0x00D0 | (n as u16 & 0xF),
+            Self::Scr => 0x00FB,
+            Self::Scl => 0x00FC,
+            Self::Exit => 0x00FD,
+            Self::Low => 0x00FE,
+            Self::High => 0x00FF,
+            Self::Jp(addr) => nnn(0x1000, addr),
+            Self::Call(addr) => nnn(0x2000, addr),
+            Self::SeByte(x, nn) => xnn(0x3000, x, nn),
+            Self::SneByte(x, nn) => xnn(0x4000, x, nn),
+            Self::SeReg(x, y) => xyn(0x5000, x, y, 0x0),
+            Self::Save(x, y) => xyn(0x5000, x, y, 0x2),
+            Self::Load(x, y) => xyn(0x5000, x, y, 0x3),
+            Self::LdByte(x, nn) => xnn(0x6000, x, nn),
+            Self::AddByte(x, nn) => xnn(0x7000, x, nn),
+            Self::LdReg(x, y) => xyn(0x8000, x, y, 0x0),
+            Self::Or(x, y) => xyn(0x8000, x, y, 0x1),
+            Self::And(x, y) => xyn(0x8000, x, y, 0x2),
+            Self::Xor(x, y) => xyn(0x8000, x, y, 0x3),
+            Self::AddReg(x, y) => xyn(0x8000, x, y, 0x4),
+            Self::Sub(x, y) => xyn(0x8000, x, y, 0x5),
+            Self::Shr(x, y) => xyn(0x8000, x, y, 0x6),
+            Self::Subn(x, y) => xyn(0x8000, x, y, 0x7),
+            Self::Shl(x, y) => xyn(0x8000, x, y, 0xE),
+            Self::SneReg(x, y) => xyn(0x9000, x, y, 0x0),
+            Self::LdI(addr) => nnn(0xA000, addr),
+            Self::JpV0(addr) => nnn(0xB000, addr),
+            Self::Rnd(x, nn) => xnn(0xC000, x, nn),
+            Self::Drw(x, y, n) => xyn(0xD000, x, y, n),
+            Self::LdILong => 0xF000,
+            Self::Plane(n) => xnn(0xF000, n as usize, 0x01),
+            Self::Audio => 0xF002,
+            Self::Skp(x) => xnn(0xE000, x, 0x9E),
+            Self::Sknp(x) => xnn(0xE000, x, 0xA1),
+            Self::LdVxDt(x) => xnn(0xF000, x, 0x07),
+            Self::LdVxK(x) => xnn(0xF000, x, 0x0A),
+            Self::LdDtVx(x) => xnn(0xF000, x, 0x15),
+            Self::LdStVx(x) => xnn(0xF000, x, 0x18),
+            Self::AddI(x) => xnn(0xF000, x, 0x1E),
+            Self::LdF(x) => xnn(0xF000, x, 0x29),
+            Self::LdHf(x) => xnn(0xF000, x, 0x30),
+            Self::LdPitch(x) => xnn(0xF000, x, 0x3A),
+            Self::LdB(x) => xnn(0xF000, x, 0x33),
+            Self::LdIVx(x) => xnn(0xF000, x, 0x55),
+            Self::LdVxI(x) => xnn(0xF000, x, 0x65),
+            Self::LdRVx(x) => xnn(0xF000, x, 0x75),
+            Self::LdVxR(x) => xnn(0xF000, x, 0x85),
+            Self::Unknown(code) => code,
+        };
+
+        Opcode::from(code)
+    }
+}
+
+impl From<Opcode> for Instruction {
+    fn from(opcode: Opcode) -> Self {
+        Self::decode(opcode)
+    }
+}
+
+impl From<Instruction> for Opcode {
+    fn from(instruction: Instruction) -> Self {
+        instruction.encode()
+    }
+}
+
+/// Lowercase Cowgod mnemonics, e.g. `ld v1, 0x2A` or `drw v0, v1, 5`
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Sys(addr) => write!(f, "sys {addr:#05X}"),
+            Self::Cls => write!(f, "cls"),
+            Self::Ret => write!(f, "ret"),
+            Self::Scd(n) => write!(f, "scd {n}"),
+            Self::Scu(n) => write!(f, "scu {n}"),
+            Self::Scr => write!(f, "scr"),
+            Self::Scl => write!(f, "scl"),
+            Self::Exit => write!(f, "exit"),
+            Self::Low => write!(f, "low"),
+            Self::High => write!(f, "high"),
+            Self::Jp(addr) => write!(f, "jp {addr:#05X}"),
+            Self::Call(addr) => write!(f, "call {addr:#05X}"),
+            Self::SeByte(x, nn) => write!(f, "se v{x:x}, {nn:#04X}"),
+            Self::SneByte(x, nn) => write!(f, "sne v{x:x}, {nn:#04X}"),
+            Self::SeReg(x, y) => write!(f, "se v{x:x}, v{y:x}"),
+            Self::Save(x, y) => write!(f, "save v{x:x}, v{y:x}"),
+            Self::Load(x, y) => write!(f, "load v{x:x}, v{y:x}"),
+            Self::LdByte(x, nn) => write!(f, "ld v{x:x}, {nn:#04X}"),
+            Self::AddByte(x, nn) => write!(f, "add v{x:x}, {nn:#04X}"),
+            Self::LdReg(x, y) => write!(f, "ld v{x:x}, v{y:x}"),
+            Self::Or(x, y) => write!(f, "or v{x:x}, v{y:x}"),
+            Self::And(x, y) => write!(f, "and v{x:x}, v{y:x}"),
+            Self::Xor(x, y) => write!(f, "xor v{x:x}, v{y:x}"),
+            Self::AddReg(x, y) => write!(f, "add v{x:x}, v{y:x}"),
+            Self::Sub(x, y) => write!(f, "sub v{x:x}, v{y:x}"),
+            Self::Shr(x, y) => write!(f, "shr v{x:x}, v{y:x}"),
+            Self::Subn(x, y) => write!(f, "subn v{x:x}, v{y:x}"),
+            Self::Shl(x, y) => write!(f, "shl v{x:x}, v{y:x}"),
+            Self::SneReg(x, y) => write!(f, "sne v{x:x}, v{y:x}"),
+            Self::LdI(addr) => write!(f, "ld i, {addr:#05X}"),
+            Self::JpV0(addr) => write!(f, "jp v0, {addr:#05X}"),
+            Self::Rnd(x, nn) => write!(f, "rnd v{x:x}, {nn:#04X}"),
+            Self::Drw(x, y, n) => write!(f, "drw v{x:x}, v{y:x}, {n}"),
+            Self::LdILong => write!(f, "ld i, long"),
+            Self::Plane(n) => write!(f, "plane {n}"),
+            Self::Audio => write!(f, "audio"),
+            Self::Skp(x) => write!(f, "skp v{x:x}"),
+            Self::Sknp(x) => write!(f, "sknp v{x:x}"),
+            Self::LdVxDt(x) => write!(f, "ld v{x:x}, dt"),
+            Self::LdVxK(x) => write!(f, "ld v{x:x}, k"),
+            Self::LdDtVx(x) => write!(f, "ld dt, v{x:x}"),
+            Self::LdStVx(x) => write!(f, "ld st, v{x:x}"),
+            Self::AddI(x) => write!(f, "add i, v{x:x}"),
+            Self::LdF(x) => write!(f, "ld f, v{x:x}"),
+            Self::LdHf(x) => write!(f, "ld hf, v{x:x}"),
+            Self::LdPitch(x) => write!(f, "ld pitch, v{x:x}"),
+            Self::LdB(x) => write!(f, "ld b, v{x:x}"),
+            Self::LdIVx(x) => write!(f, "ld [i], v{x:x}"),
+            Self::LdVxI(x) => write!(f, "ld v{x:x}, [i]"),
+            Self::LdRVx(x) => write!(f, "ld r, v{x:x}"),
+            Self::LdVxR(x) => write!(f, "ld v{x:x}, r"),
+            Self::Unknown(code) => write!(f, "dw {code:#06X}"),
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use std::{
+    fmt,
+    str::FromStr,
+};
+
+use super::instruction::Instruction;
+
+/// Instructions a platform understands, each set extends the previous one.
+///
+/// Instructions outside the selected set are handled like unknown opcodes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum InstructionSet {
+    /// The original 35 instructions
+    Chip8,
+    /// Adds `00FD`, `00FE`, `00FF`, 16x16 `Dxy0`, `Fx30`, `Fx75` and `Fx85`
+    SuperChip10,
+    /// Adds the `00Cn`, `00FB` and `00FC` scroll instructions
+    SuperChip11,
+    /// Adds `00Dn`, `5xy2`, `5xy3`, `F000 nnnn`, `Fn01`, `F002` and `Fx3A`
+    #[default]
+    XoChip,
+}
+
+impl InstructionSet {
+    /// Whether `instruction` is part of this set
+    pub fn supports(&self, instruction: &Instruction) -> bool {
+        *self >= Self::introducing(instruction)
+    }
+
+    /// First set containing `instruction`
+    pub fn introducing(instruction: &Instruction) -> Self {
+        match instruction {
+            Instruction::Exit
+            | Instruction::Low
+            | Instruction::High
+            | Instruction::LdHf(_)
+            | Instruction::LdRVx(_)
+            | Instruction::LdVxR(_) => Self::SuperChip10,
+            Instruction::Scd(_) | Instruction::Scr | Instruction::Scl => Self::SuperChip11,
+            Instruction::Scu(_)
+            | Instruction::Save(..)
+            | Instruction::Load(..)
+            | Instruction::LdILong
+            | Instruction::Plane(_)
+            | Instruction::Audio
+            | Instruction::LdPitch(_) => Self::XoChip,
+            _ => Self::Chip8,
+        }
+    }
+}
+
+impl FromStr for InstructionSet {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "chip-8" => Ok(Self::Chip8),
+            "schip-1.0" => Ok(Self::SuperChip10),
+            "schip-1.1" => Ok(Self::SuperChip11),
+            "xo-chip" => Ok(Self::XoChip),
+            _ => Err(format!(
+                "unknown instruction set `{s}`, expected chip-8, schip-1.0, schip-1.1 or xo-chip"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for InstructionSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Chip8 => "chip-8",
+            Self::SuperChip10 => "schip-1.0",
+            Self::SuperChip11 => "schip-1.1",
+            Self::XoChip => "xo-chip",
+        };
+        write!(f, "{name}")
+    }
+}
@@ -0,0 +1,67 @@
+/// Chip8 hex keypad with 16 keys from 0x0 to 0xF
+pub struct Keypad {
+    keys: [bool; 16],
+}
+
+impl Keypad {
+    pub fn new() -> Self {
+        let keys = [false; 16];
+
+        Self { keys }
+    }
+
+    pub fn press(&mut self, key: u8) {
+        if let Some(pressed) = self.keys.get_mut(key as usize) {
+            *pressed = true;
+        }
+    }
+
+    pub fn release(&mut self, key: u8) {
+        if let Some(pressed) = self.keys.get_mut(key as usize) {
+            *pressed = false;
+        }
+    }
+
+    pub fn set_keys(&mut self, keys: [bool; 16]) {
+        self.keys = keys;
+    }
+
+    pub fn keys(&self) -> [bool; 16] {
+        self.keys
+    }
+
+    /// Keys as a bit mask, bit n is key n
+    pub fn bits(&self) -> u16 {
+        self.keys
+            .iter()
+            .enumerate()
+            .filter(|(_, &pressed)| pressed)
+            .fold(0, |bits, (key, _)| bits | 1 << key)
+    }
+
+    pub fn set_bits(&mut self, bits: u16) {
+        for (key, pressed) in self.keys.iter_mut().enumerate() {
+            *pressed = bits & (1 << key) != 0;
+        }
+    }
+
+    pub fn is_pressed(&self, key: u8) -> bool {
+        let pressed = self.keys.get(key as usize).copied().unwrap_or(false);
+        log::debug!("Key: {:X}, is {}", key, pressed);
+        pressed
+    }
+
+    /// First pressed key in hex order, if any
+    pub fn first_pressed(&self) -> Option<u8> {
+        self.keys
+            .iter()
+            .position(|&pressed| pressed)
+            .map(|key| key as u8)
+    }
+}
+
+impl Default for Keypad {
+    fn default() -> Self {
+        Self::new()
+    }
+}
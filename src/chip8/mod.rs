@@ -1,51 +1,74 @@
 use std::{
-    collections::HashSet,
+    collections::BTreeMap,
     fs::File,
     io::Read,
-    process::exit,
-    thread,
-    time::Duration,
 };
 
-use {
-    rand::{
-        prelude::ThreadRng,
-        thread_rng,
-        Rng,
+pub use self::{
+    error::Chip8Error,
+    font::Font,
+    instruction::Instruction,
+    instruction_set::InstructionSet,
+    keypad::Keypad,
+    opcode::Opcode,
+    platform::Platform,
+    quirks::Quirks,
+    registers::Registers,
+    rng::Rng,
+    screen::{
+        Screen,
+        HIRES_HEIGHT,
+        HIRES_WIDTH,
+        LORES_HEIGHT,
+        LORES_WIDTH,
     },
-    sdl2::keyboard::Keycode,
-};
-
-use sdl2::{
-    event::Event,
-    pixels::Color,
-    rect::Rect,
-    EventPump,
-    Sdl,
-};
-
-use self::{
-    hex_to_key::{
-        hex_to_key,
-        key_to_hex,
+    screen_format::ScreenFormat,
+    snapshot::Snapshot,
+    stack::{
+        Stack,
+        STACK_DEPTH,
     },
-    opcode::Opcode,
-    stack::Stack,
+    step::Step,
     timers::Timers,
+    unknown_opcode::UnknownOpcodePolicy,
 };
 
-// use self::screen::Screen;
-use super::chip8::screen::Screen;
-
-mod audio;
+mod error;
 mod font;
-mod hex_to_key;
+mod instruction;
+mod instruction_set;
+mod keypad;
 mod opcode;
+mod platform;
+mod quirks;
+mod registers;
+mod rng;
 mod screen;
+mod screen_format;
+mod snapshot;
 mod stack;
+mod step;
 mod tests;
 mod timers;
+mod unknown_opcode;
+
+/// Size of the Chip8 address space
+pub const MEMORY_SIZE: usize = 0x1000;
+/// Size of the XO-CHIP address space reachable with `F000 nnnn`
+pub const XO_MEMORY_SIZE: usize = 0x10000;
+/// Address where programs are loaded and execution starts
+pub const PROGRAM_START: usize = 0x200;
+/// Instructions executed per 60 Hz frame by [`Chip8::run_frame`]
+pub const DEFAULT_INSTRUCTIONS_PER_FRAME: usize = 10;
+/// Rate of the delay and sound timers and of the display refresh
+pub const FRAMES_PER_SECOND: u32 = 60;
 
+/// Chip8 interpreter state: memory, registers, stack, timers, screen and
+/// keypad.
+///
+/// The machine has no notion of a window or of real time. A frontend loads a
+/// ROM, feeds the keypad, calls [`Chip8::run_frame`] and presents
+/// [`Chip8::screen`] at its own pace.
 pub struct Chip8 {
     /// Chip8 was commonly implemented in 4K system
     /// First 512 bytes occupies by machine
@@ -67,7 +90,9 @@ pub struct Chip8 {
     /// │                     │
     /// │                     │
     /// └─────────────────────┘ <- 0x000 Start of Chip-8 RAM
-    memory: [u8; 1024 * 4],
+    ///
+    /// XO-CHIP extends memory up to 64K.
+    memory: Vec<u8>,
     pc: usize,
     opcode: Opcode,
     stack: Stack,
@@ -77,31 +102,69 @@ pub struct Chip8 {
     v: [u8; 16],
     screen: Screen,
     i: usize,
-    rng: ThreadRng,
+    /// Seed `rng` started from, to reproduce a run
+    seed: u64,
+    rng: Rng,
     timers: Timers,
     need_redraw: bool,
     wait_key: bool,
-    sdl_cxt: Sdl,
-    events: EventPump,
+    /// Set by the SUPER-CHIP `00FD` exit instruction
+    halted: bool,
+    /// SUPER-CHIP RPL user flags, saved and restored by `Fx75` and `Fx85`
+    rpl: [u8; 16],
+    /// XO-CHIP 1-bit audio pattern loaded by `F002`, `None` until then
+    audio_pattern: Option<[u8; 16]>,
+    /// XO-CHIP playback pitch set by `Fx3A`
+    pitch: u8,
+    keypad: Keypad,
+    instructions_per_frame: usize,
+    instruction_set: InstructionSet,
+    font: Font,
+    quirks: Quirks,
+    /// Set by `Dxyn` under [`Quirks::display_wait`] to end the frame early
+    vblank_wait: bool,
+    /// Instructions executed in the current frame
+    frame_cycles: usize,
+    unknown_opcode_policy: UnknownOpcodePolicy,
+    /// How often each unknown opcode was met
+    unknown_opcodes: BTreeMap<u16, usize>,
 }
 
 impl Chip8 {
     pub fn new() -> Self {
-        let mut memory = [0; 0x1000];
-        let pc = 0x200;
+        Self::with_memory_size(MEMORY_SIZE)
+    }
+
+    /// Machine with `memory_size` bytes of memory, between [`MEMORY_SIZE`]
+    /// and [`XO_MEMORY_SIZE`]
+    pub fn with_memory_size(memory_size: usize) -> Self {
+        let mut memory = vec![0; memory_size.clamp(MEMORY_SIZE, XO_MEMORY_SIZE)];
+        let pc = PROGRAM_START;
         let opcode = Opcode::new();
         let stack = Stack::new();
         let v = [0; 16];
         let screen = Screen::new();
         let i = 0;
-        let rng = thread_rng();
+        let seed = rand::random();
+        let rng = Rng::from_seed(seed);
         let timers = Timers::new();
         let need_redraw = false;
         let wait_key = false;
-        let sdl_cxt = sdl2::init().unwrap();
-        let events = sdl_cxt.event_pump().unwrap();
+        let halted = false;
+        let rpl = [0; 16];
+        let audio_pattern = None;
+        let pitch = 64;
+        let keypad = Keypad::new();
+        let instructions_per_frame = DEFAULT_INSTRUCTIONS_PER_FRAME;
+        let instruction_set = InstructionSet::default();
+        let font = Font::default();
+        let quirks = Quirks::default();
+        let vblank_wait = false;
+        let frame_cycles = 0;
+        let unknown_opcode_policy = UnknownOpcodePolicy::default();
+        let unknown_opcodes = BTreeMap::new();
 
-        font::load_font(&mut memory);
+        font::load_font(&mut memory, font);
 
         Self {
             memory,
@@ -111,12 +174,24 @@ impl Chip8 {
             v,
             screen,
             i,
+            seed,
             rng,
             timers,
             need_redraw,
             wait_key,
-            sdl_cxt,
-            events,
+            halted,
+            rpl,
+            audio_pattern,
+            pitch,
+            keypad,
+            instructions_per_frame,
+            instruction_set,
+            font,
+            quirks,
+            vblank_wait,
+            frame_cycles,
+            unknown_opcode_policy,
+            unknown_opcodes,
         }
     }
 }
@@ -128,191 +203,546 @@ impl Default for Chip8 {
 }
 
 impl Chip8 {
-    pub fn load_from_file(&mut self, program: &mut File) {
-        let mem = &mut self.memory[0x200..];
-        program.read(mem).unwrap();
+    /// Machine set up like `platform`: memory size, instruction set, font,
+    /// quirks and speed
+    pub fn with_platform(platform: Platform) -> Self {
+        let mut chip8 = Self::with_memory_size(platform.memory_size());
+        chip8.set_instruction_set(platform.instruction_set());
+        chip8.set_font(platform.font());
+        chip8.set_quirks(platform.quirks());
+        chip8.set_instructions_per_frame(platform.instructions_per_frame());
+
+        chip8
     }
+}
 
-    pub fn run(&mut self) {
-        let sdl_video_ss = self.sdl_cxt.video().unwrap();
+impl Chip8 {
+    /// Copy a ROM image into memory at [`PROGRAM_START`].
+    pub fn load_rom(&mut self, rom: &[u8]) -> Result<(), Chip8Error> {
+        let mem = &mut self.memory[PROGRAM_START..];
+        if rom.len() > mem.len() {
+            return Err(Chip8Error::RomTooLarge {
+                size: rom.len(),
+                max: mem.len(),
+            });
+        }
+        mem[..rom.len()].copy_from_slice(rom);
 
-        let scale = 10;
-        let sdl_window = sdl_video_ss
-            .window("Chip-8 emulator", 64 * scale, 32 * scale)
-            .position_centered()
-            .build()
-            .unwrap();
+        Ok(())
+    }
 
-        let mut sdl_canvas = sdl_window.into_canvas().build().unwrap();
+    pub fn load_from_file(&mut self, program: &mut File) -> Result<(), Chip8Error> {
+        let mut rom = vec![];
+        program.read_to_end(&mut rom)?;
+        self.load_rom(&rom)
+    }
 
-        let device = audio::init(&self.sdl_cxt);
+    /// Run one 60 Hz frame: execute the configured number of instructions,
+    /// then tick the timers once.
+    ///
+    /// With [`Quirks::display_wait`] the frame ends right after a sprite is
+    /// drawn.
+    pub fn run_frame(&mut self) -> Result<(), Chip8Error> {
+        loop {
+            self.step_in_frame()?;
+            if self.at_frame_start() {
+                return Ok(());
+            }
+        }
+    }
 
-        let bg_color = Color::RGB(0, 0, 0);
-        let draw_color = Color::RGB(0, 255, 0);
+    /// Execute one instruction of the current frame and return what ran.
+    /// The timers tick if it ended the frame, see [`Chip8::at_frame_start`].
+    ///
+    /// Running a frame one instruction at a time keeps the timers in step
+    /// with [`Chip8::run_frame`], for debuggers and tracing.
+    pub fn step_in_frame(&mut self) -> Result<Step, Chip8Error> {
+        if self.frame_cycles == 0 {
+            self.vblank_wait = false;
+        }
+        let step = self.step()?;
+        self.frame_cycles += 1;
 
-        // let mut prev_keys = HashSet::new();
-        while self.pc < self.memory.len() {
-            thread::sleep(Duration::new(0, 1_000_000_000 / 60));
+        let frame_end =
+            self.frame_cycles >= self.instructions_per_frame || self.vblank_wait || self.halted;
+        if frame_end {
+            self.tick_timers();
+            self.frame_cycles = 0;
+        }
 
-            for event in self.events.poll_iter() {
-                match event {
-                    Event::Quit { .. }
-                    | Event::KeyDown {
-                        keycode: Some(Keycode::Escape),
-                        ..
-                    } => return,
-                    _ => {}
-                }
-            }
-            if self.need_redraw {
-                sdl_canvas.set_draw_color(bg_color);
-                sdl_canvas.clear();
-                sdl_canvas.set_draw_color(draw_color);
-
-                for py in 0..32 {
-                    for px in 0..64 {
-                        if self.screen.vram()[py][px] == 1 {
-                            let x = ((px as u32) * scale) as i32;
-                            let y = ((py as u32) * scale) as i32;
-                            let w = (1 * scale) as u32;
-                            let h = (1 * scale) as u32;
-                            let rect = Rect::new(x, y, w, h);
-                            sdl_canvas.draw_rect(rect).unwrap();
-                            sdl_canvas.fill_rect(rect).unwrap();
-                        }
-                    }
-                }
+        Ok(step)
+    }
 
-                sdl_canvas.present();
+    /// No instruction of the current frame ran yet
+    pub fn at_frame_start(&self) -> bool {
+        self.frame_cycles == 0
+    }
 
-                self.need_redraw = false;
-            }
+    /// Execute `n` instructions without touching the timers
+    pub fn run_cycles(&mut self, n: usize) -> Result<(), Chip8Error> {
+        for _ in 0..n {
+            self.step()?;
+        }
 
-            if self.wait_key {
-                self.ld_fx0a();
-                continue;
-            }
+        Ok(())
+    }
 
-            self.timers.countdown();
-            if self.timers.sound() > 0 {
-                device.resume();
-            } else {
-                device.pause();
-            }
+    /// Count the delay and sound timers down by one 60 Hz tick
+    pub fn tick_timers(&mut self) {
+        self.timers.countdown();
+    }
 
-            self.opcode
-                .set_from_u8(self.memory[self.pc], self.memory[self.pc + 1]);
-
-            // println!("Code: {:X}", self.opcode.code());
-            match self.opcode.code() & 0xF000 {
-                0x0000 => match self.opcode.code() & 0x00FF {
-                    0x00E0 => self.cls_00e0(),
-                    0x00EE => self.ret_00ee(),
-                    _ => (),
-                },
-                0x1000 => self.jp_1nnn(),
-                0x2000 => self.call_2nnn(),
-                0x3000 => self.se_3xnn(),
-                0x4000 => self.sne_4xnn(),
-                0x5000 => self.se_5xy0(),
-                0x6000 => self.ld_6xnn(),
-                0x7000 => self.add_7xnn(),
-                0x8000 => match self.opcode.code() & 0x000F {
-                    0x0000 => self.ld_8xy0(),
-                    0x0001 => self.or_8xy1(),
-                    0x0002 => self.and_8xy2(),
-                    0x0003 => self.xor_8xy3(),
-                    0x0004 => self.add_8xy4(),
-                    0x0005 => self.sub_8xy5(),
-                    0x0006 => self.shr_8xy6(),
-                    0x0007 => self.subn_8xy7(),
-                    0x000E => self.shl_8x0e(),
-                    _ => (),
-                },
-                0x9000 => self.sne_9xy0(),
-                0xA000 => self.ld_annn(),
-                0xB000 => self.jp_bnnn(),
-                0xC000 => self.rnd_cxnn(),
-                0xD000 => self.drw_dxyn(),
-                0xE000 => match self.opcode.code() & 0x00FF {
-                    0x009E => self.skp_ex9e(),
-                    0x00A1 => self.sknp_exa1(),
-                    _ => (),
-                },
-                0xF000 => match self.opcode.code() & 0x00FF {
-                    0x0007 => self.ld_fx07(),
-                    0x000A => self.ld_fx0a(),
-                    0x0015 => self.ld_fx15(),
-                    0x0018 => self.ld_fx18(),
-                    0x001E => self.add_fx1e(),
-                    0x0029 => self.ld_fx29(),
-                    0x0033 => self.ld_fx33(),
-                    0x0055 => self.ld_fx55(),
-                    0x0065 => self.ld_fx65(),
-                    _ => (),
-                },
-                _ => (),
-            }
+    /// Execute exactly one instruction and return what ran.
+    ///
+    /// While waiting for a key press only the pending `Fx0A` is retried and
+    /// the program counter stays on it.
+    pub fn step(&mut self) -> Result<Step, Chip8Error> {
+        if self.wait_key {
+            self.ld_fx0a();
+            return Ok(Step {
+                address: self.instruction_pc(),
+                opcode: self.opcode,
+                instruction: self.opcode.instruction(),
+            });
+        }
+
+        let address = self.pc;
+        if address + 1 >= self.memory.len() {
+            return Err(Chip8Error::InvalidPc { pc: address });
+        }
+        self.opcode
+            .set_from_u8(self.memory[address], self.memory[address + 1]);
+        self.pc += 2;
 
-            self.pc += 2;
+        let instruction = self.opcode.instruction();
+        if !self.instruction_set.supports(&instruction) {
+            self.unknown_opcode()?;
+            return Ok(Step {
+                address,
+                opcode: self.opcode,
+                instruction,
+            });
+        }
+        match instruction {
+            Instruction::Cls => self.cls_00e0(),
+            Instruction::Ret => self.ret_00ee()?,
+            Instruction::Scd(_) => self.scd_00cn(),
+            Instruction::Scu(_) => self.scu_00dn(),
+            Instruction::Scr => self.scr_00fb(),
+            Instruction::Scl => self.scl_00fc(),
+            Instruction::Exit => self.exit_00fd(),
+            Instruction::Low => self.low_00fe(),
+            Instruction::High => self.high_00ff(),
+            Instruction::Jp(_) => self.jp_1nnn(),
+            Instruction::Call(_) => self.call_2nnn()?,
+            Instruction::SeByte(..) => self.se_3xnn(),
+            Instruction::SneByte(..) => self.sne_4xnn(),
+            Instruction::SeReg(..) => self.se_5xy0(),
+            Instruction::Save(..) => self.save_5xy2()?,
+            Instruction::Load(..) => self.load_5xy3()?,
+            Instruction::LdByte(..) => self.ld_6xnn(),
+            Instruction::AddByte(..) => self.add_7xnn(),
+            Instruction::LdReg(..) => self.ld_8xy0(),
+            Instruction::Or(..) => self.or_8xy1(),
+            Instruction::And(..) => self.and_8xy2(),
+            Instruction::Xor(..) => self.xor_8xy3(),
+            Instruction::AddReg(..) => self.add_8xy4(),
+            Instruction::Sub(..) => self.sub_8xy5(),
+            Instruction::Shr(..) => self.shr_8xy6(),
+            Instruction::Subn(..) => self.subn_8xy7(),
+            Instruction::Shl(..) => self.shl_8x0e(),
+            Instruction::SneReg(..) => self.sne_9xy0(),
+            Instruction::LdI(_) => self.ld_annn(),
+            Instruction::JpV0(_) => self.jp_bnnn(),
+            Instruction::Rnd(..) => self.rnd_cxnn(),
+            Instruction::Drw(..) => self.drw_dxyn()?,
+            Instruction::LdILong => self.ld_f000()?,
+            Instruction::Plane(_) => self.plane_fn01(),
+            Instruction::Audio => self.audio_f002()?,
+            Instruction::Skp(_) => self.skp_ex9e(),
+            Instruction::Sknp(_) => self.sknp_exa1(),
+            Instruction::LdVxDt(_) => self.ld_fx07(),
+            Instruction::LdVxK(_) => self.ld_fx0a(),
+            Instruction::LdDtVx(_) => self.ld_fx15(),
+            Instruction::LdStVx(_) => self.ld_fx18(),
+            Instruction::AddI(_) => self.add_fx1e(),
+            Instruction::LdF(_) => self.ld_fx29(),
+            Instruction::LdHf(_) => self.ld_fx30(),
+            Instruction::LdPitch(_) => self.ld_fx3a(),
+            Instruction::LdB(_) => self.ld_fx33()?,
+            Instruction::LdIVx(_) => self.ld_fx55()?,
+            Instruction::LdVxI(_) => self.ld_fx65()?,
+            Instruction::LdRVx(_) => self.ld_fx75(),
+            Instruction::LdVxR(_) => self.ld_fx85(),
+            Instruction::Sys(_) | Instruction::Unknown(_) => self.unknown_opcode()?,
         }
+
+        Ok(Step {
+            address,
+            opcode: self.opcode,
+            instruction,
+        })
     }
 }
 
 impl Chip8 {
-    fn get_pressed_keys(&self) -> HashSet<Keycode> {
-        let keys: HashSet<Keycode> = self
-            .events
-            .keyboard_state()
-            .pressed_scancodes()
-            .filter_map(Keycode::from_scancode)
-            .collect();
+    /// Copy of the complete machine state
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            memory: self.memory.clone(),
+            pc: self.pc,
+            opcode: self.opcode.code(),
+            v: self.v,
+            i: self.i,
+            stack: self.stack.clone(),
+            timers: self.timers.clone(),
+            screen: self.screen.clone(),
+            wait_key: self.wait_key,
+            halted: self.halted,
+            vblank_wait: self.vblank_wait,
+            rpl: self.rpl,
+            audio_pattern: self.audio_pattern,
+            pitch: self.pitch,
+            rng: self.rng,
+            quirks: self.quirks,
+        }
+    }
 
-        keys
+    /// Go back to the state of `snapshot`, the screen is redrawn
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        self.memory = snapshot.memory.clone();
+        self.pc = snapshot.pc;
+        self.opcode = Opcode::from(snapshot.opcode);
+        self.v = snapshot.v;
+        self.i = snapshot.i;
+        self.stack = snapshot.stack.clone();
+        self.timers = snapshot.timers.clone();
+        self.screen = snapshot.screen.clone();
+        self.wait_key = snapshot.wait_key;
+        self.halted = snapshot.halted;
+        self.vblank_wait = snapshot.vblank_wait;
+        self.frame_cycles = 0;
+        self.rpl = snapshot.rpl;
+        self.audio_pattern = snapshot.audio_pattern;
+        self.pitch = snapshot.pitch;
+        self.rng = snapshot.rng;
+        self.quirks = snapshot.quirks;
+        self.need_redraw = true;
     }
-    fn is_key_pressed(&self, key: &Keycode) -> bool {
-        let keys = self.get_pressed_keys();
-        log::debug!("Pressed keys: {:?}", keys);
-        let pressed = keys.contains(key);
-        log::debug!("Key: {}, is {}", key, &pressed);
-        pressed
+}
+
+impl Chip8 {
+    /// Program counter
+    pub fn pc(&self) -> usize {
+        self.pc
+    }
+
+    /// Last fetched opcode
+    pub fn opcode(&self) -> &Opcode {
+        &self.opcode
+    }
+
+    /// Registers V0 through VF
+    pub fn v(&self) -> &[u8; 16] {
+        &self.v
+    }
+
+    /// Index register I
+    pub fn i(&self) -> usize {
+        self.i
+    }
+
+    pub fn stack(&self) -> &Stack {
+        &self.stack
+    }
+
+    pub fn timers(&self) -> &Timers {
+        &self.timers
+    }
+
+    pub fn memory(&self) -> &[u8] {
+        &self.memory
+    }
+
+    /// Grow or shrink memory to `memory_size` bytes, between [`MEMORY_SIZE`]
+    /// and [`XO_MEMORY_SIZE`], keeping the contents that still fit
+    pub fn set_memory_size(&mut self, memory_size: usize) {
+        self.memory
+            .resize(memory_size.clamp(MEMORY_SIZE, XO_MEMORY_SIZE), 0);
+    }
+
+    pub fn screen(&self) -> &Screen {
+        &self.screen
+    }
+
+    pub fn keypad(&self) -> &Keypad {
+        &self.keypad
+    }
+
+    pub fn keypad_mut(&mut self) -> &mut Keypad {
+        &mut self.keypad
+    }
+
+    /// Press or release key 0x0 through 0xF of the hex keypad
+    pub fn set_key(&mut self, key: u8, pressed: bool) {
+        if pressed {
+            self.keypad.press(key);
+        } else {
+            self.keypad.release(key);
+        }
+    }
+
+    pub fn instructions_per_frame(&self) -> usize {
+        self.instructions_per_frame
+    }
+
+    /// Set CPU speed in instructions per 60 Hz frame, at least 1
+    pub fn set_instructions_per_frame(&mut self, instructions_per_frame: usize) {
+        self.instructions_per_frame = instructions_per_frame.max(1);
+    }
+
+    pub fn instruction_set(&self) -> InstructionSet {
+        self.instruction_set
+    }
+
+    pub fn set_instruction_set(&mut self, instruction_set: InstructionSet) {
+        self.instruction_set = instruction_set;
+    }
+
+    pub fn font(&self) -> Font {
+        self.font
+    }
+
+    /// Replace the built-in hex digit sprites in memory
+    pub fn set_font(&mut self, font: Font) {
+        self.font = font;
+        font::load_font(&mut self.memory, font);
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Restart the random number generator from `seed`
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = Rng::from_seed(seed);
+    }
+
+    pub fn quirks(&self) -> Quirks {
+        self.quirks
+    }
+
+    pub fn set_quirks(&mut self, quirks: Quirks) {
+        self.quirks = quirks;
+    }
+
+    pub fn unknown_opcode_policy(&self) -> UnknownOpcodePolicy {
+        self.unknown_opcode_policy
+    }
+
+    pub fn set_unknown_opcode_policy(&mut self, policy: UnknownOpcodePolicy) {
+        self.unknown_opcode_policy = policy;
+    }
+
+    /// Unknown opcodes met so far with how often each was executed
+    pub fn unknown_opcodes(&self) -> &BTreeMap<u16, usize> {
+        &self.unknown_opcodes
+    }
+
+    /// Copy of program counter, registers, stack and timers
+    pub fn registers(&self) -> Registers {
+        Registers {
+            pc: self.pc,
+            i: self.i,
+            v: self.v,
+            stack: self.stack.stack().to_vec(),
+            delay: self.timers.delay(),
+            sound: self.timers.sound(),
+        }
+    }
+
+    /// XO-CHIP audio pattern, 128 bits played while the sound timer runs.
+    /// `None` until the program loads one, the plain beep is played then.
+    pub fn audio_pattern(&self) -> Option<[u8; 16]> {
+        self.audio_pattern
+    }
+
+    /// XO-CHIP pitch, the pattern plays at 4000 * 2 ^ ((pitch - 64) / 48)
+    /// bits per second
+    pub fn pitch(&self) -> u8 {
+        self.pitch
+    }
+
+    /// Whether the program stopped itself with `00FD`
+    pub fn is_halted(&self) -> bool {
+        self.halted
+    }
+
+    /// Whether execution is blocked on `Fx0A` until a key is pressed
+    pub fn is_waiting_key(&self) -> bool {
+        self.wait_key
+    }
+
+    /// Returns `true` once if the screen changed since the last call
+    pub fn take_redraw(&mut self) -> bool {
+        std::mem::take(&mut self.need_redraw)
+    }
+}
+
+impl Chip8 {
+    /// Address of the instruction being executed
+    fn instruction_pc(&self) -> usize {
+        self.pc.saturating_sub(2)
+    }
+
+    /// Skip the next instruction, including the address word of an XO-CHIP
+    /// `F000 nnnn`
+    fn skip(&mut self) {
+        let next = self.memory.get(self.pc..self.pc + 2);
+        let long = next == Some(&[0xF0, 0x00]) && self.instruction_set >= InstructionSet::XoChip;
+        self.pc += if long { 4 } else { 2 };
+    }
+
+    /// Memory at `address..address + len`, checked against the memory size
+    fn memory_range(&mut self, address: usize, len: usize) -> Result<&mut [u8], Chip8Error> {
+        let pc = self.instruction_pc();
+        self.memory
+            .get_mut(address..address + len)
+            .ok_or(Chip8Error::MemoryOutOfRange { pc, address })
+    }
+}
+
+impl Chip8 {
+    /// Handle an opcode without instruction according to the
+    /// [`UnknownOpcodePolicy`]
+    fn unknown_opcode(&mut self) -> Result<(), Chip8Error> {
+        let pc = self.instruction_pc();
+        let opcode = self.opcode.code();
+        let count = self.unknown_opcodes.entry(opcode).or_default();
+        *count += 1;
+        let first = *count == 1;
+
+        match self.unknown_opcode_policy {
+            UnknownOpcodePolicy::Ignore => Ok(()),
+            UnknownOpcodePolicy::Warn => {
+                // Repeats are only counted, a game loop would flood the log
+                if first {
+                    log::warn!("Unknown opcode {opcode:04X} at {pc:#05X}, skipped");
+                }
+                Ok(())
+            }
+            UnknownOpcodePolicy::Halt => {
+                // Report the state before the opcode was fetched
+                let mut registers = self.registers();
+                registers.pc = pc;
+                Err(Chip8Error::UnknownOpcode {
+                    pc,
+                    opcode,
+                    registers: Box::new(registers),
+                })
+            }
+        }
     }
 }
 
 impl Chip8 {
     /// Clear the display
+    ///
+    /// XO-CHIP: only the selected bitplanes are cleared.
     fn cls_00e0(&mut self) {
-        self.screen.clear();
+        self.screen.clear_planes();
         self.need_redraw = true;
     }
     /// Return from a subroutine.
     ///
     /// The interpreter sets the program counter to the address at the top of
     /// the stack, then subtracts 1 from the stack pointer.
-    fn ret_00ee(&mut self) {
-        log::debug!(
-            "Return from subroutine. Current PC: {}, previous: {}",
-            self.pc,
-            self.stack.stack().last().unwrap()
-        );
-        self.pc = self.stack.ret() as usize;
+    fn ret_00ee(&mut self) -> Result<(), Chip8Error> {
+        let pc = self.instruction_pc();
+        log::debug!("Return from subroutine. Current PC: {}", self.pc);
+        self.pc = self.stack.ret().ok_or(Chip8Error::StackUnderflow { pc })?;
         log::debug!("Returned from subrouting. Current PC: {}", self.pc);
+
+        Ok(())
+    }
+    /// Scroll display n lines down.
+    ///
+    /// SUPER-CHIP. The display contents move down by n pixels, the lines
+    /// scrolled in at the top are blank.
+    fn scd_00cn(&mut self) {
+        let n = self.opcode.n() as usize;
+
+        self.screen.scroll_down(n);
+        self.need_redraw = true;
+    }
+    /// Scroll display n lines up.
+    ///
+    /// XO-CHIP. The display contents move up by n pixels, the lines scrolled
+    /// in at the bottom are blank.
+    fn scu_00dn(&mut self) {
+        let n = self.opcode.n() as usize;
+
+        self.screen.scroll_up(n);
+        self.need_redraw = true;
+    }
+    /// Scroll display 4 pixels right.
+    ///
+    /// SUPER-CHIP. The columns scrolled in at the left are blank.
+    fn scr_00fb(&mut self) {
+        self.screen.scroll_right(4);
+        self.need_redraw = true;
+    }
+    /// Scroll display 4 pixels left.
+    ///
+    /// SUPER-CHIP. The columns scrolled in at the right are blank.
+    fn scl_00fc(&mut self) {
+        self.screen.scroll_left(4);
+        self.need_redraw = true;
+    }
+    /// Exit the interpreter.
+    ///
+    /// SUPER-CHIP. Execution stops, the program counter stays on this
+    /// instruction.
+    fn exit_00fd(&mut self) {
+        self.halted = true;
+        self.pc = self.instruction_pc();
+    }
+    /// Disable high resolution mode.
+    ///
+    /// SUPER-CHIP. The display switches to 64x32 and is cleared.
+    fn low_00fe(&mut self) {
+        self.screen.set_hires(false);
+        self.need_redraw = true;
+    }
+    /// Enable high resolution mode.
+    ///
+    /// SUPER-CHIP. The display switches to 128x64 and is cleared.
+    fn high_00ff(&mut self) {
+        self.screen.set_hires(true);
+        self.need_redraw = true;
     }
     /// Jump to location nnn.
     ///
     /// The interpreter sets the program counter to nnn.
     fn jp_1nnn(&mut self) {
-        self.pc = self.opcode.nnn() - 2;
+        self.pc = self.opcode.nnn();
     }
     /// Call subroutine at nnn.
     ///
     /// The interpreter increments the stack pointer, then puts the current PC
     /// on the top of the stack. The PC is then set to nnn.
-    fn call_2nnn(&mut self) {
+    fn call_2nnn(&mut self) -> Result<(), Chip8Error> {
+        if self.stack.is_full() {
+            let pc = self.instruction_pc();
+            return Err(Chip8Error::StackOverflow { pc });
+        }
         self.stack.push(self.pc);
 
         let nnn = self.opcode.nnn();
-        self.pc = nnn - 2;
+        self.pc = nnn;
+
+        Ok(())
     }
     /// Skip next instruction if Vx = kk.
     ///
@@ -324,8 +754,8 @@ impl Chip8 {
         let nn = self.opcode.nn();
 
         if vx == nn {
-            self.pc += 2;
-        };
+            self.skip();
+        }
     }
     /// Skip next instruction if Vx != kk.
     ///
@@ -337,8 +767,8 @@ impl Chip8 {
         let nn = self.opcode.nn();
 
         if vx != nn {
-            self.pc += 2;
-        };
+            self.skip();
+        }
     }
     /// Skip next instruction if Vx = Vy.
     ///
@@ -351,8 +781,43 @@ impl Chip8 {
         let vy = self.v[y];
 
         if vx == vy {
-            self.pc += 2;
+            self.skip();
+        }
+    }
+    /// Store registers Vx through Vy in memory starting at location I.
+    ///
+    /// XO-CHIP. If x is greater than y the registers are stored in reverse
+    /// order. I is not changed.
+    fn save_5xy2(&mut self) -> Result<(), Chip8Error> {
+        let x = self.opcode.x();
+        let y = self.opcode.y();
+        let registers: Vec<u8> = if x <= y {
+            self.v[x..=y].to_vec()
+        } else {
+            self.v[y..=x].iter().rev().copied().collect()
         };
+
+        self.memory_range(self.i, registers.len())?
+            .copy_from_slice(&registers);
+
+        Ok(())
+    }
+    /// Read registers Vx through Vy from memory starting at location I.
+    ///
+    /// XO-CHIP. If x is greater than y the registers are read in reverse
+    /// order. I is not changed.
+    fn load_5xy3(&mut self) -> Result<(), Chip8Error> {
+        let x = self.opcode.x();
+        let y = self.opcode.y();
+        let len = x.abs_diff(y) + 1;
+        let values = self.memory_range(self.i, len)?.to_vec();
+
+        for (offset, value) in values.into_iter().enumerate() {
+            let register = if x <= y { x + offset } else { x - offset };
+            self.v[register] = value;
+        }
+
+        Ok(())
     }
     /// Set Vx = kk.
     ///
@@ -395,6 +860,10 @@ impl Chip8 {
         let vy = self.v[y];
 
         self.v[x] |= vy;
+
+        if self.quirks.logic_resets_vf {
+            self.v[0xF] = 0;
+        }
     }
     /// Set Vx = Vx AND Vy.
     ///
@@ -408,6 +877,10 @@ impl Chip8 {
         let vy = self.v[y];
 
         self.v[x] &= vy;
+
+        if self.quirks.logic_resets_vf {
+            self.v[0xF] = 0;
+        }
     }
     /// Set Vx = Vx XOR Vy.
     ///
@@ -421,6 +894,10 @@ impl Chip8 {
         let vy = self.v[y];
 
         self.v[x] ^= vy;
+
+        if self.quirks.logic_resets_vf {
+            self.v[0xF] = 0;
+        }
     }
     /// Set Vx = Vx + Vy, set VF = carry.
     /// The values of Vx and Vy are added together. If the result is greater
@@ -434,61 +911,69 @@ impl Chip8 {
 
         let result = (vx as u16) + (vy as u16);
 
-        self.v[0xF] = if result > 255 { 1 } else { 0 };
-
+        // VF last, the flag wins over the result for x = F
         self.v[x] = result as u8;
+        self.v[0xF] = if result > 255 { 1 } else { 0 };
     }
     /// Set Vx = Vx - Vy, set VF = NOT borrow.
     ///
-    /// If Vx > Vy, then VF is set to 1, otherwise 0. Then Vy is subtracted from
-    /// Vx, and the results stored in Vx.
+    /// Vy is subtracted from Vx, and the results stored in Vx. Then VF is set
+    /// to 1 if Vx >= Vy, otherwise 0.
     fn sub_8xy5(&mut self) {
         let x = self.opcode.x();
         let vx = self.v[x];
         let y = self.opcode.y();
         let vy = self.v[y];
 
-        self.v[0xF] = if vx > vy { 1 } else { 0 };
-
         self.v[x] = vx.wrapping_sub(vy);
+        self.v[0xF] = if vx >= vy { 1 } else { 0 };
     }
     /// Set Vx = Vx SHR 1.
     ///
-    /// If the least-significant bit of Vx is 1, then VF is set to 1, otherwise
-    /// 0. Then Vx is divided by 2.
+    /// Vx is divided by 2. Then VF is set to 1 if the least-significant bit of
+    /// Vx was 1, otherwise 0. With [`Quirks::shift_uses_vy`] Vy is shifted
+    /// instead and the result stored in Vx.
     fn shr_8xy6(&mut self) {
         let x = self.opcode.x();
-        let vx = self.v[x];
-
-        self.v[0xF] = vx & 0x1;
+        let y = self.opcode.y();
+        let value = if self.quirks.shift_uses_vy {
+            self.v[y]
+        } else {
+            self.v[x]
+        };
 
-        self.v[x] >>= 1;
+        self.v[x] = value >> 1;
+        self.v[0xF] = value & 0x1;
     }
     /// Set Vx = Vy - Vx, set VF = NOT borrow.
     ///
-    /// If Vy > Vx, then VF is set to 1, otherwise 0. Then Vx is subtracted from
-    /// Vy, and the results stored in Vx.
+    /// Vx is subtracted from Vy, and the results stored in Vx. Then VF is set
+    /// to 1 if Vy >= Vx, otherwise 0.
     fn subn_8xy7(&mut self) {
         let x = self.opcode.x();
         let vx = self.v[x];
         let y = self.opcode.y();
         let vy = self.v[y];
 
-        self.v[0xF] = if vy > vx { 1 } else { 0 };
-
         self.v[x] = vy.wrapping_sub(vx);
+        self.v[0xF] = if vy >= vx { 1 } else { 0 };
     }
     /// Set Vx = Vx SHL 1.
     ///
-    /// If the most-significant bit of Vx is 1, then VF is set to 1, otherwise
-    /// to 0. Then Vx is multiplied by 2.
+    /// Vx is multiplied by 2. Then VF is set to 1 if the most-significant bit
+    /// of Vx was 1, otherwise to 0. With [`Quirks::shift_uses_vy`] Vy is
+    /// shifted instead and the result stored in Vx.
     fn shl_8x0e(&mut self) {
         let x = self.opcode.x();
-        let vx = self.v[x];
-
-        self.v[0xF] = (vx & 0x1) >> 7;
+        let y = self.opcode.y();
+        let value = if self.quirks.shift_uses_vy {
+            self.v[y]
+        } else {
+            self.v[x]
+        };
 
-        self.v[x] <<= 1;
+        self.v[x] = value << 1;
+        self.v[0xF] = (value >> 7) & 0x1;
     }
     /// Skip next instruction if Vx != Vy.
     ///
@@ -501,7 +986,7 @@ impl Chip8 {
         let vy = self.v[y];
 
         if vx != vy {
-            self.pc += 2;
+            self.skip();
         }
     }
     /// Set I = nnn.
@@ -514,12 +999,17 @@ impl Chip8 {
     }
     /// Jump to location nnn + V0.
     ///
-    /// The program counter is set to nnn plus the value of V0.
+    /// The program counter is set to nnn plus the value of V0. With
+    /// [`Quirks::jump_uses_vx`] Vx is used instead of V0.
     fn jp_bnnn(&mut self) {
         let nnn = self.opcode.nnn();
-        let v0 = self.v[0] as usize;
+        let offset = if self.quirks.jump_uses_vx {
+            self.v[self.opcode.x()]
+        } else {
+            self.v[0]
+        } as usize;
 
-        self.pc = (nnn - 2) + v0;
+        self.pc = nnn + offset;
     }
     /// Set Vx = random byte AND kk.
     ///
@@ -529,7 +1019,7 @@ impl Chip8 {
     fn rnd_cxnn(&mut self) {
         let x = self.opcode.x();
         let nn = self.opcode.nn();
-        let rn = self.rng.gen::<u8>();
+        let rn = self.rng.next_u8();
 
         self.v[x] = rn & nn;
     }
@@ -544,23 +1034,99 @@ impl Chip8 {
     /// coordinates of the display, it wraps around to the opposite side of the
     /// screen. See instruction 8xy3 for more information on XOR, and section
     /// 2.4, Display, for more information on the Chip-8 screen and sprites.
-    fn drw_dxyn(&mut self) {
+    ///
+    /// With [`Quirks::clip_sprites`] the parts outside the display are cut off
+    /// instead, only the starting coordinates wrap. SUPER-CHIP: with n = 0 a
+    /// 16x16 sprite of 32 bytes, two bytes per row, is drawn. XO-CHIP: the
+    /// sprite is drawn into every selected bitplane, with both planes selected
+    /// the data for plane 2 follows the data for plane 1.
+    fn drw_dxyn(&mut self) -> Result<(), Chip8Error> {
         let x = self.opcode.x();
         let y = self.opcode.y();
         let n = self.opcode.n() as usize;
+        let big = n == 0 && self.instruction_set >= InstructionSet::SuperChip10;
+        let (sprite_width, sprite_height) = if big { (16, 16) } else { (8, n) };
+        let bytes_per_row = sprite_width / 8;
+        let sprite_len = sprite_height * bytes_per_row;
+        let planes: Vec<u8> = [0b01, 0b10]
+            .into_iter()
+            .filter(|plane| self.screen.planes() & plane != 0)
+            .collect();
+        let sprites = self
+            .memory_range(self.i, sprite_len * planes.len())?
+            .to_vec();
+
+        let (width, height) = (self.screen.width(), self.screen.height());
+        let start_x = self.v[x] as usize % width;
+        let start_y = self.v[y] as usize % height;
 
         self.v[0xF] = 0; // reset if collisons were before
-        for byte in 0..n {
-            let y = (self.v[y] as usize + byte) % 32;
-            for bit in 0..8 {
-                let x = (self.v[x] as usize + bit) % 64;
-                let color = (self.memory[self.i + byte] >> (7 - bit)) & 0x1;
-                self.v[0x0F] |= color & self.screen.vram()[y][x];
-                self.screen.set_xy(x, y, color);
+
+        // A zero height sprite has no bytes, but chunks must not be empty
+        for (plane, sprite) in planes.iter().zip(sprites.chunks(sprite_len.max(1))) {
+            for (byte, row) in sprite.chunks(bytes_per_row).enumerate() {
+                let y = start_y + byte;
+                if self.quirks.clip_sprites && y >= height {
+                    break;
+                }
+                let y = y % height;
+                let row = row.iter().fold(0u16, |bits, &b| (bits << 8) | b as u16);
+                for bit in 0..sprite_width {
+                    let x = start_x + bit;
+                    if self.quirks.clip_sprites && x >= width {
+                        break;
+                    }
+                    let x = x % width;
+                    if (row >> (sprite_width - 1 - bit)) & 0x1 == 0 {
+                        continue;
+                    }
+                    if self.screen.pixel(x, y) & plane != 0 {
+                        self.v[0x0F] = 1;
+                    }
+                    self.screen.set_xy(x, y, *plane);
+                }
             }
         }
 
         self.need_redraw = true;
+        self.vblank_wait = self.quirks.display_wait;
+
+        Ok(())
+    }
+
+    /// Set I = nnnn.
+    ///
+    /// XO-CHIP. The 16 bit address nnnn is the word following the opcode, the
+    /// program counter moves past it.
+    fn ld_f000(&mut self) -> Result<(), Chip8Error> {
+        let address = self.memory_range(self.pc, 2)?;
+        let nnnn = u16::from_be_bytes([address[0], address[1]]);
+
+        self.i = nnnn as usize;
+        self.pc += 2;
+
+        Ok(())
+    }
+    /// Select drawing planes.
+    ///
+    /// XO-CHIP. Bit 0 of n selects plane 1, bit 1 selects plane 2. Drawing,
+    /// clearing and scrolling only affect the selected planes.
+    fn plane_fn01(&mut self) {
+        let n = self.opcode.x() as u8;
+
+        self.screen.set_planes(n);
+    }
+    /// Load the audio pattern.
+    ///
+    /// XO-CHIP. The 16 bytes starting at I become the 128 bit pattern played
+    /// while the sound timer is active.
+    fn audio_f002(&mut self) -> Result<(), Chip8Error> {
+        let mut pattern = [0; 16];
+        pattern.copy_from_slice(self.memory_range(self.i, 16)?);
+
+        self.audio_pattern = Some(pattern);
+
+        Ok(())
     }
 
     /// Skip next instruction if key with the value of Vx is pressed.
@@ -570,11 +1136,9 @@ impl Chip8 {
     fn skp_ex9e(&mut self) {
         let x = self.opcode.x();
         let vx = self.v[x];
-        if let Some(key) = hex_to_key(vx) {
-            if self.is_key_pressed(&key) {
-                self.pc += 2;
-            }
-        };
+        if self.keypad.is_pressed(vx) {
+            self.skip();
+        }
     }
     /// Skip next instruction if key with the value of Vx is not pressed.
     ///
@@ -583,11 +1147,9 @@ impl Chip8 {
     fn sknp_exa1(&mut self) {
         let x = self.opcode.x();
         let vx = self.v[x];
-        if let Some(key) = hex_to_key(vx) {
-            if !self.is_key_pressed(&key) {
-                self.pc += 2;
-            }
-        };
+        if !self.keypad.is_pressed(vx) {
+            self.skip();
+        }
     }
     /// Set Vx = delay timer value.
     ///
@@ -605,12 +1167,9 @@ impl Chip8 {
         let x = self.opcode.x();
 
         self.wait_key = true;
-        let pressed_keys = self.get_pressed_keys();
-        if pressed_keys.len() > 0 {
-            if let Some(key) = key_to_hex(pressed_keys.iter().next().unwrap().clone()) {
-                self.wait_key = false;
-                self.v[x] = key;
-            }
+        if let Some(key) = self.keypad.first_pressed() {
+            self.wait_key = false;
+            self.v[x] = key;
         }
     }
     /// Set delay timer = Vx.
@@ -633,12 +1192,18 @@ impl Chip8 {
     }
     /// Set I = I + Vx.
     ///
-    /// The values of I and Vx are added, and the results are stored in I.
+    /// The values of I and Vx are added, and the results are stored in I. With
+    /// [`Quirks::add_i_overflow_sets_vf`] VF is set to 1 if I goes past 0xFFF,
+    /// otherwise 0.
     fn add_fx1e(&mut self) {
         let x = self.opcode.x();
         let vx = self.v[x] as usize;
 
         self.i += vx;
+
+        if self.quirks.add_i_overflow_sets_vf {
+            self.v[0xF] = if self.i > 0xFFF { 1 } else { 0 };
+        }
     }
     /// Set I = location of sprite for digit Vx.
     ///
@@ -649,40 +1214,97 @@ impl Chip8 {
         let x = self.opcode.x();
         let vx = self.v[x] as usize;
 
-        self.i = vx * 5;
+        self.i = font::FONT_START + vx * font::FONT_SPRITE_LEN;
+    }
+    /// Set I = location of big sprite for digit Vx.
+    ///
+    /// SUPER-CHIP. Like Fx29, but for the 8x10 font.
+    fn ld_fx30(&mut self) {
+        let x = self.opcode.x();
+        let vx = self.v[x] as usize;
+
+        self.i = font::BIG_FONT_START + (vx & 0xF) * font::BIG_FONT_SPRITE_LEN;
+    }
+    /// Set pitch = Vx.
+    ///
+    /// XO-CHIP. Sets the playback rate of the audio pattern.
+    fn ld_fx3a(&mut self) {
+        let x = self.opcode.x();
+
+        self.pitch = self.v[x];
     }
     /// Store BCD representation of Vx in memory locations I, I+1, and I+2.
     ///
     /// The interpreter takes the decimal value of Vx, and places the hundreds
     /// digit in memory at location in I, the tens digit at location I+1, and
     /// the ones digit at location I+2.
-    fn ld_fx33(&mut self) {
+    fn ld_fx33(&mut self) -> Result<(), Chip8Error> {
         let x = self.opcode.x();
         let vx = self.v[x];
 
-        let i = self.i;
-        self.memory[i + 0] = vx / 100;
-        self.memory[i + 1] = (vx / 10) % 10;
-        self.memory[i + 2] = vx % 10;
+        let bcd = self.memory_range(self.i, 3)?;
+        bcd[0] = vx / 100;
+        bcd[1] = (vx / 10) % 10;
+        bcd[2] = vx % 10;
+
+        Ok(())
     }
     /// Store registers V0 through Vx in memory starting at location I.
     ///
     /// The interpreter copies the values of registers V0 through Vx into
-    /// memory, starting at the address in I.
-    fn ld_fx55(&mut self) {
+    /// memory, starting at the address in I. With
+    /// [`Quirks::load_store_increments_i`] I is left at I + x + 1, or I + x
+    /// with [`Quirks::load_store_increments_i_by_x`].
+    fn ld_fx55(&mut self) -> Result<(), Chip8Error> {
         let x = self.opcode.x();
-        for i in 0..=x {
-            self.memory[self.i + i] = self.v[i];
+        let v = self.v;
+        self.memory_range(self.i, x + 1)?.copy_from_slice(&v[..=x]);
+
+        if self.quirks.load_store_increments_i {
+            self.i += self.load_store_increment(x);
         }
+
+        Ok(())
     }
     /// Read registers V0 through Vx from memory starting at location I.
     ///
     /// The interpreter reads values from memory starting at location I into
-    /// registers V0 through Vx.
-    fn ld_fx65(&mut self) {
+    /// registers V0 through Vx. With [`Quirks::load_store_increments_i`] I is
+    /// left at I + x + 1, or I + x with
+    /// [`Quirks::load_store_increments_i_by_x`].
+    fn ld_fx65(&mut self) -> Result<(), Chip8Error> {
         let x = self.opcode.x();
-        for i in 0..=x {
-            self.v[i] = self.memory[self.i + i];
+        let registers = self.memory_range(self.i, x + 1)?.to_vec();
+        self.v[..=x].copy_from_slice(&registers);
+
+        if self.quirks.load_store_increments_i {
+            self.i += self.load_store_increment(x);
         }
+
+        Ok(())
+    }
+    /// Amount `Fx55` and `Fx65` advance I by
+    fn load_store_increment(&self, x: usize) -> usize {
+        if self.quirks.load_store_increments_i_by_x {
+            x
+        } else {
+            x + 1
+        }
+    }
+    /// Store V0 through Vx in the RPL user flags.
+    ///
+    /// SUPER-CHIP. The flags survive until the interpreter is reset.
+    fn ld_fx75(&mut self) {
+        let x = self.opcode.x();
+
+        self.rpl[..=x].copy_from_slice(&self.v[..=x]);
+    }
+    /// Read V0 through Vx from the RPL user flags.
+    ///
+    /// SUPER-CHIP.
+    fn ld_fx85(&mut self) {
+        let x = self.opcode.x();
+
+        self.v[..=x].copy_from_slice(&self.rpl[..=x]);
     }
 }
@@ -1,3 +1,7 @@
+use super::instruction::Instruction;
+
+/// Raw 16 bit instruction with accessors for its operand fields
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Opcode {
     lr: u16,
 }
@@ -23,26 +27,42 @@ impl Opcode {
     pub fn set_from_u16(&mut self, lr: u16) {
         self.lr = lr;
     }
+
+    pub fn instruction(&self) -> Instruction {
+        Instruction::decode(*self)
+    }
+}
+
+impl From<u16> for Opcode {
+    fn from(lr: u16) -> Self {
+        Self { lr }
+    }
+}
+
+impl Default for Opcode {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Opcode {
-    pub fn nnn(&mut self) -> usize {
+    pub fn nnn(&self) -> usize {
         (self.lr & 0x0FFF) as usize
     }
 
-    pub fn nn(&mut self) -> u8 {
+    pub fn nn(&self) -> u8 {
         (self.lr & 0x00FF) as u8
     }
 
-    pub fn n(&mut self) -> u8 {
+    pub fn n(&self) -> u8 {
         (self.lr & 0x000F) as u8
     }
 
-    pub fn x(&mut self) -> usize {
+    pub fn x(&self) -> usize {
         ((self.lr & 0x0F00) >> 8) as usize
     }
 
-    pub fn y(&mut self) -> usize {
+    pub fn y(&self) -> usize {
         ((self.lr & 0x00F0) >> 4) as usize
     }
 }
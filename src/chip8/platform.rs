@@ -0,0 +1,138 @@
+use std::{
+    fmt,
+    str::FromStr,
+};
+
+use super::{
+    font::Font,
+    instruction_set::InstructionSet,
+    quirks::Quirks,
+    MEMORY_SIZE,
+    XO_MEMORY_SIZE,
+};
+
+/// Known Chip8 platforms, each bundling the settings its programs expect
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Platform {
+    /// Original interpreter on the RCA COSMAC VIP
+    CosmacVip,
+    /// CHIP-48 on the HP 48 calculators
+    Chip48,
+    /// SUPER-CHIP 1.0 on the HP 48
+    SuperChip10,
+    /// SUPER-CHIP 1.1 on the HP 48
+    SuperChip11,
+    /// SUPER-CHIP as implemented by modern interpreters like Octo
+    ModernSuperChip,
+    /// XO-CHIP, as defined by Octo
+    XoChip,
+}
+
+impl Platform {
+    /// Names accepted by [`Platform::from_str`]
+    pub const NAMES: [&'static str; 6] = [
+        "cosmac-vip",
+        "chip-48",
+        "schip-1.0",
+        "schip-1.1",
+        "schip-modern",
+        "xo-chip",
+    ];
+
+    pub fn instruction_set(&self) -> InstructionSet {
+        match self {
+            Self::CosmacVip | Self::Chip48 => InstructionSet::Chip8,
+            Self::SuperChip10 => InstructionSet::SuperChip10,
+            Self::SuperChip11 | Self::ModernSuperChip => InstructionSet::SuperChip11,
+            Self::XoChip => InstructionSet::XoChip,
+        }
+    }
+
+    /// Quirks of the platform's interpreter
+    pub fn quirks(&self) -> Quirks {
+        match self {
+            Self::CosmacVip => Quirks {
+                shift_uses_vy: true,
+                load_store_increments_i: true,
+                logic_resets_vf: true,
+                clip_sprites: true,
+                display_wait: true,
+                ..Quirks::default()
+            },
+            // CHIP-48 and SUPER-CHIP 1.0 advance I by x on `Fx55` and `Fx65`
+            Self::Chip48 | Self::SuperChip10 => Quirks {
+                load_store_increments_i: true,
+                load_store_increments_i_by_x: true,
+                jump_uses_vx: true,
+                clip_sprites: true,
+                ..Quirks::default()
+            },
+            Self::SuperChip11 | Self::ModernSuperChip => Quirks {
+                jump_uses_vx: true,
+                clip_sprites: true,
+                ..Quirks::default()
+            },
+            Self::XoChip => Quirks {
+                shift_uses_vy: true,
+                load_store_increments_i: true,
+                ..Quirks::default()
+            },
+        }
+    }
+
+    pub fn instructions_per_frame(&self) -> usize {
+        match self {
+            Self::CosmacVip => 15,
+            Self::Chip48 | Self::SuperChip10 | Self::SuperChip11 | Self::ModernSuperChip => 30,
+            Self::XoChip => 100,
+        }
+    }
+
+    pub fn font(&self) -> Font {
+        match self {
+            Self::CosmacVip => Font::CosmacVip,
+            Self::Chip48 | Self::SuperChip10 | Self::SuperChip11 => Font::SuperChip,
+            Self::ModernSuperChip | Self::XoChip => Font::Octo,
+        }
+    }
+
+    pub fn memory_size(&self) -> usize {
+        match self {
+            Self::XoChip => XO_MEMORY_SIZE,
+            _ => MEMORY_SIZE,
+        }
+    }
+}
+
+impl FromStr for Platform {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cosmac-vip" => Ok(Self::CosmacVip),
+            "chip-48" => Ok(Self::Chip48),
+            "schip-1.0" => Ok(Self::SuperChip10),
+            "schip-1.1" => Ok(Self::SuperChip11),
+            "schip-modern" => Ok(Self::ModernSuperChip),
+            "xo-chip" => Ok(Self::XoChip),
+            _ => Err(format!(
+                "unknown platform `{s}`, expected one of: {}",
+                Self::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Platform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::CosmacVip => "cosmac-vip",
+            Self::Chip48 => "chip-48",
+            Self::SuperChip10 => "schip-1.0",
+            Self::SuperChip11 => "schip-1.1",
+            Self::ModernSuperChip => "schip-modern",
+            Self::XoChip => "xo-chip",
+        };
+        write!(f, "{name}")
+    }
+}
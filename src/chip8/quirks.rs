@@ -0,0 +1,100 @@
+/// Behaviors that differ between Chip8 implementations.
+///
+/// The default is what this interpreter always did: modern behavior with
+/// every flag off.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Quirks {
+    /// `8xy6` and `8xyE` shift Vy and store the result in Vx, instead of
+    /// shifting Vx in place (COSMAC VIP)
+    pub shift_uses_vy: bool,
+    /// `Fx55` and `Fx65` leave I pointing past the last register, instead of
+    /// leaving I unchanged (COSMAC VIP)
+    pub load_store_increments_i: bool,
+    /// With [`Quirks::load_store_increments_i`], I advances by x instead of
+    /// x + 1 (CHIP-48, SUPER-CHIP 1.0)
+    pub load_store_increments_i_by_x: bool,
+    /// `Bnnn` jumps to nnn plus Vx, where x is the highest nibble of nnn,
+    /// instead of nnn plus V0 (CHIP-48, SUPER-CHIP)
+    pub jump_uses_vx: bool,
+    /// `8xy1`, `8xy2` and `8xy3` set VF to 0 (COSMAC VIP)
+    pub logic_resets_vf: bool,
+    /// `Dxyn` clips sprites at the screen edges, instead of wrapping them
+    /// around to the opposite side
+    pub clip_sprites: bool,
+    /// `Dxyn` waits for the vertical blank, so at most one sprite is drawn per
+    /// frame (COSMAC VIP)
+    pub display_wait: bool,
+    /// `Fx1E` sets VF to 1 when I overflows past 0xFFF, otherwise 0 (Amiga
+    /// interpreter)
+    pub add_i_overflow_sets_vf: bool,
+}
+
+impl Quirks {
+    /// Names accepted by [`Quirks::set`]
+    pub const NAMES: [&'static str; 8] = [
+        "shift-uses-vy",
+        "load-store-increments-i",
+        "jump-uses-vx",
+        "logic-resets-vf",
+        "clip-sprites",
+        "display-wait",
+        "add-i-overflow-sets-vf",
+        // Later names so save states keep their quirk bits
+        "load-store-increments-i-by-x",
+    ];
+
+    /// Set a quirk by its name from [`Quirks::NAMES`]
+    pub fn set(&mut self, name: &str, enabled: bool) -> Result<(), String> {
+        let flag = match name {
+            "shift-uses-vy" => &mut self.shift_uses_vy,
+            "load-store-increments-i" => &mut self.load_store_increments_i,
+            "load-store-increments-i-by-x" => &mut self.load_store_increments_i_by_x,
+            "jump-uses-vx" => &mut self.jump_uses_vx,
+            "logic-resets-vf" => &mut self.logic_resets_vf,
+            "clip-sprites" => &mut self.clip_sprites,
+            "display-wait" => &mut self.display_wait,
+            "add-i-overflow-sets-vf" => &mut self.add_i_overflow_sets_vf,
+            _ => {
+                return Err(format!(
+                    "unknown quirk `{name}`, expected one of: {}",
+                    Self::NAMES.join(", ")
+                ))
+            }
+        };
+        *flag = enabled;
+
+        Ok(())
+    }
+
+    /// Value of a quirk by its name from [`Quirks::NAMES`]
+    pub fn get(&self, name: &str) -> Option<bool> {
+        let flag = match name {
+            "shift-uses-vy" => self.shift_uses_vy,
+            "load-store-increments-i" => self.load_store_increments_i,
+            "load-store-increments-i-by-x" => self.load_store_increments_i_by_x,
+            "jump-uses-vx" => self.jump_uses_vx,
+            "logic-resets-vf" => self.logic_resets_vf,
+            "clip-sprites" => self.clip_sprites,
+            "display-wait" => self.display_wait,
+            "add-i-overflow-sets-vf" => self.add_i_overflow_sets_vf,
+            _ => return None,
+        };
+
+        Some(flag)
+    }
+
+    /// Apply a `name` or `name=true|false` setting
+    pub fn apply(&mut self, setting: &str) -> Result<(), String> {
+        let (name, enabled) = match setting.split_once('=') {
+            Some((name, value)) => {
+                let enabled = value
+                    .parse()
+                    .map_err(|_| format!("invalid value `{value}` for quirk `{name}`"))?;
+                (name, enabled)
+            }
+            None => (setting, true),
+        };
+
+        self.set(name, enabled)
+    }
+}
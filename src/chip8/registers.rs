@@ -0,0 +1,29 @@
+use std::fmt;
+
+/// Copy of the CPU visible state: program counter, registers, stack and
+/// timers
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Registers {
+    pub pc: usize,
+    pub i: usize,
+    pub v: [u8; 16],
+    pub stack: Vec<usize>,
+    pub delay: u8,
+    pub sound: u8,
+}
+
+impl fmt::Display for Registers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PC={:03X} I={:03X}", self.pc, self.i)?;
+        for (x, vx) in self.v.iter().enumerate() {
+            write!(f, " V{x:X}={vx:02X}")?;
+        }
+        write!(
+            f,
+            " DT={:02X} ST={:02X} SP={}",
+            self.delay,
+            self.sound,
+            self.stack.len()
+        )
+    }
+}
@@ -0,0 +1,47 @@
+/// Random number generator for `Cxkk`.
+///
+/// SplitMix64, its whole state is a single `u64`, so the machine can be saved
+/// and restored with the exact same upcoming random numbers, and a seed makes
+/// runs reproducible.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Generator seeded from the operating system
+    pub fn new() -> Self {
+        Self::from_seed(rand::random())
+    }
+
+    pub fn from_seed(seed: u64) -> Self {
+        Self::from_state(seed)
+    }
+
+    pub fn from_state(state: u64) -> Self {
+        Self { state }
+    }
+
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+
+    /// Next random byte
+    pub fn next_u8(&mut self) -> u8 {
+        (self.next_u64() >> 56) as u8
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+impl Default for Rng {
+    fn default() -> Self {
+        Self::new()
+    }
+}
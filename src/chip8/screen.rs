@@ -1,30 +1,251 @@
+use std::{
+    fmt,
+    io::{
+        self,
+        Write,
+    },
+};
+
+use crate::palette::Palette;
+
+use super::ScreenFormat;
+
+pub const LORES_WIDTH: usize = 64;
+pub const LORES_HEIGHT: usize = 32;
+pub const HIRES_WIDTH: usize = 128;
+pub const HIRES_HEIGHT: usize = 64;
+
+/// Display, one byte per pixel.
+///
+/// The display is 64x32 in low resolution and 128x64 in the SUPER-CHIP high
+/// resolution mode. `vram` always has room for the high resolution, only the
+/// top left `width() x height()` pixels are shown.
+///
+/// Every pixel holds one bit per XO-CHIP bitplane: bit 0 is plane 1, bit 1 is
+/// plane 2, so a pixel is a color index from 0 to 3. Programs that never
+/// select plane 2 only produce 0 and 1.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Screen {
-    vram: [[u8; 64]; 32],
+    vram: [[u8; HIRES_WIDTH]; HIRES_HEIGHT],
+    hires: bool,
+    /// Bitplanes affected by drawing, clearing and scrolling
+    planes: u8,
 }
 
 impl Screen {
     pub fn new() -> Self {
-        let vram = [[0; 64]; 32];
+        let vram = [[0; HIRES_WIDTH]; HIRES_HEIGHT];
+        let hires = false;
+        let planes = 0b01;
 
-        Self { vram }
+        Self {
+            vram,
+            hires,
+            planes,
+        }
     }
     pub fn clear(&mut self) {
-        self.vram = [[0; 64]; 32];
+        self.vram = [[0; HIRES_WIDTH]; HIRES_HEIGHT];
     }
 
-    pub fn set_vram(&mut self, vram: [[u8; 64]; 32]) {
+    pub fn set_vram(&mut self, vram: [[u8; HIRES_WIDTH]; HIRES_HEIGHT]) {
         self.vram = vram;
     }
 
-    pub fn vram(&self) -> [[u8; 64]; 32] {
-        self.vram
+    pub fn vram(&self) -> &[[u8; HIRES_WIDTH]; HIRES_HEIGHT] {
+        &self.vram
     }
 
     pub fn set_xy(&mut self, x: usize, y: usize, v: u8) {
         self.vram[y][x] ^= v;
     }
 
-    pub fn vram_mut(&mut self) -> &mut [[u8; 64]; 32] {
+    pub fn vram_mut(&mut self) -> &mut [[u8; HIRES_WIDTH]; HIRES_HEIGHT] {
         &mut self.vram
     }
+
+    /// Clear the selected bitplanes only
+    pub fn clear_planes(&mut self) {
+        let keep = !self.planes;
+        for row in &mut self.vram {
+            for pixel in row.iter_mut() {
+                *pixel &= keep;
+            }
+        }
+    }
+
+    /// Selected bitplanes, bit 0 for plane 1 and bit 1 for plane 2
+    pub fn planes(&self) -> u8 {
+        self.planes
+    }
+
+    pub fn set_planes(&mut self, planes: u8) {
+        self.planes = planes & 0b11;
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> u8 {
+        self.vram[y][x]
+    }
+
+    pub fn is_hires(&self) -> bool {
+        self.hires
+    }
+
+    /// Switch between 64x32 and 128x64, the display is cleared
+    pub fn set_hires(&mut self, hires: bool) {
+        self.hires = hires;
+        self.clear();
+    }
+
+    pub fn width(&self) -> usize {
+        if self.hires {
+            HIRES_WIDTH
+        } else {
+            LORES_WIDTH
+        }
+    }
+
+    pub fn height(&self) -> usize {
+        if self.hires {
+            HIRES_HEIGHT
+        } else {
+            LORES_HEIGHT
+        }
+    }
+
+    /// Visible rows, each `width()` pixels long
+    pub fn rows(&self) -> impl Iterator<Item = &[u8]> {
+        let width = self.width();
+        self.vram[..self.height()]
+            .iter()
+            .map(move |row| &row[..width])
+    }
+
+    /// Lit pixels as `#` and dark ones as `.`, one line per row
+    pub fn to_ascii(&self) -> String {
+        let mut ascii = String::new();
+        for row in self.rows() {
+            ascii.extend(row.iter().map(|&pixel| if pixel != 0 { '#' } else { '.' }));
+            ascii.push('\n');
+        }
+        ascii
+    }
+
+    /// Binary PBM image, lit pixels are black
+    pub fn to_pbm(&self) -> Vec<u8> {
+        let mut pbm = format!("P4\n{} {}\n", self.width(), self.height()).into_bytes();
+        for row in self.rows() {
+            for pixels in row.chunks(8) {
+                let byte = pixels.iter().enumerate().fold(0, |byte, (i, &pixel)| {
+                    byte | ((pixel != 0) as u8) << (7 - i)
+                });
+                pbm.push(byte);
+            }
+        }
+        pbm
+    }
+
+    /// PNG image in the colors of `palette`, `scale` image pixels per screen
+    /// pixel in each direction
+    pub fn write_png(&self, output: impl Write, palette: &Palette, scale: usize) -> io::Result<()> {
+        let scale = scale.max(1);
+        let (width, height) = (self.width() * scale, self.height() * scale);
+        let mut encoder = png::Encoder::new(output, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut data = Vec::with_capacity(width * height * 3);
+        for row in self.rows() {
+            let line: Vec<u8> = row
+                .iter()
+                .flat_map(|&pixel| palette.color(pixel).repeat(scale))
+                .collect();
+            for _ in 0..scale {
+                data.extend(&line);
+            }
+        }
+
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&data)?;
+        writer.finish()?;
+        Ok(())
+    }
+
+    /// The screen as a file in `format`, `palette` and `scale` are for PNG
+    pub fn export(&self, format: ScreenFormat, palette: &Palette, scale: usize) -> Vec<u8> {
+        match format {
+            ScreenFormat::Text => self.to_string().into_bytes(),
+            ScreenFormat::Ascii => self.to_ascii().into_bytes(),
+            ScreenFormat::Pbm => self.to_pbm(),
+            ScreenFormat::Png => {
+                let mut png = vec![];
+                self.write_png(&mut png, palette, scale)
+                    .expect("writing to a Vec doesn't fail");
+                png
+            }
+        }
+    }
+
+    pub fn scroll_down(&mut self, n: usize) {
+        let height = self.height();
+        for y in (0..height).rev() {
+            let src = y.checked_sub(n);
+            self.scroll_row(y, src, 0);
+        }
+    }
+
+    pub fn scroll_up(&mut self, n: usize) {
+        let height = self.height();
+        for y in 0..height {
+            let src = Some(y + n).filter(|&src| src < height);
+            self.scroll_row(y, src, 0);
+        }
+    }
+
+    pub fn scroll_right(&mut self, n: usize) {
+        for y in 0..self.height() {
+            self.scroll_row(y, Some(y), -(n as isize));
+        }
+    }
+
+    pub fn scroll_left(&mut self, n: usize) {
+        for y in 0..self.height() {
+            self.scroll_row(y, Some(y), n as isize);
+        }
+    }
+
+    /// Replace the selected planes of row `y` with row `src` shifted by `dx`
+    /// columns, pixels from outside the display are blank
+    fn scroll_row(&mut self, y: usize, src: Option<usize>, dx: isize) {
+        let width = self.width();
+        let planes = self.planes;
+        let src_row = src.map(|src| self.vram[src]);
+        for x in 0..width {
+            let shifted = src_row
+                .zip(x.checked_add_signed(dx).filter(|&x| x < width))
+                .map_or(0, |(row, x)| row[x]);
+            let pixel = &mut self.vram[y][x];
+            *pixel = (*pixel & !planes) | (shifted & planes);
+        }
+    }
+}
+
+impl Default for Screen {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Visible pixels as their color index digits, one line per row
+impl fmt::Display for Screen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for pixel in row {
+                write!(f, "{pixel}")?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
 }
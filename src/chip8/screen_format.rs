@@ -0,0 +1,59 @@
+use std::{
+    fmt,
+    path::Path,
+    str::FromStr,
+};
+
+/// File format of a [`Screen`](super::Screen) export
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ScreenFormat {
+    /// Color index digit per pixel, see `Screen`'s `Display`
+    #[default]
+    Text,
+    /// `#` for lit pixels and `.` for dark ones
+    Ascii,
+    /// Binary PBM, lit pixels are black
+    Pbm,
+    /// PNG in the palette colors
+    Png,
+}
+
+impl ScreenFormat {
+    /// Format of a file name extension: `.txt`, `.pbm` or `.png`
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "txt" => Some(Self::Text),
+            "pbm" => Some(Self::Pbm),
+            "png" => Some(Self::Png),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for ScreenFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "ascii" => Ok(Self::Ascii),
+            "pbm" => Ok(Self::Pbm),
+            "png" => Ok(Self::Png),
+            _ => Err(format!(
+                "unknown screen format `{s}`, expected text, ascii, pbm or png"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ScreenFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Text => "text",
+            Self::Ascii => "ascii",
+            Self::Pbm => "pbm",
+            Self::Png => "png",
+        };
+        write!(f, "{name}")
+    }
+}
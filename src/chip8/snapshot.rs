@@ -0,0 +1,36 @@
+use super::{
+    Quirks,
+    Rng,
+    Screen,
+    Stack,
+    Timers,
+};
+
+/// Copy of the complete machine state, taken by [`Chip8::snapshot`] and put
+/// back by [`Chip8::restore`].
+///
+/// Settings like speed, instruction set and unknown opcode policy are not
+/// part of it, quirks are since a program only runs right with the quirks it
+/// was started with.
+///
+/// [`Chip8::snapshot`]: super::Chip8::snapshot
+/// [`Chip8::restore`]: super::Chip8::restore
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snapshot {
+    pub memory: Vec<u8>,
+    pub pc: usize,
+    pub opcode: u16,
+    pub v: [u8; 16],
+    pub i: usize,
+    pub stack: Stack,
+    pub timers: Timers,
+    pub screen: Screen,
+    pub wait_key: bool,
+    pub halted: bool,
+    pub vblank_wait: bool,
+    pub rpl: [u8; 16],
+    pub audio_pattern: Option<[u8; 16]>,
+    pub pitch: u8,
+    pub rng: Rng,
+    pub quirks: Quirks,
+}
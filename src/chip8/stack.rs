@@ -1,3 +1,8 @@
+/// Nesting depth of the original interpreters
+pub const STACK_DEPTH: usize = 16;
+
+/// Return addresses of the called subroutines
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Stack {
     stack: Vec<usize>,
 }
@@ -13,11 +18,21 @@ impl Stack {
         self.stack.push(value);
     }
 
-    pub fn ret(&mut self) -> usize {
-        self.stack.pop().unwrap()
+    pub fn ret(&mut self) -> Option<usize> {
+        self.stack.pop()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.stack.len() >= STACK_DEPTH
     }
 
     pub fn stack(&self) -> &[usize] {
         self.stack.as_ref()
     }
 }
+
+impl Default for Stack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,13 @@
+use super::{
+    instruction::Instruction,
+    opcode::Opcode,
+};
+
+/// Instruction executed by a single [`Chip8::step`](super::Chip8::step)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Step {
+    /// Address the instruction was fetched from
+    pub address: usize,
+    pub opcode: Opcode,
+    pub instruction: Instruction,
+}
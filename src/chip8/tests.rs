@@ -51,6 +51,89 @@ mod opcode {
     }
 }
 
+#[cfg(test)]
+mod instruction {
+    use crate::chip8::{
+        instruction::Instruction,
+        opcode::Opcode,
+    };
+
+    #[test]
+    fn decode() {
+        assert_eq!(Opcode::from(0x00E0).instruction(), Instruction::Cls);
+        assert_eq!(Opcode::from(0x0123).instruction(), Instruction::Sys(0x123));
+        assert_eq!(
+            Opcode::from(0x7A04).instruction(),
+            Instruction::AddByte(0xA, 0x04)
+        );
+        assert_eq!(
+            Opcode::from(0x8AB6).instruction(),
+            Instruction::Shr(0xA, 0xB)
+        );
+        assert_eq!(
+            Opcode::from(0xDAB1).instruction(),
+            Instruction::Drw(0xA, 0xB, 1)
+        );
+        assert_eq!(
+            Opcode::from(0x5121).instruction(),
+            Instruction::Unknown(0x5121)
+        );
+        assert_eq!(
+            Opcode::from(0xE1FF).instruction(),
+            Instruction::Unknown(0xE1FF)
+        );
+    }
+
+    #[test]
+    fn encode_decode_round_trip() {
+        for code in 0..=u16::MAX {
+            let opcode = Opcode::from(code);
+            let instruction = Instruction::decode(opcode);
+
+            assert_eq!(
+                instruction.encode(),
+                opcode,
+                "{code:04X} -> {instruction:?}"
+            );
+            assert_eq!(Instruction::decode(instruction.encode()), instruction);
+        }
+    }
+
+    #[test]
+    fn display() {
+        let cases = [
+            (0x00E0, "cls"),
+            (0x00EE, "ret"),
+            (0x1208, "jp 0x208"),
+            (0x2ABC, "call 0xABC"),
+            (0x3A40, "se va, 0x40"),
+            (0x6B06, "ld vb, 0x06"),
+            (0x8014, "add v0, v1"),
+            (0xA30C, "ld i, 0x30C"),
+            (0xB200, "jp v0, 0x200"),
+            (0xDAB1, "drw va, vb, 1"),
+            (0xF30A, "ld v3, k"),
+            (0xF555, "ld [i], v5"),
+            (0xF565, "ld v5, [i]"),
+            (0x00C4, "scd 4"),
+            (0x00FF, "high"),
+            (0xF130, "ld hf, v1"),
+            (0xF775, "ld r, v7"),
+            (0x00D4, "scu 4"),
+            (0x5132, "save v1, v3"),
+            (0x5313, "load v3, v1"),
+            (0xF000, "ld i, long"),
+            (0xF201, "plane 2"),
+            (0xF002, "audio"),
+            (0xF13A, "ld pitch, v1"),
+            (0xFFFF, "dw 0xFFFF"),
+        ];
+        for (code, mnemonic) in cases {
+            assert_eq!(Opcode::from(code).instruction().to_string(), mnemonic);
+        }
+    }
+}
+
 #[cfg(test)]
 mod instructions {
     use super::super::Chip8;
@@ -58,13 +141,13 @@ mod instructions {
     fn cls_00e0() {
         let mut chip8 = Chip8::new();
 
-        let vram_prev = [[0; 64]; 32];
-        let vram = [[1; 64]; 32];
+        let vram_prev = [[0; 128]; 64];
+        let vram = [[1; 128]; 64];
         chip8.screen.set_vram(vram);
 
         chip8.cls_00e0();
         assert_eq!(
-            vram_prev,
+            &vram_prev,
             chip8.screen.vram(),
             "Screen should be fill by 0 values"
         );
@@ -75,10 +158,10 @@ mod instructions {
 
         chip8.stack.push(123);
 
-        chip8.ret_00ee();
+        chip8.ret_00ee().unwrap();
         assert_eq!(
             chip8.stack.stack(),
-            vec![],
+            Vec::<usize>::new(),
             "It suppouse to remove last element from stack and set pc to it"
         );
     }
@@ -97,7 +180,7 @@ mod instructions {
 
         chip8.opcode.set_from_u16(0x2123);
 
-        chip8.call_2nnn();
+        chip8.call_2nnn().unwrap();
         assert_eq!(chip8.pc, 0x0123, "Program counter should change to 0x0123");
         assert_eq!(
             chip8.stack.stack(),
@@ -215,8 +298,9 @@ mod instructions {
         chip8.v[0x1] = 0x12;
         chip8.opcode.set_from_u16(0x8010);
 
+        let expected = chip8.v[0] ^ chip8.v[1];
         chip8.xor_8xy3();
-        assert_eq!(chip8.v[0], chip8.v[0] ^ chip8.v[1]);
+        assert_eq!(chip8.v[0], expected);
     }
     #[test]
     fn add_8xy4() {
@@ -277,6 +361,42 @@ mod instructions {
         assert_eq!(chip8.v[0xF], 1);
     }
     #[test]
+    fn flag_written_last() {
+        let mut chip8 = Chip8::new();
+
+        // VF -= V1 with equal values, no borrow
+        chip8.v[0xF] = 0x05;
+        chip8.v[0x1] = 0x05;
+        chip8.opcode.set_from_u16(0x8F15);
+        chip8.sub_8xy5();
+        assert_eq!(chip8.v[0xF], 1, "The flag replaces the result");
+
+        chip8.v[0xF] = 0x03;
+        chip8.v[0x1] = 0x05;
+        chip8.sub_8xy5();
+        assert_eq!(chip8.v[0xF], 0);
+
+        chip8.v[0xF] = 0x05;
+        chip8.opcode.set_from_u16(0x8F17);
+        chip8.subn_8xy7();
+        assert_eq!(chip8.v[0xF], 1);
+
+        chip8.v[0xF] = 0xFF;
+        chip8.opcode.set_from_u16(0x8F14);
+        chip8.add_8xy4();
+        assert_eq!(chip8.v[0xF], 1);
+
+        chip8.v[0xF] = 0x02;
+        chip8.opcode.set_from_u16(0x8F06);
+        chip8.shr_8xy6();
+        assert_eq!(chip8.v[0xF], 0);
+
+        chip8.v[0xF] = 0x80;
+        chip8.opcode.set_from_u16(0x8F0E);
+        chip8.shl_8x0e();
+        assert_eq!(chip8.v[0xF], 1);
+    }
+    #[test]
     fn sne_9xy0() {
         let mut chip8 = Chip8::new();
 
@@ -315,4 +435,852 @@ mod instructions {
 
         assert_eq!(chip8.pc, v0 + 0x0123);
     }
+    #[test]
+    fn skp_ex9e() {
+        let mut chip8 = Chip8::new();
+
+        chip8.v[0] = 0xA;
+        chip8.opcode.set_from_u16(0xE09E);
+
+        let pc = chip8.pc;
+        chip8.skp_ex9e();
+        assert_eq!(chip8.pc, pc);
+
+        let mut keys = [false; 16];
+        keys[0xA] = true;
+        chip8.keypad_mut().set_keys(keys);
+
+        chip8.skp_ex9e();
+        assert_eq!(chip8.pc, pc + 2);
+    }
+    #[test]
+    fn ld_fx0a() {
+        let mut chip8 = Chip8::new();
+
+        chip8.opcode.set_from_u16(0xF30A);
+
+        chip8.ld_fx0a();
+        assert!(chip8.wait_key, "Should wait until a key is pressed");
+
+        let mut keys = [false; 16];
+        keys[0x7] = true;
+        chip8.keypad_mut().set_keys(keys);
+
+        chip8.step().unwrap();
+        assert!(!chip8.wait_key);
+        assert_eq!(chip8.v[3], 0x7);
+    }
+}
+
+#[cfg(test)]
+mod step {
+    use {
+        super::super::{
+            Chip8,
+            Instruction,
+            Step,
+        },
+        crate::chip8::opcode::Opcode,
+    };
+
+    #[test]
+    fn headless() {
+        let mut chip8 = Chip8::new();
+
+        // 0x200: LD V1, 0x2A; 0x202: JP 0x200
+        chip8.memory[0x200..0x204].copy_from_slice(&[0x61, 0x2A, 0x12, 0x00]);
+
+        chip8.step().unwrap();
+        assert_eq!(chip8.v[1], 0x2A);
+        assert_eq!(chip8.pc, 0x202);
+
+        chip8.step().unwrap();
+        assert_eq!(chip8.pc, 0x200);
+    }
+
+    #[test]
+    fn returns_executed_instruction() {
+        let mut chip8 = Chip8::new();
+
+        chip8.load_rom(&[0x00, 0xE0]).unwrap();
+
+        let mut opcode = Opcode::new();
+        opcode.set_from_u16(0x00E0);
+        assert_eq!(
+            chip8.step().unwrap(),
+            Step {
+                address: 0x200,
+                opcode,
+                instruction: Instruction::Cls,
+            }
+        );
+    }
+
+    #[test]
+    fn run_frame_ticks_timers_once() {
+        let mut chip8 = Chip8::new();
+
+        // 0x200: LD V0, 0x05; 0x202: LD DT, V0; 0x204: JP 0x204
+        chip8
+            .load_rom(&[0x60, 0x05, 0xF0, 0x15, 0x12, 0x04])
+            .unwrap();
+
+        chip8.run_frame().unwrap();
+        assert_eq!(chip8.timers().delay(), 4);
+        assert_eq!(chip8.pc(), 0x204);
+
+        chip8.run_cycles(100).unwrap();
+        assert_eq!(chip8.timers().delay(), 4);
+    }
+}
+
+#[cfg(test)]
+mod errors {
+    use super::super::{
+        Chip8,
+        Chip8Error,
+        MEMORY_SIZE,
+        PROGRAM_START,
+    };
+
+    #[test]
+    fn rom_too_large() {
+        let mut chip8 = Chip8::new();
+
+        let rom = vec![0; MEMORY_SIZE - PROGRAM_START + 1];
+        assert!(matches!(
+            chip8.load_rom(&rom),
+            Err(Chip8Error::RomTooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn stack_underflow() {
+        let mut chip8 = Chip8::new();
+
+        chip8.load_rom(&[0x00, 0xEE]).unwrap();
+        assert!(matches!(
+            chip8.step(),
+            Err(Chip8Error::StackUnderflow { pc: 0x200 })
+        ));
+    }
+
+    #[test]
+    fn stack_overflow() {
+        let mut chip8 = Chip8::new();
+
+        // 0x200: CALL 0x200
+        chip8.load_rom(&[0x22, 0x00]).unwrap();
+        for _ in 0..16 {
+            chip8.step().unwrap();
+        }
+        assert!(matches!(
+            chip8.step(),
+            Err(Chip8Error::StackOverflow { pc: 0x200 })
+        ));
+    }
+
+    #[test]
+    fn memory_out_of_range() {
+        let mut chip8 = Chip8::new();
+
+        // 0x200: LD I, 0xFFE; 0x202: LD [I], V3
+        chip8.load_rom(&[0xAF, 0xFE, 0xF3, 0x55]).unwrap();
+        chip8.step().unwrap();
+        assert!(matches!(
+            chip8.step(),
+            Err(Chip8Error::MemoryOutOfRange {
+                pc: 0x202,
+                address: 0xFFE
+            })
+        ));
+    }
+
+    #[test]
+    fn invalid_pc() {
+        let mut chip8 = Chip8::new();
+
+        // 0x200: JP 0xFFF
+        chip8.load_rom(&[0x1F, 0xFF]).unwrap();
+        chip8.step().unwrap();
+        assert!(matches!(
+            chip8.step(),
+            Err(Chip8Error::InvalidPc { pc: 0xFFF })
+        ));
+    }
+}
+
+#[cfg(test)]
+mod unknown_opcode {
+    use super::super::{
+        Chip8,
+        Chip8Error,
+        UnknownOpcodePolicy,
+    };
+
+    #[test]
+    fn counts_skipped_opcodes() {
+        let mut chip8 = Chip8::new();
+        chip8.set_unknown_opcode_policy(UnknownOpcodePolicy::Ignore);
+
+        // 0x200: SYS 0x123; 0x202: 0xFFFF; 0x204: SYS 0x123
+        chip8
+            .load_rom(&[0x01, 0x23, 0xFF, 0xFF, 0x01, 0x23])
+            .unwrap();
+        chip8.run_cycles(3).unwrap();
+
+        assert_eq!(chip8.pc(), 0x206);
+        assert_eq!(
+            chip8.unknown_opcodes().iter().collect::<Vec<_>>(),
+            vec![(&0x0123, &2), (&0xFFFF, &1)]
+        );
+    }
+
+    #[test]
+    fn halt() {
+        let mut chip8 = Chip8::new();
+        chip8.set_unknown_opcode_policy(UnknownOpcodePolicy::Halt);
+
+        // 0x200: LD V2, 0x07; 0x202: 0x5121
+        chip8.load_rom(&[0x62, 0x07, 0x51, 0x21]).unwrap();
+        chip8.step().unwrap();
+
+        match chip8.step() {
+            Err(Chip8Error::UnknownOpcode {
+                pc,
+                opcode,
+                registers,
+            }) => {
+                assert_eq!(pc, 0x202);
+                assert_eq!(opcode, 0x5121);
+                assert_eq!(registers.pc, 0x202);
+                assert_eq!(registers.v[2], 0x07);
+            }
+            result => panic!("Expected unknown opcode error, got {result:?}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod quirks {
+    use super::super::{
+        Chip8,
+        Quirks,
+    };
+
+    fn chip8_with(quirks: Quirks) -> Chip8 {
+        let mut chip8 = Chip8::new();
+        chip8.set_quirks(quirks);
+        chip8
+    }
+
+    #[test]
+    fn apply() {
+        let mut quirks = Quirks::default();
+        quirks.apply("shift-uses-vy").unwrap();
+        quirks.apply("clip-sprites=true").unwrap();
+        quirks.apply("clip-sprites=false").unwrap();
+
+        assert!(quirks.shift_uses_vy);
+        assert!(!quirks.clip_sprites);
+        assert!(quirks.apply("no-such-quirk").is_err());
+        assert!(quirks.apply("display-wait=maybe").is_err());
+    }
+
+    #[test]
+    fn shift_uses_vy() {
+        let mut chip8 = chip8_with(Quirks {
+            shift_uses_vy: true,
+            ..Quirks::default()
+        });
+
+        chip8.v[0x0] = 0xFF;
+        chip8.v[0x1] = 0x2;
+        chip8.opcode.set_from_u16(0x8016);
+        chip8.shr_8xy6();
+        assert_eq!(chip8.v[0x0], 0x1);
+        assert_eq!(chip8.v[0xF], 0);
+
+        chip8.opcode.set_from_u16(0x801E);
+        chip8.shl_8x0e();
+        assert_eq!(chip8.v[0x0], 0x4);
+    }
+
+    #[test]
+    fn load_store_increments_i() {
+        let mut chip8 = chip8_with(Quirks {
+            load_store_increments_i: true,
+            ..Quirks::default()
+        });
+
+        chip8.i = 0x300;
+        chip8.opcode.set_from_u16(0xF255);
+        chip8.ld_fx55().unwrap();
+        assert_eq!(chip8.i, 0x303);
+
+        chip8.opcode.set_from_u16(0xF165);
+        chip8.ld_fx65().unwrap();
+        assert_eq!(chip8.i, 0x305);
+    }
+
+    #[test]
+    fn load_store_increments_i_by_x() {
+        let mut chip8 = chip8_with(Quirks {
+            load_store_increments_i: true,
+            load_store_increments_i_by_x: true,
+            ..Quirks::default()
+        });
+
+        chip8.i = 0x300;
+        chip8.opcode.set_from_u16(0xF255);
+        chip8.ld_fx55().unwrap();
+        assert_eq!(chip8.i, 0x302);
+
+        chip8.opcode.set_from_u16(0xF165);
+        chip8.ld_fx65().unwrap();
+        assert_eq!(chip8.i, 0x303);
+    }
+
+    #[test]
+    fn jump_uses_vx() {
+        let mut chip8 = chip8_with(Quirks {
+            jump_uses_vx: true,
+            ..Quirks::default()
+        });
+
+        chip8.v[0x0] = 0x10;
+        chip8.v[0x2] = 0x20;
+        chip8.opcode.set_from_u16(0xB230);
+        chip8.jp_bnnn();
+        assert_eq!(chip8.pc, 0x250);
+    }
+
+    #[test]
+    fn logic_resets_vf() {
+        let mut chip8 = chip8_with(Quirks {
+            logic_resets_vf: true,
+            ..Quirks::default()
+        });
+
+        chip8.v[0xF] = 1;
+        chip8.opcode.set_from_u16(0x8011);
+        chip8.or_8xy1();
+        assert_eq!(chip8.v[0xF], 0);
+    }
+
+    #[test]
+    fn clip_sprites() {
+        let mut chip8 = chip8_with(Quirks {
+            clip_sprites: true,
+            ..Quirks::default()
+        });
+
+        // Font sprite "0" drawn at (60, 30)
+        chip8.i = 0;
+        chip8.v[0x0] = 60;
+        chip8.v[0x1] = 30;
+        chip8.opcode.set_from_u16(0xD015);
+        chip8.drw_dxyn().unwrap();
+
+        let vram = chip8.screen.vram();
+        assert_eq!(vram[30][60..64], [1, 1, 1, 1]);
+        assert_eq!(vram[0][..64], [0; 64], "Rows below the edge are clipped");
+        assert_eq!(vram[31][0..4], [0; 4], "Columns past the edge are clipped");
+    }
+
+    #[test]
+    fn wrap_sprites() {
+        let mut chip8 = Chip8::new();
+
+        chip8.i = 0;
+        chip8.v[0x0] = 62;
+        chip8.v[0x1] = 30;
+        chip8.opcode.set_from_u16(0xD015);
+        chip8.drw_dxyn().unwrap();
+
+        let vram = chip8.screen.vram();
+        assert_eq!(vram[30][0..2], [1, 1]);
+        assert_eq!(vram[0][62..64], [1, 0]);
+    }
+
+    #[test]
+    fn display_wait() {
+        let mut chip8 = chip8_with(Quirks {
+            display_wait: true,
+            ..Quirks::default()
+        });
+
+        // 0x200: DRW V0, V0, 1; 0x202: JP 0x200
+        chip8.load_rom(&[0xD0, 0x01, 0x12, 0x00]).unwrap();
+        chip8.run_frame().unwrap();
+
+        assert_eq!(chip8.pc, 0x202, "Frame should end after the draw");
+    }
+
+    #[test]
+    fn add_i_overflow_sets_vf() {
+        let mut chip8 = chip8_with(Quirks {
+            add_i_overflow_sets_vf: true,
+            ..Quirks::default()
+        });
+
+        chip8.i = 0xFFF;
+        chip8.v[0x0] = 0x1;
+        chip8.opcode.set_from_u16(0xF01E);
+        chip8.add_fx1e();
+        assert_eq!(chip8.v[0xF], 1);
+    }
+}
+
+#[cfg(test)]
+mod super_chip {
+    use super::super::Chip8;
+
+    #[test]
+    fn hires() {
+        let mut chip8 = Chip8::new();
+
+        chip8.opcode.set_from_u16(0x00FF);
+        chip8.high_00ff();
+        assert_eq!((chip8.screen.width(), chip8.screen.height()), (128, 64));
+
+        // Font sprite "0" drawn at (126, 62) wraps at 128x64
+        chip8.i = 0;
+        chip8.v[0x0] = 126;
+        chip8.v[0x1] = 62;
+        chip8.opcode.set_from_u16(0xD015);
+        chip8.drw_dxyn().unwrap();
+        assert_eq!(chip8.screen.pixel(126, 62), 1);
+        assert_eq!(chip8.screen.pixel(0, 62), 1);
+        assert_eq!(chip8.screen.pixel(126, 0), 1);
+
+        chip8.opcode.set_from_u16(0x00FE);
+        chip8.low_00fe();
+        assert_eq!((chip8.screen.width(), chip8.screen.height()), (64, 32));
+        assert!(chip8.screen.rows().all(|row| row.iter().all(|&p| p == 0)));
+    }
+
+    #[test]
+    fn drw_16x16() {
+        let mut chip8 = Chip8::new();
+
+        chip8.memory[0x300..0x320].copy_from_slice(&[0xFF; 32]);
+        chip8.i = 0x300;
+        chip8.opcode.set_from_u16(0xD000);
+        chip8.drw_dxyn().unwrap();
+
+        let lit = chip8.screen.rows().flatten().filter(|&&p| p == 1).count();
+        assert_eq!(lit, 16 * 16);
+        assert_eq!(chip8.v[0xF], 0);
+
+        chip8.drw_dxyn().unwrap();
+        assert_eq!(chip8.v[0xF], 1);
+    }
+
+    #[test]
+    fn scroll() {
+        let mut chip8 = Chip8::new();
+
+        chip8.screen.set_xy(10, 10, 1);
+
+        chip8.opcode.set_from_u16(0x00C3);
+        chip8.scd_00cn();
+        assert_eq!(chip8.screen.pixel(10, 13), 1);
+
+        chip8.scr_00fb();
+        assert_eq!(chip8.screen.pixel(14, 13), 1);
+
+        chip8.scl_00fc();
+        chip8.scl_00fc();
+        assert_eq!(chip8.screen.pixel(6, 13), 1);
+        assert_eq!(
+            chip8.screen.rows().flatten().filter(|&&p| p == 1).count(),
+            1
+        );
+    }
+
+    #[test]
+    fn exit() {
+        let mut chip8 = Chip8::new();
+
+        chip8.load_rom(&[0x00, 0xFD]).unwrap();
+        chip8.run_frame().unwrap();
+
+        assert!(chip8.is_halted());
+        assert_eq!(chip8.pc, 0x200);
+    }
+
+    #[test]
+    fn ld_fx30() {
+        let mut chip8 = Chip8::new();
+
+        chip8.v[0x3] = 0x2;
+        chip8.opcode.set_from_u16(0xF330);
+        chip8.ld_fx30();
+        assert_eq!(chip8.i, 0x50 + 2 * 10);
+        assert_eq!(chip8.memory[chip8.i], 0x3E);
+    }
+
+    #[test]
+    fn rpl_flags() {
+        let mut chip8 = Chip8::new();
+
+        chip8.v[..3].copy_from_slice(&[1, 2, 3]);
+        chip8.opcode.set_from_u16(0xF275);
+        chip8.ld_fx75();
+
+        chip8.v = [0; 16];
+        chip8.opcode.set_from_u16(0xF185);
+        chip8.ld_fx85();
+        assert_eq!(chip8.v[..3], [1, 2, 0]);
+    }
+}
+
+#[cfg(test)]
+mod xo_chip {
+    use super::super::{
+        Chip8,
+        XO_MEMORY_SIZE,
+    };
+
+    #[test]
+    fn memory_size() {
+        let chip8 = Chip8::with_memory_size(XO_MEMORY_SIZE);
+        assert_eq!(chip8.memory().len(), 0x10000);
+
+        let chip8 = Chip8::with_memory_size(0x100);
+        assert_eq!(chip8.memory().len(), 0x1000);
+    }
+
+    #[test]
+    fn long_i() {
+        let mut chip8 = Chip8::with_memory_size(XO_MEMORY_SIZE);
+
+        // ld i, long 0xABCD; se v0, 0; ld i, long 0x1234; cls
+        chip8
+            .load_rom(&[
+                0xF0, 0x00, 0xAB, 0xCD, 0x30, 0x00, 0xF0, 0x00, 0x12, 0x34, 0x00, 0xE0,
+            ])
+            .unwrap();
+
+        chip8.step().unwrap();
+        assert_eq!(chip8.i, 0xABCD);
+        assert_eq!(chip8.pc, 0x204);
+
+        // The skip jumps over the whole four byte instruction
+        chip8.step().unwrap();
+        assert_eq!(chip8.pc, 0x20A);
+        assert_eq!(chip8.i, 0xABCD);
+    }
+
+    #[test]
+    fn planes() {
+        let mut chip8 = Chip8::new();
+
+        chip8.opcode.set_from_u16(0xF301);
+        chip8.plane_fn01();
+        assert_eq!(chip8.screen.planes(), 0b11);
+
+        // Plane 1 gets the first sprite, plane 2 the second one
+        chip8.i = 0x300;
+        chip8.memory[0x300] = 0b1100_0000;
+        chip8.memory[0x301] = 0b1010_0000;
+        chip8.opcode.set_from_u16(0xD001);
+        chip8.drw_dxyn().unwrap();
+        assert_eq!(chip8.screen.vram()[0][..3], [0b11, 0b01, 0b10]);
+        assert_eq!(chip8.v[0xF], 0);
+
+        // A collision on plane 2 only still sets VF
+        chip8.opcode.set_from_u16(0xF201);
+        chip8.plane_fn01();
+        chip8.memory[0x300] = 0b0010_0000;
+        chip8.opcode.set_from_u16(0xD001);
+        chip8.drw_dxyn().unwrap();
+        assert_eq!(chip8.screen.vram()[0][..3], [0b11, 0b01, 0b00]);
+        assert_eq!(chip8.v[0xF], 1);
+
+        // Clearing leaves plane 1 alone
+        chip8.cls_00e0();
+        assert_eq!(chip8.screen.vram()[0][..3], [0b01, 0b01, 0b00]);
+    }
+
+    #[test]
+    fn scroll_selected_plane() {
+        let mut chip8 = Chip8::new();
+
+        chip8.screen.set_xy(0, 5, 0b11);
+        chip8.opcode.set_from_u16(0xF201);
+        chip8.plane_fn01();
+
+        chip8.opcode.set_from_u16(0x00D2);
+        chip8.scu_00dn();
+        assert_eq!(chip8.screen.pixel(0, 5), 0b01);
+        assert_eq!(chip8.screen.pixel(0, 3), 0b10);
+    }
+
+    #[test]
+    fn save_load() {
+        let mut chip8 = Chip8::new();
+
+        chip8.i = 0x300;
+        chip8.v[1..4].copy_from_slice(&[1, 2, 3]);
+        chip8.opcode.set_from_u16(0x5132);
+        chip8.save_5xy2().unwrap();
+        assert_eq!(chip8.memory[0x300..0x303], [1, 2, 3]);
+        assert_eq!(chip8.i, 0x300);
+
+        chip8.opcode.set_from_u16(0x5312);
+        chip8.save_5xy2().unwrap();
+        assert_eq!(chip8.memory[0x300..0x303], [3, 2, 1]);
+
+        chip8.memory[0x300..0x303].copy_from_slice(&[7, 8, 9]);
+        chip8.opcode.set_from_u16(0x5463);
+        chip8.load_5xy3().unwrap();
+        assert_eq!(chip8.v[4..7], [7, 8, 9]);
+
+        chip8.opcode.set_from_u16(0x5A83);
+        chip8.load_5xy3().unwrap();
+        assert_eq!(chip8.v[8..11], [9, 8, 7]);
+    }
+
+    #[test]
+    fn audio() {
+        let mut chip8 = Chip8::new();
+        assert_eq!(chip8.audio_pattern(), None);
+        assert_eq!(chip8.pitch(), 64);
+
+        chip8.i = 0x300;
+        chip8.memory[0x300..0x310].copy_from_slice(&[0xAA; 16]);
+        chip8.opcode.set_from_u16(0xF002);
+        chip8.audio_f002().unwrap();
+        assert_eq!(chip8.audio_pattern(), Some([0xAA; 16]));
+
+        chip8.v[0x1] = 112;
+        chip8.opcode.set_from_u16(0xF13A);
+        chip8.ld_fx3a();
+        assert_eq!(chip8.pitch(), 112);
+    }
+}
+
+#[cfg(test)]
+mod platform {
+    use super::super::{
+        Chip8,
+        Font,
+        Instruction,
+        InstructionSet,
+        Platform,
+        UnknownOpcodePolicy,
+    };
+
+    #[test]
+    fn names() {
+        for name in Platform::NAMES {
+            let platform: Platform = name.parse().unwrap();
+            assert_eq!(platform.to_string(), name);
+        }
+        assert!("chip-9".parse::<Platform>().is_err());
+    }
+
+    #[test]
+    fn cosmac_vip() {
+        let chip8 = Chip8::with_platform(Platform::CosmacVip);
+
+        assert_eq!(chip8.instruction_set(), InstructionSet::Chip8);
+        assert_eq!(chip8.font(), Font::CosmacVip);
+        assert_eq!(chip8.instructions_per_frame(), 15);
+        assert_eq!(chip8.memory().len(), 0x1000);
+        assert!(chip8.quirks().shift_uses_vy);
+        assert!(chip8.quirks().display_wait);
+        // The VIP "1" has a wider top than the CHIP-48 one
+        assert_eq!(chip8.memory()[5], 0x60);
+    }
+
+    #[test]
+    fn chip48_increments_i_by_x() {
+        for platform in [Platform::Chip48, Platform::SuperChip10] {
+            let mut chip8 = Chip8::with_platform(platform);
+            // ld i, 0x300; ld [i], v2
+            chip8.load_rom(&[0xA3, 0x00, 0xF2, 0x55]).unwrap();
+            chip8.run_cycles(2).unwrap();
+
+            assert_eq!(chip8.i(), 0x302, "{platform}");
+        }
+    }
+
+    #[test]
+    fn xo_chip() {
+        let chip8 = Chip8::with_platform(Platform::XoChip);
+
+        assert_eq!(chip8.instruction_set(), InstructionSet::XoChip);
+        assert_eq!(chip8.memory().len(), 0x10000);
+        assert!(!chip8.quirks().clip_sprites);
+    }
+
+    #[test]
+    fn super_chip_font() {
+        let chip8 = Chip8::with_platform(Platform::SuperChip11);
+
+        // Big digits stop at 9
+        assert_ne!(chip8.memory()[0x50 + 9 * 10], 0);
+        assert!(chip8.memory()[0x50 + 10 * 10..0x50 + 16 * 10]
+            .iter()
+            .all(|&b| b == 0));
+    }
+
+    #[test]
+    fn instruction_set() {
+        assert!(InstructionSet::Chip8.supports(&Instruction::Cls));
+        assert!(!InstructionSet::Chip8.supports(&Instruction::High));
+        assert!(InstructionSet::SuperChip10.supports(&Instruction::High));
+        assert!(!InstructionSet::SuperChip10.supports(&Instruction::Scr));
+        assert!(InstructionSet::SuperChip11.supports(&Instruction::Scd(1)));
+        assert!(!InstructionSet::SuperChip11.supports(&Instruction::Audio));
+        assert!(InstructionSet::XoChip.supports(&Instruction::Audio));
+    }
+
+    #[test]
+    fn unsupported_instruction() {
+        let mut chip8 = Chip8::with_platform(Platform::Chip48);
+        chip8.set_unknown_opcode_policy(UnknownOpcodePolicy::Ignore);
+
+        // high; drw v0, v0, 0
+        chip8.load_rom(&[0x00, 0xFF, 0xD0, 0x00]).unwrap();
+        chip8.run_cycles(2).unwrap();
+
+        assert!(!chip8.screen().is_hires());
+        assert_eq!(chip8.unknown_opcodes().get(&0x00FF), Some(&1));
+        assert!(chip8.screen().rows().flatten().all(|&p| p == 0));
+    }
+
+    #[test]
+    fn override_memory_size() {
+        let mut chip8 = Chip8::with_platform(Platform::CosmacVip);
+
+        chip8.set_memory_size(0x10000);
+        assert_eq!(chip8.memory().len(), 0x10000);
+        assert_eq!(chip8.memory()[5], 0x60);
+    }
+}
+
+#[cfg(test)]
+mod random {
+    use super::super::Chip8;
+
+    /// Values of `rnd v0, 0xFF` run `n` times
+    fn random_bytes(chip8: &mut Chip8, n: usize) -> Vec<u8> {
+        (0..n)
+            .map(|_| {
+                chip8.opcode.set_from_u16(0xC0FF);
+                chip8.rnd_cxnn();
+                chip8.v[0x0]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn seeded() {
+        let mut a = Chip8::new();
+        let mut b = Chip8::new();
+        a.set_seed(42);
+        b.set_seed(42);
+
+        assert_eq!(a.seed(), 42);
+        assert_eq!(random_bytes(&mut a, 32), random_bytes(&mut b, 32));
+
+        b.set_seed(43);
+        assert_ne!(random_bytes(&mut a, 32), random_bytes(&mut b, 32));
+    }
+
+    #[test]
+    fn mask() {
+        let mut chip8 = Chip8::new();
+        chip8.set_seed(7);
+
+        for _ in 0..64 {
+            chip8.opcode.set_from_u16(0xC10F);
+            chip8.rnd_cxnn();
+            assert_eq!(chip8.v[0x1] & 0xF0, 0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod screen {
+    use std::path::Path;
+
+    use {
+        super::super::{
+            Chip8,
+            Screen,
+            ScreenFormat,
+        },
+        crate::palette::Palette,
+    };
+
+    /// Font sprite "1" drawn at (1, 0)
+    fn screen() -> Screen {
+        let mut chip8 = Chip8::new();
+        chip8.i = 5;
+        chip8.v[0x0] = 1;
+        chip8.opcode.set_from_u16(0xD015);
+        chip8.drw_dxyn().unwrap();
+        chip8.screen
+    }
+
+    #[test]
+    fn ascii() {
+        let ascii = screen().to_ascii();
+        let rows: Vec<&str> = ascii.lines().collect();
+
+        assert_eq!(rows.len(), 32);
+        assert_eq!(
+            rows[..6].iter().map(|row| &row[..6]).collect::<Vec<_>>(),
+            ["...#..", "..##..", "...#..", "...#..", "..###.", "......"]
+        );
+    }
+
+    #[test]
+    fn pbm() {
+        let pbm = screen().to_pbm();
+
+        assert!(pbm.starts_with(b"P4\n64 32\n"));
+        assert_eq!(pbm.len(), 9 + 32 * 8);
+        assert_eq!(pbm[9..11], [0b0001_0000, 0]);
+        assert_eq!(pbm[17], 0b0011_0000);
+    }
+
+    #[test]
+    fn png() {
+        let palette: Palette = "#000000,#FFFFFF".parse().unwrap();
+        let png = screen().export(ScreenFormat::Png, &palette, 2);
+
+        let mut reader = png::Decoder::new(png.as_slice()).read_info().unwrap();
+        let mut data = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut data).unwrap();
+        assert_eq!((info.width, info.height), (128, 64));
+        // Pixel (3, 0) of the screen is image pixels (6..8, 0..2)
+        let pixel = |x: usize, y: usize| &data[(y * 128 + x) * 3..][..3];
+        assert_eq!(pixel(5, 1), [0, 0, 0]);
+        assert_eq!(pixel(6, 1), [0xFF, 0xFF, 0xFF]);
+        assert_eq!(pixel(7, 0), [0xFF, 0xFF, 0xFF]);
+        assert_eq!(pixel(8, 0), [0, 0, 0]);
+    }
+
+    #[test]
+    fn formats() {
+        assert_eq!("ascii".parse(), Ok(ScreenFormat::Ascii));
+        assert!("gif".parse::<ScreenFormat>().is_err());
+        assert_eq!(
+            ScreenFormat::from_path(Path::new("out/screen.png")),
+            Some(ScreenFormat::Png)
+        );
+        assert_eq!(ScreenFormat::from_path(Path::new("screen")), None);
+
+        assert!("#000000".parse::<Palette>().is_err());
+        assert!("#000000,white".parse::<Palette>().is_err());
+        let palette: Palette = "#101010, #FFAA00".parse().unwrap();
+        assert_eq!(palette.color(1), [0xFF, 0xAA, 0x00]);
+        assert_eq!(palette.color(2), Palette::default().color(2));
+    }
 }
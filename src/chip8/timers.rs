@@ -1,3 +1,5 @@
+/// Delay and sound timers, both count down to 0 at 60 Hz
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Timers {
     delay: u8,
     sound: u8,
@@ -29,9 +31,15 @@ impl Timers {
     pub fn countdown(&mut self) {
         if self.delay > 0 {
             self.delay -= 1;
-        } 
+        }
         if self.sound > 0 {
             self.sound -= 1;
-        } 
+        }
+    }
+}
+
+impl Default for Timers {
+    fn default() -> Self {
+        Self::new()
     }
 }
@@ -0,0 +1,44 @@
+use std::{
+    fmt,
+    str::FromStr,
+};
+
+/// What to do when an opcode has no known or implemented instruction, like
+/// `0NNN` machine code calls
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UnknownOpcodePolicy {
+    /// Skip the opcode silently
+    Ignore,
+    /// Skip the opcode and log a warning with PC and opcode the first time it
+    /// is met
+    #[default]
+    Warn,
+    /// Stop with [`Chip8Error::UnknownOpcode`](super::Chip8Error::UnknownOpcode)
+    Halt,
+}
+
+impl FromStr for UnknownOpcodePolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ignore" => Ok(Self::Ignore),
+            "warn" => Ok(Self::Warn),
+            "halt" => Ok(Self::Halt),
+            _ => Err(format!(
+                "unknown opcode policy `{s}`, expected ignore, warn or halt"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for UnknownOpcodePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Ignore => "ignore",
+            Self::Warn => "warn",
+            Self::Halt => "halt",
+        };
+        write!(f, "{name}")
+    }
+}
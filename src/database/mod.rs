@@ -0,0 +1,251 @@
+use std::{
+    collections::{
+        BTreeMap,
+        HashMap,
+    },
+    error::Error,
+    fmt,
+    fs,
+    io,
+    path::Path,
+};
+
+use serde::Deserialize;
+
+mod tests;
+
+use crate::{
+    chip8::{
+        Platform,
+        Quirks,
+    },
+    palette::{
+        parse_color,
+        Palette,
+    },
+};
+
+/// Entries compiled into the binary, in chip-8-database `programs.json`
+/// format.
+///
+/// The community database isn't vendored yet, so this list is empty and ROMs
+/// are only identified by a local database, see `--database`.
+const BUNDLED: &str = include_str!("programs.json");
+
+/// Settings for known ROMs, keyed by the SHA-1 of the ROM image.
+///
+/// Entries use the `programs.json` format of the chip-8-database project:
+/// a list of programs, each with a `roms` object mapping SHA-1 hashes to
+/// platforms, quirks, tick rate, colors and key hints.
+#[derive(Debug)]
+pub struct RomDatabase {
+    roms: HashMap<String, RomInfo>,
+}
+
+/// What the database knows about one ROM image
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RomInfo {
+    pub title: String,
+    /// First platform of the entry this interpreter knows, `None` for the
+    /// defaults
+    pub platform: Option<Platform>,
+    /// Platform quirks with the entry's adjustments
+    pub quirks: Option<Quirks>,
+    pub instructions_per_frame: Option<usize>,
+    pub palette: Option<Palette>,
+    /// Hex keys by their role in the game, like `up` or `a`
+    pub keys: BTreeMap<String, u8>,
+}
+
+/// Reading or parsing a database file failed
+#[derive(Debug)]
+pub enum DatabaseError {
+    Io(io::Error),
+    Json(serde_json::Error),
+}
+
+impl RomDatabase {
+    pub fn new() -> Self {
+        let roms = HashMap::new();
+
+        Self { roms }
+    }
+
+    /// The database shipped with the crate
+    pub fn bundled() -> Self {
+        Self::from_json(BUNDLED).expect("bundled ROM database is valid")
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, DatabaseError> {
+        let programs: Vec<Program> = serde_json::from_str(json)?;
+
+        let mut roms = HashMap::new();
+        for program in programs {
+            for (hash, rom) in program.roms {
+                roms.insert(hash.to_lowercase(), rom.info(&program.title));
+            }
+        }
+
+        Ok(Self { roms })
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, DatabaseError> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Add the entries of `other`, replacing entries for the same ROM
+    pub fn extend(&mut self, other: Self) {
+        self.roms.extend(other.roms);
+    }
+
+    pub fn len(&self) -> usize {
+        self.roms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.roms.is_empty()
+    }
+
+    /// Entry for a lowercase hex SHA-1
+    pub fn get(&self, sha1: &str) -> Option<&RomInfo> {
+        self.roms.get(sha1)
+    }
+
+    /// Entry for a ROM image
+    pub fn identify(&self, rom: &[u8]) -> Option<&RomInfo> {
+        self.get(&sha1_hex(rom))
+    }
+}
+
+impl Default for RomDatabase {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Lowercase hex SHA-1 of a ROM image, the key of database entries
+pub fn sha1_hex(rom: &[u8]) -> String {
+    sha1_smol::Sha1::from(rom).digest().to_string()
+}
+
+/// Platform for a chip-8-database platform id
+fn platform_from_id(id: &str) -> Option<Platform> {
+    match id {
+        "originalChip8" | "hybridVIP" => Some(Platform::CosmacVip),
+        "chip48" => Some(Platform::Chip48),
+        "superchip1" => Some(Platform::SuperChip10),
+        "superchip" => Some(Platform::SuperChip11),
+        "modernChip8" => Some(Platform::ModernSuperChip),
+        "xochip" => Some(Platform::XoChip),
+        _ => None,
+    }
+}
+
+/// Apply a chip-8-database quirk, unknown names are ignored
+fn apply_quirk(quirks: &mut Quirks, name: &str, enabled: bool) {
+    match name {
+        // The database names the quirk after the modern behavior
+        "shift" => quirks.shift_uses_vy = !enabled,
+        "memoryLeaveIUnchanged" => quirks.load_store_increments_i = !enabled,
+        "memoryIncrementByX" => {
+            quirks.load_store_increments_i_by_x = enabled;
+            quirks.load_store_increments_i |= enabled;
+        }
+        "wrap" => quirks.clip_sprites = !enabled,
+        "jump" => quirks.jump_uses_vx = enabled,
+        "vblank" => quirks.display_wait = enabled,
+        "logic" => quirks.logic_resets_vf = enabled,
+        _ => log::debug!("Ignoring database quirk `{name}`"),
+    }
+}
+
+#[derive(Deserialize)]
+struct Program {
+    title: String,
+    #[serde(default)]
+    roms: HashMap<String, Rom>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Rom {
+    #[serde(default)]
+    platforms: Vec<String>,
+    #[serde(default)]
+    quirky_platforms: HashMap<String, HashMap<String, bool>>,
+    tickrate: Option<usize>,
+    colors: Option<Colors>,
+    #[serde(default)]
+    keys: BTreeMap<String, u8>,
+}
+
+#[derive(Deserialize)]
+struct Colors {
+    #[serde(default)]
+    pixels: Vec<String>,
+}
+
+impl Rom {
+    fn info(self, title: &str) -> RomInfo {
+        let known = self
+            .platforms
+            .iter()
+            .find_map(|id| platform_from_id(id).map(|platform| (id, platform)));
+        let platform = known.map(|(_, platform)| platform);
+        let quirks = known.map(|(id, platform)| {
+            let mut quirks = platform.quirks();
+            for (name, &enabled) in self.quirky_platforms.get(id).into_iter().flatten() {
+                apply_quirk(&mut quirks, name, enabled);
+            }
+            quirks
+        });
+
+        let palette = self.colors.and_then(|colors| {
+            let pixels: Option<Vec<_>> = colors.pixels.iter().map(|c| parse_color(c)).collect();
+            let mut palette = Palette::default();
+            for (slot, color) in palette.colors.iter_mut().zip(pixels?) {
+                *slot = color;
+            }
+            Some(palette)
+        });
+
+        RomInfo {
+            title: title.to_string(),
+            platform,
+            quirks,
+            instructions_per_frame: self.tickrate,
+            palette,
+            keys: self.keys,
+        }
+    }
+}
+
+impl fmt::Display for DatabaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "Failed to read ROM database: {err}"),
+            Self::Json(err) => write!(f, "Invalid ROM database: {err}"),
+        }
+    }
+}
+
+impl Error for DatabaseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Json(err) => Some(err),
+        }
+    }
+}
+
+impl From<io::Error> for DatabaseError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for DatabaseError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Json(err)
+    }
+}
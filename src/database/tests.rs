@@ -0,0 +1,113 @@
+#[cfg(test)]
+mod database {
+    use crate::{
+        chip8::Platform,
+        database::{
+            sha1_hex,
+            RomDatabase,
+        },
+    };
+
+    const PROGRAMS: &str = r##"[
+        {
+            "title": "Test Game",
+            "roms": {
+                "A9993E364706816ABA3E25717850C26C9CD0D89D": {
+                    "file": "abc.ch8",
+                    "platforms": ["chip8x", "superchip"],
+                    "quirkyPlatforms": {
+                        "superchip": { "wrap": true, "vblank": true, "memoryIncrementByX": true }
+                    },
+                    "tickrate": 50,
+                    "colors": { "pixels": ["#101010", "#FFAA00"] },
+                    "keys": { "up": 5, "down": 8 }
+                }
+            }
+        },
+        {
+            "title": "Modern",
+            "roms": {
+                "da39a3ee5e6b4b0d3255bfef95601890afd80709": {
+                    "platforms": ["modernChip8"]
+                }
+            }
+        },
+        {
+            "title": "Unknown",
+            "roms": {
+                "11f6ad8ec52a2984abaafd7c3b516503785c2072": {
+                    "platforms": ["megachip8"]
+                }
+            }
+        }
+    ]"##;
+
+    #[test]
+    fn sha1() {
+        assert_eq!(sha1_hex(b"abc"), "a9993e364706816aba3e25717850c26c9cd0d89d");
+    }
+
+    #[test]
+    fn identify() {
+        let database = RomDatabase::from_json(PROGRAMS).unwrap();
+        let info = database.identify(b"abc").unwrap();
+
+        assert_eq!(info.title, "Test Game");
+        assert_eq!(info.platform, Some(Platform::SuperChip11));
+        assert_eq!(info.instructions_per_frame, Some(50));
+        assert_eq!(info.keys.get("up"), Some(&5));
+
+        let quirks = info.quirks.unwrap();
+        assert!(quirks.jump_uses_vx);
+        assert!(!quirks.clip_sprites);
+        assert!(quirks.display_wait);
+        assert!(quirks.load_store_increments_i);
+        assert!(quirks.load_store_increments_i_by_x);
+
+        let palette = info.palette.unwrap();
+        assert_eq!(palette.color(0), [0x10, 0x10, 0x10]);
+        assert_eq!(palette.color(1), [0xFF, 0xAA, 0x00]);
+    }
+
+    #[test]
+    fn modern_chip8() {
+        let database = RomDatabase::from_json(PROGRAMS).unwrap();
+        let info = database.identify(b"").unwrap();
+
+        assert_eq!(info.platform, Some(Platform::ModernSuperChip));
+        assert_eq!(info.quirks, Some(Platform::ModernSuperChip.quirks()));
+    }
+
+    #[test]
+    fn unknown_platform() {
+        let database = RomDatabase::from_json(PROGRAMS).unwrap();
+        let info = database.identify(b"x").unwrap();
+
+        assert_eq!(info.platform, None);
+        assert_eq!(info.quirks, None);
+        assert_eq!(database.identify(b"unknown"), None);
+    }
+
+    #[test]
+    fn override_entries() {
+        let mut database = RomDatabase::from_json(PROGRAMS).unwrap();
+        let local = RomDatabase::from_json(
+            r#"[{ "title": "Mine", "roms": {
+                "a9993e364706816aba3e25717850c26c9cd0d89d": { "platforms": ["xochip"] }
+            } }]"#,
+        )
+        .unwrap();
+        database.extend(local);
+
+        assert_eq!(database.len(), 3);
+        let info = database.identify(b"abc").unwrap();
+        assert_eq!(info.title, "Mine");
+        assert_eq!(info.platform, Some(Platform::XoChip));
+    }
+
+    #[test]
+    fn bundled() {
+        RomDatabase::bundled();
+        assert!(RomDatabase::from_json("{}").is_err());
+    }
+}
@@ -0,0 +1,569 @@
+use std::{
+    collections::{
+        BTreeSet,
+        VecDeque,
+    },
+    fs,
+    io::{
+        self,
+        BufRead,
+        Write,
+    },
+    path::Path,
+    sync::mpsc::{
+        self,
+        Receiver,
+        TryRecvError,
+    },
+    thread,
+};
+
+use serde_json::{
+    json,
+    Value,
+};
+
+use {
+    super::{
+        Debugger,
+        Stop,
+    },
+    crate::chip8::{
+        Chip8,
+        Instruction,
+        Platform,
+        PROGRAM_START,
+    },
+};
+
+/// Instructions run between checks for `pause` while the program runs
+const BATCH: usize = 1000;
+
+const THREAD_ID: u64 = 1;
+const REGISTERS_REFERENCE: u64 = 1;
+const TIMERS_REFERENCE: u64 = 2;
+const SOURCE_REFERENCE: u64 = 1;
+
+/// Debug Adapter Protocol server, the `chip-8 dap` mode.
+///
+/// Editors talk to it over stdio with `Content-Length` framed JSON
+/// messages. There is one thread and no source code: the program is shown
+/// as a disassembly source where line 1 is the instruction at 0x200 and
+/// every following line the next two bytes, so line breakpoints map to
+/// addresses. Instruction breakpoints work on addresses directly.
+///
+/// Launch arguments are `program`, the ROM path, and optionally `platform`,
+/// `seed` and `stopOnEntry`.
+pub struct DapServer<W: Write> {
+    output: W,
+    seq: u64,
+    debugger: Option<Debugger>,
+    program: String,
+    stop_on_entry: bool,
+    line_breakpoints: BTreeSet<usize>,
+    instruction_breakpoints: BTreeSet<usize>,
+    /// Requests that came in while the program was running
+    pending: VecDeque<Value>,
+}
+
+impl<W: Write> DapServer<W> {
+    pub fn new(output: W) -> Self {
+        let seq = 0;
+        let debugger = None;
+        let program = String::new();
+        let stop_on_entry = false;
+        let line_breakpoints = BTreeSet::new();
+        let instruction_breakpoints = BTreeSet::new();
+        let pending = VecDeque::new();
+
+        Self {
+            output,
+            seq,
+            debugger,
+            program,
+            stop_on_entry,
+            line_breakpoints,
+            instruction_breakpoints,
+            pending,
+        }
+    }
+
+    /// Serve requests read from `input` until `disconnect` or the end of
+    /// the input
+    pub fn run(&mut self, input: impl BufRead + Send + 'static) -> io::Result<()> {
+        // Reading happens on its own thread so a running program can be
+        // paused
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let mut input = input;
+            while let Ok(Some(message)) = read_message(&mut input) {
+                if sender.send(message).is_err() {
+                    break;
+                }
+            }
+        });
+
+        loop {
+            let request = match self.pending.pop_front() {
+                Some(request) => request,
+                None => match receiver.recv() {
+                    Ok(request) => request,
+                    Err(_) => return Ok(()),
+                },
+            };
+
+            let run = match self.handle(&request) {
+                Ok((body, run)) => {
+                    self.respond(&request, body)?;
+                    run
+                }
+                Err(message) => {
+                    self.respond_error(&request, &message)?;
+                    None
+                }
+            };
+
+            match request["command"].as_str() {
+                Some("initialize") => self.event("initialized", json!({}))?,
+                Some("disconnect") => return Ok(()),
+                _ => {}
+            }
+            if let Some(run) = run {
+                if !self.execute(run, &receiver)? {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Response body of a request and whether the program has to run
+    /// afterwards
+    fn handle(&mut self, request: &Value) -> Result<(Value, Option<Run>), String> {
+        let arguments = &request["arguments"];
+
+        let body = match request["command"].as_str().unwrap_or_default() {
+            "initialize" => json!({
+                "supportsConfigurationDoneRequest": true,
+                "supportsReadMemoryRequest": true,
+                "supportsInstructionBreakpoints": true,
+            }),
+            "launch" => {
+                self.launch(arguments)?;
+                Value::Null
+            }
+            "setBreakpoints" => {
+                let lines = arguments["breakpoints"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|breakpoint| breakpoint["line"].as_u64());
+                self.line_breakpoints = lines.map(|line| line_address(line as usize)).collect();
+                self.update_breakpoints();
+
+                let breakpoints: Vec<Value> = self
+                    .line_breakpoints
+                    .iter()
+                    .map(|&address| json!({ "verified": true, "line": address_line(address) }))
+                    .collect();
+                json!({ "breakpoints": breakpoints })
+            }
+            "setInstructionBreakpoints" => {
+                let addresses: Vec<Option<usize>> = arguments["breakpoints"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(|breakpoint| {
+                        let offset = breakpoint["offset"].as_i64().unwrap_or(0);
+                        breakpoint["instructionReference"]
+                            .as_str()
+                            .and_then(parse_reference)
+                            .map(|address| address.wrapping_add_signed(offset as isize))
+                    })
+                    .collect();
+                self.instruction_breakpoints = addresses.iter().flatten().copied().collect();
+                self.update_breakpoints();
+
+                let breakpoints: Vec<Value> = addresses
+                    .iter()
+                    .map(|address| json!({ "verified": address.is_some() }))
+                    .collect();
+                json!({ "breakpoints": breakpoints })
+            }
+            "configurationDone" => {
+                let run = if self.stop_on_entry {
+                    Run::Entry
+                } else {
+                    Run::Continue
+                };
+                return Ok((Value::Null, Some(run)));
+            }
+            "threads" => json!({ "threads": [{ "id": THREAD_ID, "name": "CHIP-8" }] }),
+            "stackTrace" => {
+                let frames = self.stack_frames()?;
+                json!({ "totalFrames": frames.len(), "stackFrames": frames })
+            }
+            "scopes" => json!({ "scopes": [
+                { "name": "Registers", "variablesReference": REGISTERS_REFERENCE, "expensive": false },
+                { "name": "Timers", "variablesReference": TIMERS_REFERENCE, "expensive": false },
+            ] }),
+            "variables" => {
+                let variables = self.variables(arguments["variablesReference"].as_u64())?;
+                json!({ "variables": variables })
+            }
+            "source" => json!({ "content": self.source()? }),
+            "readMemory" => self.read_memory(arguments)?,
+            command @ ("continue" | "next" | "stepIn" | "stepOut") => {
+                self.debugger()?;
+                let (body, run) = match command {
+                    "continue" => (json!({ "allThreadsContinued": true }), Run::Continue),
+                    "next" => (Value::Null, Run::StepOver),
+                    "stepIn" => (Value::Null, Run::Step),
+                    _ => (Value::Null, Run::StepOut),
+                };
+                return Ok((body, Some(run)));
+            }
+            // Only a running program can be paused, see `execute`
+            "pause" | "disconnect" | "terminate" => Value::Null,
+            command => return Err(format!("Unsupported request `{command}`")),
+        };
+
+        Ok((body, None))
+    }
+
+    fn launch(&mut self, arguments: &Value) -> Result<(), String> {
+        let program = arguments["program"]
+            .as_str()
+            .ok_or("Missing `program` in the launch arguments")?;
+        let rom = fs::read(program).map_err(|err| format!("{program}: {err}"))?;
+
+        let mut chip8 = match arguments["platform"].as_str() {
+            Some(platform) => Chip8::with_platform(platform.parse::<Platform>()?),
+            None => Chip8::new(),
+        };
+        if let Some(seed) = arguments["seed"].as_u64() {
+            chip8.set_seed(seed);
+        }
+        chip8.load_rom(&rom).map_err(|err| err.to_string())?;
+
+        self.debugger = Some(Debugger::new(chip8));
+        self.program = program.to_string();
+        self.stop_on_entry = arguments["stopOnEntry"].as_bool().unwrap_or(false);
+        self.update_breakpoints();
+
+        Ok(())
+    }
+
+    fn debugger(&self) -> Result<&Debugger, String> {
+        self.debugger
+            .as_ref()
+            .ok_or_else(|| "No program launched".to_string())
+    }
+
+    fn update_breakpoints(&mut self) {
+        let Some(debugger) = &mut self.debugger else {
+            return;
+        };
+
+        debugger.clear_breakpoints();
+        for &address in self.line_breakpoints.union(&self.instruction_breakpoints) {
+            debugger.add_breakpoint(address);
+        }
+    }
+
+    /// Run the program, returns `false` if the client went away meanwhile
+    fn execute(&mut self, run: Run, receiver: &Receiver<Value>) -> io::Result<bool> {
+        let Some(debugger) = &mut self.debugger else {
+            return Ok(true);
+        };
+
+        let stop = match run {
+            Run::Entry => {
+                return self
+                    .event("stopped", stopped_body("entry", None))
+                    .map(|_| true)
+            }
+            Run::Step => debugger.step(1),
+            Run::StepOver => debugger.step_over(),
+            Run::StepOut => debugger.step_out(),
+            Run::Continue => loop {
+                match debugger.step(BATCH) {
+                    Stop::Step => {}
+                    stop => break stop,
+                }
+
+                // Other requests wait until the program stops
+                match receiver.try_recv() {
+                    Ok(request) if request["command"] == "pause" => {
+                        self.respond(&request, Value::Null)?;
+                        return self
+                            .event("stopped", stopped_body("pause", None))
+                            .map(|_| true);
+                    }
+                    Ok(request) if request["command"] == "disconnect" => {
+                        self.respond(&request, Value::Null)?;
+                        return Ok(false);
+                    }
+                    Ok(request) => self.pending.push_back(request),
+                    Err(TryRecvError::Empty) => {}
+                    Err(TryRecvError::Disconnected) => return Ok(false),
+                }
+            },
+        };
+
+        match stop {
+            Stop::Step => self.event("stopped", stopped_body("step", None))?,
+            Stop::Breakpoint(_) => self.event("stopped", stopped_body("breakpoint", None))?,
+            Stop::Halted => {
+                self.event("exited", json!({ "exitCode": 0 }))?;
+                self.event("terminated", json!({}))?;
+            }
+            Stop::Error(ref err) => {
+                let text = err.to_string();
+                self.event("stopped", stopped_body("exception", Some(&text)))?;
+            }
+            ref stop => {
+                let text = stop.to_string();
+                self.event("stopped", stopped_body("pause", Some(&text)))?;
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Current instruction, then one frame per return address on the stack
+    fn stack_frames(&self) -> Result<Vec<Value>, String> {
+        let debugger = self.debugger()?;
+        let chip8 = debugger.chip8();
+        let name = Path::new(&self.program)
+            .file_name()
+            .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+        let source = json!({ "name": name, "sourceReference": SOURCE_REFERENCE });
+
+        // A frame's function is the target of the call below it
+        let returns = chip8.stack().stack();
+        let functions = returns
+            .iter()
+            .rev()
+            .map(|&address| match debugger.instruction_at(address - 2) {
+                Some(Instruction::Call(nnn)) => format!("sub_{nnn:03X}"),
+                _ => format!("{:#05X}", address - 2),
+            })
+            .chain(["main".to_string()]);
+        let addresses = [chip8.pc()]
+            .into_iter()
+            .chain(returns.iter().rev().map(|address| address - 2));
+
+        let frames = addresses
+            .zip(functions)
+            .enumerate()
+            .map(|(id, (address, name))| {
+                json!({
+                    "id": id,
+                    "name": name,
+                    "source": source,
+                    "line": address_line(address),
+                    "column": 1,
+                    "instructionPointerReference": format!("{address:#05X}"),
+                })
+            })
+            .collect();
+
+        Ok(frames)
+    }
+
+    fn variables(&self, reference: Option<u64>) -> Result<Vec<Value>, String> {
+        let chip8 = self.debugger()?.chip8();
+        let variable = |name: String, value: String| json!({ "name": name, "value": value, "variablesReference": 0 });
+
+        let variables = match reference {
+            Some(REGISTERS_REFERENCE) => {
+                let mut variables: Vec<Value> = chip8
+                    .v()
+                    .iter()
+                    .enumerate()
+                    .map(|(x, vx)| variable(format!("V{x:X}"), format!("{vx:#04X}")))
+                    .collect();
+                let mut i = variable("I".to_string(), format!("{:#05X}", chip8.i()));
+                i["memoryReference"] = json!(format!("{:#05X}", chip8.i()));
+                variables.push(i);
+                variables.push(variable("PC".to_string(), format!("{:#05X}", chip8.pc())));
+                variables
+            }
+            Some(TIMERS_REFERENCE) => vec![
+                variable("DT".to_string(), chip8.timers().delay().to_string()),
+                variable("ST".to_string(), chip8.timers().sound().to_string()),
+            ],
+            _ => return Err("Unknown variables reference".to_string()),
+        };
+
+        Ok(variables)
+    }
+
+    /// Disassembly of the program, one line per two bytes from 0x200
+    fn source(&self) -> Result<String, String> {
+        let debugger = self.debugger()?;
+        let end = debugger.chip8().memory().len();
+
+        let lines: Vec<String> = (PROGRAM_START..end - 1)
+            .step_by(2)
+            .filter_map(|address| {
+                let instruction = debugger.instruction_at(address)?;
+                Some(format!(
+                    "{address:#05X}: {:04X}  {instruction}",
+                    instruction.encode().code()
+                ))
+            })
+            .collect();
+
+        Ok(lines.join("\n"))
+    }
+
+    fn read_memory(&self, arguments: &Value) -> Result<Value, String> {
+        let memory = self.debugger()?.chip8().memory();
+        let address = arguments["memoryReference"]
+            .as_str()
+            .and_then(parse_reference)
+            .ok_or("Invalid memory reference")?
+            .wrapping_add_signed(arguments["offset"].as_i64().unwrap_or(0) as isize);
+        let count = arguments["count"].as_u64().unwrap_or(0) as usize;
+
+        let start = address.min(memory.len());
+        let end = address.saturating_add(count).min(memory.len());
+        let data = &memory[start..end];
+
+        Ok(json!({
+            "address": format!("{address:#05X}"),
+            "data": base64(data),
+            "unreadableBytes": count - data.len(),
+        }))
+    }
+
+    fn respond(&mut self, request: &Value, body: Value) -> io::Result<()> {
+        let mut response = json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "success": true,
+            "command": request["command"],
+        });
+        if !body.is_null() {
+            response["body"] = body;
+        }
+        self.send(response)
+    }
+
+    fn respond_error(&mut self, request: &Value, message: &str) -> io::Result<()> {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request["seq"],
+            "success": false,
+            "command": request["command"],
+            "message": message,
+        }))
+    }
+
+    fn event(&mut self, event: &str, body: Value) -> io::Result<()> {
+        self.send(json!({ "type": "event", "event": event, "body": body }))
+    }
+
+    fn send(&mut self, mut message: Value) -> io::Result<()> {
+        self.seq += 1;
+        message["seq"] = json!(self.seq);
+        write_message(&mut self.output, &message)
+    }
+}
+
+/// What the program does after a request
+#[derive(Clone, Copy, Debug)]
+enum Run {
+    /// Nothing, only report the stop at the entry point
+    Entry,
+    Step,
+    StepOver,
+    StepOut,
+    Continue,
+}
+
+fn stopped_body(reason: &str, description: Option<&str>) -> Value {
+    let mut body = json!({
+        "reason": reason,
+        "threadId": THREAD_ID,
+        "allThreadsStopped": true,
+    });
+    if let Some(description) = description {
+        body["description"] = json!(description);
+    }
+    body
+}
+
+/// Address of a line of the disassembly source
+fn line_address(line: usize) -> usize {
+    PROGRAM_START + line.saturating_sub(1) * 2
+}
+
+fn address_line(address: usize) -> usize {
+    address.saturating_sub(PROGRAM_START) / 2 + 1
+}
+
+/// `0x` prefixed hex address
+fn parse_reference(reference: &str) -> Option<usize> {
+    let digits = reference
+        .strip_prefix("0x")
+        .or_else(|| reference.strip_prefix("0X"))?;
+    usize::from_str_radix(digits, 16).ok()
+}
+
+/// Read one `Content-Length` framed message, `None` at the end of the input
+pub fn read_message(input: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some(value) = line.strip_prefix("Content-Length:") {
+            length = value.trim().parse().ok();
+        }
+    }
+
+    let length = length
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length"))?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(io::Error::from)
+}
+
+pub fn write_message(output: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{body}", body.len())?;
+    output.flush()
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let bits = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(bits >> (18 - i * 6)) as usize & 0x3F] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
@@ -0,0 +1,308 @@
+use std::{
+    collections::BTreeSet,
+    fmt,
+};
+
+use crate::chip8::{
+    Chip8,
+    Chip8Error,
+    Instruction,
+    Opcode,
+};
+
+pub use self::{
+    dap::DapServer,
+    repl::Repl,
+};
+
+mod dap;
+mod repl;
+mod tests;
+
+/// Execution control over a [`Chip8`]: breakpoints, watchpoints and
+/// stepping.
+///
+/// Instructions run through [`Chip8::step_in_frame`], so the timers tick at
+/// the same points as when the program runs normally.
+pub struct Debugger {
+    chip8: Chip8,
+    breakpoints: BTreeSet<usize>,
+    watchpoints: Vec<Watchpoint>,
+}
+
+/// State a watchpoint stops on when it changes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Watchpoint {
+    /// Memory from `start` up to, not including, `end`
+    Memory {
+        start: usize,
+        end: usize,
+    },
+    /// Register V0 to VF
+    Register(usize),
+    I,
+}
+
+/// Why execution stopped
+#[derive(Debug)]
+pub enum Stop {
+    /// Requested number of instructions ran
+    Step,
+    Breakpoint(usize),
+    /// Watched state changed from `old` to `new`, memory shows the first
+    /// changed address
+    Watchpoint {
+        watchpoint: Watchpoint,
+        address: usize,
+        old: usize,
+        new: usize,
+    },
+    /// `Fx0A` waits for a key press
+    WaitingKey,
+    /// Program jumps to itself, it can't get anywhere anymore
+    Loop,
+    /// Program exited with `00FD`
+    Halted,
+    Error(Chip8Error),
+}
+
+impl Debugger {
+    pub fn new(chip8: Chip8) -> Self {
+        let breakpoints = BTreeSet::new();
+        let watchpoints = vec![];
+
+        Self {
+            chip8,
+            breakpoints,
+            watchpoints,
+        }
+    }
+
+    pub fn chip8(&self) -> &Chip8 {
+        &self.chip8
+    }
+
+    pub fn chip8_mut(&mut self) -> &mut Chip8 {
+        &mut self.chip8
+    }
+
+    pub fn breakpoints(&self) -> &BTreeSet<usize> {
+        &self.breakpoints
+    }
+
+    /// Returns `false` if there already was a breakpoint at `address`
+    pub fn add_breakpoint(&mut self, address: usize) -> bool {
+        self.breakpoints.insert(address)
+    }
+
+    pub fn remove_breakpoint(&mut self, address: usize) -> bool {
+        self.breakpoints.remove(&address)
+    }
+
+    pub fn clear_breakpoints(&mut self) {
+        self.breakpoints.clear();
+    }
+
+    pub fn watchpoints(&self) -> &[Watchpoint] {
+        &self.watchpoints
+    }
+
+    pub fn add_watchpoint(&mut self, watchpoint: Watchpoint) -> bool {
+        if self.watchpoints.contains(&watchpoint) {
+            return false;
+        }
+        self.watchpoints.push(watchpoint);
+        true
+    }
+
+    pub fn remove_watchpoint(&mut self, watchpoint: Watchpoint) -> bool {
+        let len = self.watchpoints.len();
+        self.watchpoints.retain(|&w| w != watchpoint);
+        self.watchpoints.len() != len
+    }
+
+    /// Instruction at `address`
+    pub fn instruction_at(&self, address: usize) -> Option<Instruction> {
+        let memory = self.chip8.memory();
+        let bytes = memory.get(address..address.checked_add(2)?)?;
+        Some(Opcode::from(u16::from_be_bytes([bytes[0], bytes[1]])).instruction())
+    }
+
+    /// Execute up to `n` instructions, stopping early on breakpoints and
+    /// watchpoints
+    pub fn step(&mut self, n: usize) -> Stop {
+        for _ in 0..n {
+            if let Some(stop) = self.step_one() {
+                return stop;
+            }
+        }
+        Stop::Step
+    }
+
+    /// Step, running a `2NNN` subroutine call to its return
+    pub fn step_over(&mut self) -> Stop {
+        match self.instruction_at(self.chip8.pc()) {
+            Some(Instruction::Call(_)) => {
+                let depth = self.chip8.stack().stack().len();
+                self.run_until(|chip8| chip8.stack().stack().len() <= depth)
+            }
+            _ => self.step(1),
+        }
+    }
+
+    /// Run until the current subroutine returns to its caller
+    pub fn step_out(&mut self) -> Stop {
+        let depth = self.chip8.stack().stack().len();
+        if depth == 0 {
+            return self.cont();
+        }
+        self.run_until(|chip8| chip8.stack().stack().len() < depth)
+    }
+
+    /// Run until something stops the program
+    pub fn cont(&mut self) -> Stop {
+        self.run_until(|_| false)
+    }
+
+    fn run_until(&mut self, done: impl Fn(&Chip8) -> bool) -> Stop {
+        loop {
+            if let Some(stop) = self.step_one() {
+                return stop;
+            }
+            if done(&self.chip8) {
+                return Stop::Step;
+            }
+        }
+    }
+
+    /// Execute one instruction, `Some` if execution has to stop after it
+    fn step_one(&mut self) -> Option<Stop> {
+        if self.chip8.is_halted() {
+            return Some(Stop::Halted);
+        }
+
+        let before: Vec<Vec<u8>> = self
+            .watchpoints
+            .iter()
+            .map(|watchpoint| self.watched(watchpoint))
+            .collect();
+        let address = self.chip8.pc();
+
+        if let Err(err) = self.chip8.step_in_frame() {
+            return Some(Stop::Error(err));
+        }
+
+        for (watchpoint, old) in self.watchpoints.iter().zip(before) {
+            let new = self.watched(watchpoint);
+            if let Some(offset) = old.iter().zip(&new).position(|(old, new)| old != new) {
+                let (address, old, new) = match *watchpoint {
+                    Watchpoint::Memory { start, .. } => {
+                        (start + offset, old[offset] as usize, new[offset] as usize)
+                    }
+                    Watchpoint::I => (0, decode_be(&old), decode_be(&new)),
+                    Watchpoint::Register(x) => (x, old[0] as usize, new[0] as usize),
+                };
+                return Some(Stop::Watchpoint {
+                    watchpoint: *watchpoint,
+                    address,
+                    old,
+                    new,
+                });
+            }
+        }
+
+        if self.chip8.is_halted() {
+            return Some(Stop::Halted);
+        }
+        if self.chip8.is_waiting_key() && self.chip8.keypad().first_pressed().is_none() {
+            return Some(Stop::WaitingKey);
+        }
+        if self.chip8.pc() == address {
+            return Some(Stop::Loop);
+        }
+        if self.breakpoints.contains(&self.chip8.pc()) {
+            return Some(Stop::Breakpoint(self.chip8.pc()));
+        }
+
+        None
+    }
+
+    /// Current bytes of a watched location
+    fn watched(&self, watchpoint: &Watchpoint) -> Vec<u8> {
+        match *watchpoint {
+            Watchpoint::Memory { start, end } => {
+                let memory = self.chip8.memory();
+                memory[start.min(memory.len())..end.min(memory.len())].to_vec()
+            }
+            Watchpoint::Register(x) => vec![self.chip8.v()[x]],
+            Watchpoint::I => (self.chip8.i() as u32).to_be_bytes().to_vec(),
+        }
+    }
+
+    /// Disassembly of `before` instructions before `address` up to `after`
+    /// instructions after it, as address, opcode and instruction
+    pub fn disassemble(
+        &self,
+        address: usize,
+        before: usize,
+        after: usize,
+    ) -> Vec<(usize, u16, Instruction)> {
+        let start = address.saturating_sub(before.saturating_mul(2));
+        let end = address
+            .saturating_add(after.saturating_mul(2))
+            .min(self.chip8.memory().len());
+        (start..=end)
+            .step_by(2)
+            .filter_map(|address| {
+                let instruction = self.instruction_at(address)?;
+                Some((address, instruction.encode().code(), instruction))
+            })
+            .collect()
+    }
+}
+
+fn decode_be(bytes: &[u8]) -> usize {
+    bytes.iter().fold(0, |value, &b| (value << 8) | b as usize)
+}
+
+impl fmt::Display for Watchpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Memory { start, end } if end - start == 1 => write!(f, "[{start:#05X}]"),
+            Self::Memory { start, end } => write!(f, "[{start:#05X}..{end:#05X}]"),
+            Self::Register(x) => write!(f, "V{x:X}"),
+            Self::I => write!(f, "I"),
+        }
+    }
+}
+
+impl fmt::Display for Stop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Step => write!(f, "Stepped"),
+            Self::Breakpoint(address) => write!(f, "Breakpoint at {address:#05X}"),
+            Self::Watchpoint {
+                watchpoint: Watchpoint::Memory { .. },
+                address,
+                old,
+                new,
+            } => write!(
+                f,
+                "Watchpoint: memory at {address:#05X} changed from {old:#04X} to {new:#04X}"
+            ),
+            Self::Watchpoint {
+                watchpoint,
+                old,
+                new,
+                ..
+            } => write!(
+                f,
+                "Watchpoint: {watchpoint} changed from {old:#X} to {new:#X}"
+            ),
+            Self::WaitingKey => write!(f, "Waiting for a key press"),
+            Self::Loop => write!(f, "Program jumps to itself"),
+            Self::Halted => write!(f, "Program exited"),
+            Self::Error(err) => write!(f, "{err}"),
+        }
+    }
+}
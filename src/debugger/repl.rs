@@ -0,0 +1,275 @@
+use std::io::{
+    self,
+    BufRead,
+    Write,
+};
+
+use super::{
+    Debugger,
+    Stop,
+    Watchpoint,
+};
+
+const HELP: &str = "\
+break ADDR (b)         set a breakpoint
+delete [ADDR] (d)      remove a breakpoint, all without ADDR
+watch TARGET (w)       stop when TARGET changes: ADDR, ADDR..END, V0-VF or I
+unwatch TARGET         remove a watchpoint
+info (i)               list breakpoints and watchpoints
+step [N] (s)           execute N instructions, 1 by default
+next (n)               step over 2NNN subroutine calls
+finish (o)             run until the current subroutine returns
+continue (c)           run until a breakpoint, watchpoint or exit
+regs (r)               show registers
+stack                  show the call stack
+timers (t)             show the delay and sound timers
+list [ADDR] (l)        disassemble around PC or ADDR
+x ADDR [LEN]           dump LEN bytes of memory, 16 by default
+press KEY              hold key 0-F down
+release KEY            let key 0-F go
+quit (q)               leave the debugger
+Numbers are hex, an empty line repeats the last command.";
+
+/// Line based command interface to a [`Debugger`], the `chip-8 debug` mode
+pub struct Repl {
+    debugger: Debugger,
+    last_command: String,
+}
+
+impl Repl {
+    pub fn new(debugger: Debugger) -> Self {
+        let last_command = String::new();
+
+        Self {
+            debugger,
+            last_command,
+        }
+    }
+
+    pub fn debugger(&self) -> &Debugger {
+        &self.debugger
+    }
+
+    /// Read commands until `quit` or the end of the input
+    pub fn run(&mut self, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+        self.list(&mut output, self.debugger.chip8().pc())?;
+        write!(output, "(chip-8) ")?;
+        output.flush()?;
+
+        for line in input.lines() {
+            if !self.execute(&line?, &mut output)? {
+                break;
+            }
+            write!(output, "(chip-8) ")?;
+            output.flush()?;
+        }
+        writeln!(output)?;
+
+        Ok(())
+    }
+
+    /// Execute one command line, returns `false` on `quit`
+    pub fn execute(&mut self, line: &str, out: &mut impl Write) -> io::Result<bool> {
+        let line = match line.trim() {
+            "" => self.last_command.clone(),
+            line => line.to_string(),
+        };
+        self.last_command = line.clone();
+
+        let mut words = line.split_whitespace();
+        let Some(command) = words.next() else {
+            return Ok(true);
+        };
+        let args: Vec<&str> = words.collect();
+
+        if let Err(message) = self.command(command, &args, out)? {
+            writeln!(out, "{message}")?;
+        }
+
+        Ok(!matches!(command, "quit" | "q"))
+    }
+
+    /// Run a command, the inner `Err` is a usage error for the user
+    fn command(
+        &mut self,
+        command: &str,
+        args: &[&str],
+        out: &mut impl Write,
+    ) -> io::Result<Result<(), String>> {
+        let debugger = &mut self.debugger;
+        let pc = debugger.chip8().pc();
+
+        match (command, args) {
+            ("break" | "b", [address]) => {
+                let Ok(address) = parse_hex(address) else {
+                    return Ok(Err(format!("Invalid address `{address}`")));
+                };
+                if debugger.add_breakpoint(address) {
+                    writeln!(out, "Breakpoint at {address:#05X}")?;
+                }
+            }
+            ("delete" | "d", []) => debugger.clear_breakpoints(),
+            ("delete" | "d", [address]) => {
+                let removed = parse_hex(address).map(|address| debugger.remove_breakpoint(address));
+                if removed != Ok(true) {
+                    return Ok(Err(format!("No breakpoint at `{address}`")));
+                }
+            }
+            ("watch" | "w", [target]) => match parse_watchpoint(target) {
+                Ok(watchpoint) => {
+                    if debugger.add_watchpoint(watchpoint) {
+                        writeln!(out, "Watching {watchpoint}")?;
+                    }
+                }
+                Err(message) => return Ok(Err(message)),
+            },
+            ("unwatch", [target]) => {
+                let removed = parse_watchpoint(target)
+                    .map(|watchpoint| debugger.remove_watchpoint(watchpoint));
+                if removed != Ok(true) {
+                    return Ok(Err(format!("Not watching `{target}`")));
+                }
+            }
+            ("info" | "i", []) => {
+                for address in debugger.breakpoints() {
+                    writeln!(out, "Breakpoint at {address:#05X}")?;
+                }
+                for watchpoint in debugger.watchpoints() {
+                    writeln!(out, "Watching {watchpoint}")?;
+                }
+            }
+            ("step" | "s", []) => return self.stopped(debugger_step(1), out),
+            ("step" | "s", [n]) => {
+                let Ok(n) = parse_hex(n) else {
+                    return Ok(Err(format!("Invalid count `{n}`")));
+                };
+                return self.stopped(debugger_step(n), out);
+            }
+            ("next" | "n", []) => return self.stopped(Debugger::step_over, out),
+            ("finish" | "o", []) => return self.stopped(Debugger::step_out, out),
+            ("continue" | "c", []) => return self.stopped(Debugger::cont, out),
+            ("regs" | "r", []) => writeln!(out, "{}", debugger.chip8().registers())?,
+            ("stack", []) => {
+                let stack = debugger.chip8().stack().stack();
+                if stack.is_empty() {
+                    writeln!(out, "Stack is empty")?;
+                }
+                for (depth, address) in stack.iter().rev().enumerate() {
+                    writeln!(out, "#{depth} returns to {address:#05X}")?;
+                }
+            }
+            ("timers" | "t", []) => {
+                let timers = debugger.chip8().timers();
+                writeln!(out, "DT={:02X} ST={:02X}", timers.delay(), timers.sound())?;
+            }
+            ("list" | "l", []) => self.list(out, pc)?,
+            ("list" | "l", [address]) => match parse_hex(address) {
+                Ok(address) if address < debugger.chip8().memory().len() => {
+                    self.list(out, address)?
+                }
+                Ok(_) => return Ok(Err(format!("Address `{address}` is out of memory"))),
+                Err(_) => return Ok(Err(format!("Invalid address `{address}`"))),
+            },
+            ("x", [address, rest @ ..]) if rest.len() <= 1 => {
+                let (Ok(address), Ok(len)) = (
+                    parse_hex(address),
+                    rest.first().map_or(Ok(16), |len| parse_hex(len)),
+                ) else {
+                    return Ok(Err("Usage: x ADDR [LEN]".to_string()));
+                };
+                let memory = debugger.chip8().memory();
+                if address >= memory.len() {
+                    return Ok(Err(format!("Address {address:#05X} is out of memory")));
+                }
+                let end = address.saturating_add(len).min(memory.len());
+                for (row, bytes) in memory[address..end].chunks(16).enumerate() {
+                    write!(out, "{:#05X}:", address + row * 16)?;
+                    for byte in bytes {
+                        write!(out, " {byte:02X}")?;
+                    }
+                    writeln!(out)?;
+                }
+            }
+            ("press" | "release", [key]) => match u8::from_str_radix(key, 16) {
+                Ok(key) if key < 16 => debugger.chip8_mut().set_key(key, command == "press"),
+                _ => return Ok(Err(format!("Invalid key `{key}`, expected 0 to F"))),
+            },
+            ("help" | "h" | "?", []) => writeln!(out, "{HELP}")?,
+            ("quit" | "q", []) => {}
+            _ => return Ok(Err(format!("Unknown command `{}`, try help", command))),
+        }
+
+        Ok(Ok(()))
+    }
+
+    /// Run `action`, then report why it stopped and where
+    fn stopped(
+        &mut self,
+        action: impl FnOnce(&mut Debugger) -> Stop,
+        out: &mut impl Write,
+    ) -> io::Result<Result<(), String>> {
+        let stop = action(&mut self.debugger);
+        if !matches!(stop, Stop::Step) {
+            writeln!(out, "{stop}")?;
+        }
+        self.list(out, self.debugger.chip8().pc())?;
+
+        Ok(Ok(()))
+    }
+
+    /// Disassembly around `address`, the program counter is marked
+    fn list(&self, out: &mut impl Write, address: usize) -> io::Result<()> {
+        let pc = self.debugger.chip8().pc();
+        for (address, opcode, instruction) in self.debugger.disassemble(address, 3, 4) {
+            let marker = match (
+                address == pc,
+                self.debugger.breakpoints().contains(&address),
+            ) {
+                (true, _) => "=>",
+                (false, true) => " *",
+                (false, false) => "  ",
+            };
+            writeln!(out, "{marker} {address:#05X}: {opcode:04X}  {instruction}")?;
+        }
+
+        Ok(())
+    }
+}
+
+fn debugger_step(n: usize) -> impl FnOnce(&mut Debugger) -> Stop {
+    move |debugger| debugger.step(n)
+}
+
+fn parse_hex(value: &str) -> Result<usize, String> {
+    let digits = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value);
+    usize::from_str_radix(digits, 16).map_err(|_| format!("Invalid number `{value}`"))
+}
+
+/// `ADDR`, `ADDR..END`, `V0` to `VF` or `I`
+fn parse_watchpoint(target: &str) -> Result<Watchpoint, String> {
+    let lower = target.to_lowercase();
+    if lower == "i" {
+        return Ok(Watchpoint::I);
+    }
+    if let Some(register) = lower.strip_prefix('v').filter(|x| x.len() == 1) {
+        let x = usize::from_str_radix(register, 16)
+            .map_err(|_| format!("Invalid register `{target}`"))?;
+        return Ok(Watchpoint::Register(x));
+    }
+
+    let (start, end) = match lower.split_once("..") {
+        Some((start, end)) => (parse_hex(start)?, parse_hex(end)?),
+        None => {
+            let start = parse_hex(&lower)?;
+            (start, start.saturating_add(1))
+        }
+    };
+    if end <= start {
+        return Err(format!("Empty memory range `{target}`"));
+    }
+
+    Ok(Watchpoint::Memory { start, end })
+}
@@ -0,0 +1,392 @@
+#[cfg(test)]
+mod debugger {
+    use crate::{
+        chip8::Chip8,
+        debugger::{
+            Debugger,
+            Stop,
+            Watchpoint,
+        },
+    };
+
+    /// 0x200: call 0x208, 0x202: ld v1, 0x02, 0x204: jp 0x204,
+    /// 0x208: ld v0, 0x01, 0x20A: ld [i], v0 with i = 0x300, 0x20C: ret
+    fn debugger() -> Debugger {
+        let mut chip8 = Chip8::new();
+        chip8
+            .load_rom(&[
+                0x22, 0x08, 0x61, 0x02, 0x12, 0x04, 0x00, 0x00, 0x60, 0x01, 0xA3, 0x00, 0xF0, 0x55,
+                0x00, 0xEE,
+            ])
+            .unwrap();
+        Debugger::new(chip8)
+    }
+
+    #[test]
+    fn step() {
+        let mut debugger = debugger();
+
+        assert!(matches!(debugger.step(2), Stop::Step));
+        assert_eq!(debugger.chip8().pc(), 0x20A);
+        assert_eq!(debugger.chip8().v()[0], 1);
+    }
+
+    #[test]
+    fn step_over_call() {
+        let mut debugger = debugger();
+
+        assert!(matches!(debugger.step_over(), Stop::Step));
+        assert_eq!(debugger.chip8().pc(), 0x202);
+        assert_eq!(debugger.chip8().memory()[0x300], 1);
+    }
+
+    #[test]
+    fn step_out() {
+        let mut debugger = debugger();
+        debugger.step(1);
+
+        assert!(matches!(debugger.step_out(), Stop::Step));
+        assert_eq!(debugger.chip8().pc(), 0x202);
+        assert!(debugger.chip8().stack().stack().is_empty());
+    }
+
+    #[test]
+    fn breakpoint() {
+        let mut debugger = debugger();
+        debugger.add_breakpoint(0x20C);
+
+        assert!(matches!(debugger.cont(), Stop::Breakpoint(0x20C)));
+        // Continuing from a breakpoint doesn't stop on it again
+        assert!(matches!(debugger.cont(), Stop::Loop));
+        assert_eq!(debugger.chip8().pc(), 0x204);
+    }
+
+    #[test]
+    fn watch_memory() {
+        let mut debugger = debugger();
+        debugger.add_watchpoint(Watchpoint::Memory {
+            start: 0x2FF,
+            end: 0x302,
+        });
+
+        let stop = debugger.cont();
+
+        assert!(matches!(
+            stop,
+            Stop::Watchpoint {
+                address: 0x300,
+                old: 0,
+                new: 1,
+                ..
+            }
+        ));
+        assert_eq!(debugger.chip8().pc(), 0x20E);
+    }
+
+    #[test]
+    fn watch_register() {
+        let mut debugger = debugger();
+        debugger.add_watchpoint(Watchpoint::Register(1));
+        debugger.add_watchpoint(Watchpoint::I);
+
+        assert!(matches!(
+            debugger.cont(),
+            Stop::Watchpoint {
+                watchpoint: Watchpoint::I,
+                new: 0x300,
+                ..
+            }
+        ));
+        assert!(matches!(
+            debugger.cont(),
+            Stop::Watchpoint {
+                watchpoint: Watchpoint::Register(1),
+                old: 0,
+                new: 2,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn waiting_key() {
+        let mut chip8 = Chip8::new();
+        chip8.load_rom(&[0xF0, 0x0A, 0x12, 0x02]).unwrap();
+        let mut debugger = Debugger::new(chip8);
+
+        assert!(matches!(debugger.cont(), Stop::WaitingKey));
+
+        debugger.chip8_mut().set_key(5, true);
+        debugger.step(1);
+        debugger.chip8_mut().set_key(5, false);
+
+        assert!(matches!(debugger.cont(), Stop::Loop));
+        assert_eq!(debugger.chip8().v()[0], 5);
+    }
+}
+
+#[cfg(test)]
+mod repl {
+    use crate::{
+        chip8::Chip8,
+        debugger::{
+            Debugger,
+            Repl,
+        },
+    };
+
+    fn session(script: &str) -> (Repl, String) {
+        let mut chip8 = Chip8::new();
+        chip8
+            .load_rom(&[0x22, 0x06, 0x61, 0x02, 0x12, 0x04, 0x60, 0x01, 0x00, 0xEE])
+            .unwrap();
+        let mut repl = Repl::new(Debugger::new(chip8));
+        let mut output = vec![];
+        repl.run(script.as_bytes(), &mut output).unwrap();
+
+        (repl, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn scripted_session() {
+        let (repl, output) = session("b 206\nc\nstack\nregs\nfinish\nc\nq\nstep\n");
+
+        assert!(output.contains("Breakpoint at 0x206"));
+        assert!(output.contains("#0 returns to 0x202"));
+        assert!(output.contains("=> 0x206: 6001  "));
+        assert!(output.contains("Program jumps to itself"));
+        // Nothing runs after quit
+        assert_eq!(repl.debugger().chip8().pc(), 0x204);
+        assert_eq!(repl.debugger().chip8().v()[..2], [1, 2]);
+    }
+
+    #[test]
+    fn empty_line_repeats() {
+        let (repl, _) = session("s\n\n\n");
+
+        // Call, ld v0 and ret
+        assert_eq!(repl.debugger().chip8().pc(), 0x202);
+    }
+
+    #[test]
+    fn memory_and_watch() {
+        let (repl, output) = session("x 200 4\nwatch v0\nw 3..1\nc\ninfo\nunwatch v0\ni\n");
+
+        assert!(output.contains("0x200: 22 06 61 02\n"));
+        assert!(output.contains("Empty memory range `3..1`"));
+        assert!(output.contains("Watchpoint: V0 changed from 0x0 to 0x1"));
+        assert_eq!(output.matches("Watching V0").count(), 2);
+        assert_eq!(repl.debugger().watchpoints(), []);
+    }
+
+    #[test]
+    fn errors() {
+        let (_, output) = session("frobnicate\nb zz\npress 10\ndelete 300\n");
+
+        assert!(output.contains("Unknown command `frobnicate`, try help"));
+        assert!(output.contains("Invalid address `zz`"));
+        assert!(output.contains("Invalid key `10`, expected 0 to F"));
+        assert!(output.contains("No breakpoint at `300`"));
+    }
+
+    #[test]
+    fn huge_numbers() {
+        let (_, output) = session(
+            "list ffffffffffffffff\nx fff ffffffffffffffff\nx ffffffffffffffff\nw ffffffffffffffff\n",
+        );
+
+        assert!(output.contains("Address `ffffffffffffffff` is out of memory"));
+        assert!(output.contains("0xFFF: 00\n"));
+        assert!(output.contains("Address 0xFFFFFFFFFFFFFFFF is out of memory"));
+        assert!(output.contains("Empty memory range `ffffffffffffffff`"));
+    }
+}
+
+#[cfg(test)]
+mod dap {
+    use std::{
+        env,
+        fs,
+        io::Cursor,
+        process,
+    };
+
+    use serde_json::{
+        json,
+        Value,
+    };
+
+    use crate::debugger::{
+        dap::{
+            read_message,
+            write_message,
+        },
+        DapServer,
+    };
+
+    /// Run a scripted client session, `launch` gets `rom` as its program
+    fn session(name: &str, rom: &[u8], launch: Value, requests: &[(&str, Value)]) -> Vec<Value> {
+        let path = env::temp_dir().join(format!("chip-8-dap-{}-{name}.ch8", process::id()));
+        fs::write(&path, rom).unwrap();
+
+        let mut launch = launch;
+        launch["program"] = json!(path.to_str().unwrap());
+        let script = [
+            ("initialize", json!({ "adapterID": "chip-8" })),
+            ("launch", launch),
+        ];
+
+        let mut input = vec![];
+        for (seq, (command, arguments)) in script.iter().chain(requests).enumerate() {
+            let request = json!({
+                "seq": seq + 1,
+                "type": "request",
+                "command": command,
+                "arguments": arguments,
+            });
+            write_message(&mut input, &request).unwrap();
+        }
+
+        let mut output = vec![];
+        DapServer::new(&mut output).run(Cursor::new(input)).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let mut output = Cursor::new(output);
+        let mut messages = vec![];
+        while let Some(message) = read_message(&mut output).unwrap() {
+            messages.push(message);
+        }
+        messages
+    }
+
+    fn response<'a>(messages: &'a [Value], command: &str) -> &'a Value {
+        messages
+            .iter()
+            .find(|message| message["type"] == "response" && message["command"] == command)
+            .unwrap()
+    }
+
+    fn events<'a>(messages: &'a [Value], event: &str) -> Vec<&'a Value> {
+        messages
+            .iter()
+            .filter(|message| message["event"] == event)
+            .collect()
+    }
+
+    #[test]
+    fn breakpoints_and_stepping() {
+        // 0x200: call 0x206, 0x202: ld v1, 0x02, 0x204: jp 0x204,
+        // 0x206: ld v0, 0x01, 0x208: ret
+        let rom = [0x22, 0x06, 0x61, 0x02, 0x12, 0x04, 0x60, 0x01, 0x00, 0xEE];
+        let messages = session(
+            "stepping",
+            &rom,
+            json!({ "stopOnEntry": true }),
+            &[
+                (
+                    "setBreakpoints",
+                    json!({ "source": {}, "breakpoints": [{ "line": 4 }] }),
+                ),
+                ("configurationDone", json!({})),
+                ("continue", json!({ "threadId": 1 })),
+                ("stackTrace", json!({ "threadId": 1 })),
+                ("next", json!({ "threadId": 1 })),
+                ("variables", json!({ "variablesReference": 1 })),
+                (
+                    "readMemory",
+                    json!({ "memoryReference": "0x200", "count": 4 }),
+                ),
+                ("stepOut", json!({ "threadId": 1 })),
+                ("continue", json!({ "threadId": 1 })),
+                ("disconnect", json!({})),
+            ],
+        );
+
+        assert!(messages.iter().all(|message| message["success"] != false));
+        assert_eq!(events(&messages, "initialized").len(), 1);
+        let breakpoints = &response(&messages, "setBreakpoints")["body"]["breakpoints"];
+        assert_eq!(breakpoints[0]["line"], 4);
+
+        let reasons: Vec<&Value> = events(&messages, "stopped")
+            .iter()
+            .map(|event| &event["body"]["reason"])
+            .collect();
+        assert_eq!(reasons, ["entry", "breakpoint", "step", "step", "pause"]);
+
+        let frames = &response(&messages, "stackTrace")["body"]["stackFrames"];
+        assert_eq!(frames.as_array().unwrap().len(), 2);
+        assert_eq!(frames[0]["name"], "sub_206");
+        assert_eq!(frames[0]["line"], 4);
+        assert_eq!(frames[0]["instructionPointerReference"], "0x206");
+        assert_eq!(frames[1]["name"], "main");
+        assert_eq!(frames[1]["line"], 1);
+
+        let variables = &response(&messages, "variables")["body"]["variables"];
+        assert_eq!(
+            variables[0],
+            json!({ "name": "V0", "value": "0x01", "variablesReference": 0 })
+        );
+        assert_eq!(variables[17]["value"], "0x208");
+
+        let memory = &response(&messages, "readMemory")["body"];
+        assert_eq!(memory["data"], "IgZhAg==");
+        assert_eq!(memory["unreadableBytes"], 0);
+    }
+
+    #[test]
+    fn pause() {
+        // 0x200: add v0, 0x01, 0x202: jp 0x200
+        let messages = session(
+            "pause",
+            &[0x70, 0x01, 0x12, 0x00],
+            json!({}),
+            &[
+                ("configurationDone", json!({})),
+                ("pause", json!({ "threadId": 1 })),
+                ("threads", json!({})),
+                ("disconnect", json!({})),
+            ],
+        );
+
+        let stopped = events(&messages, "stopped");
+        assert_eq!(stopped.len(), 1);
+        assert_eq!(stopped[0]["body"]["reason"], "pause");
+        assert_eq!(
+            response(&messages, "threads")["body"]["threads"][0]["id"],
+            1
+        );
+    }
+
+    #[test]
+    fn exit() {
+        let messages = session(
+            "exit",
+            &[0x00, 0xFD],
+            json!({ "platform": "schip-1.1" }),
+            &[("configurationDone", json!({})), ("disconnect", json!({}))],
+        );
+
+        assert_eq!(events(&messages, "exited")[0]["body"]["exitCode"], 0);
+        assert_eq!(events(&messages, "terminated").len(), 1);
+    }
+
+    #[test]
+    fn errors() {
+        let messages = session(
+            "errors",
+            &[0x12, 0x00],
+            json!({ "platform": "gameboy" }),
+            &[("stackTrace", json!({})), ("frobnicate", json!({}))],
+        );
+
+        assert_eq!(response(&messages, "launch")["success"], false);
+        assert_eq!(
+            response(&messages, "stackTrace")["message"],
+            "No program launched"
+        );
+        assert_eq!(
+            response(&messages, "frobnicate")["message"],
+            "Unsupported request `frobnicate`"
+        );
+    }
+}
@@ -0,0 +1,209 @@
+use std::collections::{
+    BTreeMap,
+    BTreeSet,
+};
+
+use crate::chip8::{
+    Instruction,
+    Opcode,
+    PROGRAM_START,
+};
+
+pub use self::syntax::Syntax;
+
+mod syntax;
+mod tests;
+
+/// Bytes per line of data
+const DATA_ROW: usize = 8;
+
+/// ROM split into code and data by following its control flow.
+///
+/// Tracing starts at 0x200 and follows jumps, calls, both ways of every
+/// skip and the fall through of everything that doesn't end a path. `RET`,
+/// `EXIT`, `JP`, `JP V0` and opcodes that don't decode end a path. The
+/// base of a `JP V0` jump table is traced as well since its real targets
+/// aren't known. Bytes never reached are data.
+///
+/// Jump and call targets and `LD I` addresses inside the ROM get labels:
+/// `sub_XXX` for subroutines, `label_XXX` for jump targets and `data_XXX`
+/// for `LD I`.
+#[derive(Debug)]
+pub struct Disassembly {
+    rom: Vec<u8>,
+    /// Instructions by address, `LD I, long` covers 4 bytes, others 2
+    code: BTreeMap<usize, Instruction>,
+    labels: BTreeMap<usize, String>,
+}
+
+/// One line of a disassembly
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Line<'a> {
+    Label(&'a str),
+    /// Instruction with its address and the bytes it covers
+    Code(usize, &'a [u8], Instruction),
+    /// Unreached bytes starting at the address
+    Data(usize, &'a [u8]),
+}
+
+impl Disassembly {
+    pub fn new(rom: &[u8]) -> Self {
+        let rom = rom.to_vec();
+        let code = BTreeMap::new();
+        let labels = BTreeMap::new();
+
+        let mut disassembly = Self { rom, code, labels };
+        disassembly.trace();
+        disassembly
+    }
+
+    pub fn instruction_at(&self, address: usize) -> Option<Instruction> {
+        self.code.get(&address).copied()
+    }
+
+    pub fn label(&self, address: usize) -> Option<&str> {
+        self.labels.get(&address).map(String::as_str)
+    }
+
+    /// Code, data and labels in address order.
+    ///
+    /// An instruction overlapping the next one, as when a jump lands in the
+    /// middle of it, is shown as data so every address appears once.
+    pub fn lines(&self) -> Vec<Line<'_>> {
+        let end = PROGRAM_START + self.rom.len();
+        let starts: BTreeSet<usize> = self
+            .code
+            .keys()
+            .chain(self.labels.keys())
+            .copied()
+            .collect();
+
+        let mut lines = vec![];
+        let mut address = PROGRAM_START;
+        while address < end {
+            if let Some(label) = self.label(address) {
+                lines.push(Line::Label(label));
+            }
+            let next = starts.range(address + 1..).next().copied().unwrap_or(end);
+
+            match self.code.get(&address) {
+                Some(&instruction) if address + instruction_len(instruction) <= next => {
+                    let len = instruction_len(instruction);
+                    lines.push(Line::Code(address, self.bytes(address, len), instruction));
+                    address += len;
+                }
+                _ => {
+                    let len = (next - address).min(DATA_ROW);
+                    lines.push(Line::Data(address, self.bytes(address, len)));
+                    address += len;
+                }
+            }
+        }
+
+        lines
+    }
+
+    /// Disassembly as text in `syntax`
+    pub fn render(&self, syntax: Syntax) -> String {
+        let mut text = String::new();
+        for line in self.lines() {
+            text.push_str(&syntax.line(&line, |address| self.label(address)));
+            text.push('\n');
+        }
+        text
+    }
+
+    fn bytes(&self, address: usize, len: usize) -> &[u8] {
+        &self.rom[address - PROGRAM_START..address - PROGRAM_START + len]
+    }
+
+    fn decode(&self, address: usize) -> Option<Instruction> {
+        let offset = address.checked_sub(PROGRAM_START)?;
+        let bytes = self.rom.get(offset..offset + 2)?;
+        let instruction = Opcode::from(u16::from_be_bytes([bytes[0], bytes[1]])).instruction();
+        // The long address is the following word
+        if instruction == Instruction::LdILong && self.rom.len() < offset + 4 {
+            return None;
+        }
+
+        Some(instruction)
+    }
+
+    fn in_rom(&self, address: usize) -> bool {
+        (PROGRAM_START..PROGRAM_START + self.rom.len()).contains(&address)
+    }
+
+    fn add_label(&mut self, address: usize, prefix: &str) {
+        // Calls name an address over jumps, jumps over `LD I`
+        let rank = |prefix: &str| ["data", "label", "sub"].iter().position(|&p| p == prefix);
+        if !self.in_rom(address) {
+            return;
+        }
+        let current = self
+            .labels
+            .get(&address)
+            .and_then(|label| label.split('_').next())
+            .and_then(rank);
+        if current < rank(prefix) {
+            self.labels
+                .insert(address, format!("{prefix}_{address:03X}"));
+        }
+    }
+
+    fn trace(&mut self) {
+        let mut pending = vec![PROGRAM_START];
+
+        while let Some(address) = pending.pop() {
+            if self.code.contains_key(&address) {
+                continue;
+            }
+            let Some(instruction) = self.decode(address) else {
+                continue;
+            };
+            let next = address + instruction_len(instruction);
+
+            match instruction {
+                Instruction::Unknown(_) | Instruction::Sys(_) => continue,
+                Instruction::Ret | Instruction::Exit => {}
+                Instruction::Jp(nnn) | Instruction::JpV0(nnn) => {
+                    self.add_label(nnn, "label");
+                    pending.push(nnn);
+                }
+                Instruction::Call(nnn) => {
+                    self.add_label(nnn, "sub");
+                    pending.extend([next, nnn]);
+                }
+                Instruction::SeByte(..)
+                | Instruction::SneByte(..)
+                | Instruction::SeReg(..)
+                | Instruction::SneReg(..)
+                | Instruction::Skp(_)
+                | Instruction::Sknp(_) => {
+                    // Skipping `LD I, long` skips all 4 bytes
+                    let skipped = self.decode(next).map_or(2, instruction_len);
+                    pending.extend([next + skipped, next]);
+                }
+                Instruction::LdI(nnn) => {
+                    self.add_label(nnn, "data");
+                    pending.push(next);
+                }
+                Instruction::LdILong => {
+                    let offset = address - PROGRAM_START + 2;
+                    let nnnn = u16::from_be_bytes([self.rom[offset], self.rom[offset + 1]]);
+                    self.add_label(nnnn as usize, "data");
+                    pending.push(next);
+                }
+                _ => pending.push(next),
+            }
+            self.code.insert(address, instruction);
+        }
+    }
+}
+
+/// Bytes taken by `instruction`
+fn instruction_len(instruction: Instruction) -> usize {
+    match instruction {
+        Instruction::LdILong => 4,
+        _ => 2,
+    }
+}
@@ -0,0 +1,173 @@
+use std::{
+    fmt,
+    str::FromStr,
+};
+
+use {
+    super::Line,
+    crate::chip8::Instruction,
+};
+
+/// Assembly language of a disassembly
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Syntax {
+    /// Lowercase Cowgod mnemonics as in [`Instruction`]'s `Display`, each
+    /// line starting with the address and the raw bytes
+    #[default]
+    Cowgod,
+    /// Octo source that assembles back to the ROM, addresses and raw bytes
+    /// are comments
+    Octo,
+}
+
+impl Syntax {
+    /// Text of one line, `label` names the labeled addresses
+    pub fn line<'a>(&self, line: &Line<'_>, label: impl Fn(usize) -> Option<&'a str>) -> String {
+        match (self, line) {
+            (Self::Cowgod, Line::Label(name)) => format!("{name}:"),
+            (Self::Cowgod, &Line::Code(address, bytes, instruction)) => format!(
+                "{address:#05X}: {}  {}",
+                hex(bytes),
+                cowgod(instruction, bytes, label)
+            ),
+            (Self::Cowgod, &Line::Data(address, bytes)) => {
+                let bytes_list: Vec<String> = bytes.iter().map(|b| format!("{b:#04X}")).collect();
+                format!(
+                    "{address:#05X}: {}  db {}",
+                    hex(bytes),
+                    bytes_list.join(", ")
+                )
+            }
+            (Self::Octo, Line::Label(name)) => format!(": {name}"),
+            (Self::Octo, &Line::Code(address, bytes, instruction)) => format!(
+                "\t{:<23} # {address:#05X} {}",
+                octo(instruction, bytes, label),
+                hex(bytes)
+            ),
+            (Self::Octo, &Line::Data(address, bytes)) => {
+                format!("\t{:<23} # {address:#05X}", octo_bytes(bytes))
+            }
+        }
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02X}")).collect()
+}
+
+/// Label of `address` or the address itself
+fn target<'a>(address: usize, label: impl Fn(usize) -> Option<&'a str>) -> String {
+    label(address).map_or_else(|| format!("{address:#05X}"), str::to_string)
+}
+
+/// Address following the `LD I, long` opcode
+fn long_address(bytes: &[u8]) -> usize {
+    u16::from_be_bytes([bytes[2], bytes[3]]) as usize
+}
+
+fn cowgod<'a>(
+    instruction: Instruction,
+    bytes: &[u8],
+    label: impl Fn(usize) -> Option<&'a str>,
+) -> String {
+    match instruction {
+        Instruction::Jp(addr) => format!("jp {}", target(addr, label)),
+        Instruction::Call(addr) => format!("call {}", target(addr, label)),
+        Instruction::LdI(addr) => format!("ld i, {}", target(addr, label)),
+        Instruction::JpV0(addr) => format!("jp v0, {}", target(addr, label)),
+        Instruction::LdILong => format!("ld i, long {}", target(long_address(bytes), label)),
+        instruction => instruction.to_string(),
+    }
+}
+
+fn octo<'a>(
+    instruction: Instruction,
+    bytes: &[u8],
+    label: impl Fn(usize) -> Option<&'a str>,
+) -> String {
+    match instruction {
+        Instruction::Sys(_) | Instruction::Unknown(_) => octo_bytes(bytes),
+        Instruction::Cls => "clear".to_string(),
+        Instruction::Ret => "return".to_string(),
+        Instruction::Scd(n) => format!("scroll-down {n}"),
+        Instruction::Scu(n) => format!("scroll-up {n}"),
+        Instruction::Scr => "scroll-right".to_string(),
+        Instruction::Scl => "scroll-left".to_string(),
+        Instruction::Exit => "exit".to_string(),
+        Instruction::Low => "lores".to_string(),
+        Instruction::High => "hires".to_string(),
+        Instruction::Jp(addr) => format!("jump {}", target(addr, label)),
+        Instruction::Call(addr) => match label(addr) {
+            Some(name) => name.to_string(),
+            None => format!(":call {addr:#05X}"),
+        },
+        // Octo's `if ... then` runs the next instruction when the condition
+        // holds, so it's the opposite of the skip condition
+        Instruction::SeByte(x, nn) => format!("if v{x:x} != {nn:#04X} then"),
+        Instruction::SneByte(x, nn) => format!("if v{x:x} == {nn:#04X} then"),
+        Instruction::SeReg(x, y) => format!("if v{x:x} != v{y:x} then"),
+        Instruction::SneReg(x, y) => format!("if v{x:x} == v{y:x} then"),
+        Instruction::Skp(x) => format!("if v{x:x} -key then"),
+        Instruction::Sknp(x) => format!("if v{x:x} key then"),
+        Instruction::Save(x, y) => format!("save v{x:x} - v{y:x}"),
+        Instruction::Load(x, y) => format!("load v{x:x} - v{y:x}"),
+        Instruction::LdByte(x, nn) => format!("v{x:x} := {nn:#04X}"),
+        Instruction::AddByte(x, nn) => format!("v{x:x} += {nn:#04X}"),
+        Instruction::LdReg(x, y) => format!("v{x:x} := v{y:x}"),
+        Instruction::Or(x, y) => format!("v{x:x} |= v{y:x}"),
+        Instruction::And(x, y) => format!("v{x:x} &= v{y:x}"),
+        Instruction::Xor(x, y) => format!("v{x:x} ^= v{y:x}"),
+        Instruction::AddReg(x, y) => format!("v{x:x} += v{y:x}"),
+        Instruction::Sub(x, y) => format!("v{x:x} -= v{y:x}"),
+        Instruction::Shr(x, y) => format!("v{x:x} >>= v{y:x}"),
+        Instruction::Subn(x, y) => format!("v{x:x} =- v{y:x}"),
+        Instruction::Shl(x, y) => format!("v{x:x} <<= v{y:x}"),
+        Instruction::LdI(addr) => format!("i := {}", target(addr, label)),
+        Instruction::JpV0(addr) => format!("jump0 {}", target(addr, label)),
+        Instruction::Rnd(x, nn) => format!("v{x:x} := random {nn:#04X}"),
+        Instruction::Drw(x, y, n) => format!("sprite v{x:x} v{y:x} {n}"),
+        Instruction::LdILong => format!("i := long {}", target(long_address(bytes), label)),
+        Instruction::Plane(n) => format!("plane {n}"),
+        Instruction::Audio => "audio".to_string(),
+        Instruction::LdVxDt(x) => format!("v{x:x} := delay"),
+        Instruction::LdVxK(x) => format!("v{x:x} := key"),
+        Instruction::LdDtVx(x) => format!("delay := v{x:x}"),
+        Instruction::LdStVx(x) => format!("buzzer := v{x:x}"),
+        Instruction::AddI(x) => format!("i += v{x:x}"),
+        Instruction::LdF(x) => format!("i := hex v{x:x}"),
+        Instruction::LdHf(x) => format!("i := bighex v{x:x}"),
+        Instruction::LdPitch(x) => format!("pitch := v{x:x}"),
+        Instruction::LdB(x) => format!("bcd v{x:x}"),
+        Instruction::LdIVx(x) => format!("save v{x:x}"),
+        Instruction::LdVxI(x) => format!("load v{x:x}"),
+        Instruction::LdRVx(x) => format!("saveflags v{x:x}"),
+        Instruction::LdVxR(x) => format!("loadflags v{x:x}"),
+    }
+}
+
+fn octo_bytes(bytes: &[u8]) -> String {
+    let bytes: Vec<String> = bytes.iter().map(|b| format!("{b:#04X}")).collect();
+    bytes.join(" ")
+}
+
+impl FromStr for Syntax {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cowgod" => Ok(Self::Cowgod),
+            "octo" => Ok(Self::Octo),
+            _ => Err(format!("unknown syntax `{s}`, expected cowgod or octo")),
+        }
+    }
+}
+
+impl fmt::Display for Syntax {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Cowgod => "cowgod",
+            Self::Octo => "octo",
+        };
+        write!(f, "{name}")
+    }
+}
@@ -0,0 +1,129 @@
+#[cfg(test)]
+mod disassembly {
+    use crate::{
+        chip8::Instruction,
+        disasm::{
+            Disassembly,
+            Line,
+            Syntax,
+        },
+    };
+
+    /// 0x200: call 0x208, 0x202: ld v1, 0x02, 0x204: ld i, 0x20C,
+    /// 0x206: jp 0x204, 0x208: ld v0, 0x01, 0x20A: ret, 0x20C: sprite
+    const ROM: [u8; 16] = [
+        0x22, 0x08, 0x61, 0x02, 0xA2, 0x0C, 0x12, 0x04, 0x60, 0x01, 0x00, 0xEE, 0xFF, 0x81, 0x81,
+        0xFF,
+    ];
+
+    #[test]
+    fn cowgod() {
+        let text = Disassembly::new(&ROM).render(Syntax::Cowgod);
+
+        assert_eq!(
+            text,
+            "\
+0x200: 2208  call sub_208
+0x202: 6102  ld v1, 0x02
+label_204:
+0x204: A20C  ld i, data_20C
+0x206: 1204  jp label_204
+sub_208:
+0x208: 6001  ld v0, 0x01
+0x20A: 00EE  ret
+data_20C:
+0x20C: FF8181FF  db 0xFF, 0x81, 0x81, 0xFF
+"
+        );
+    }
+
+    #[test]
+    fn octo() {
+        let text = Disassembly::new(&ROM).render(Syntax::Octo);
+
+        assert_eq!(
+            text,
+            "\
+\tsub_208                 # 0x200 2208
+\tv1 := 0x02              # 0x202 6102
+: label_204
+\ti := data_20C           # 0x204 A20C
+\tjump label_204          # 0x206 1204
+: sub_208
+\tv0 := 0x01              # 0x208 6001
+\treturn                  # 0x20A 00EE
+: data_20C
+\t0xFF 0x81 0x81 0xFF     # 0x20C
+"
+        );
+    }
+
+    #[test]
+    fn skips_follow_both_ways() {
+        // se v0, 0x00, jp 0x208, exit, unreached, 0x208: ret
+        let rom = [0x30, 0x00, 0x12, 0x08, 0x00, 0xFD, 0x12, 0x34, 0x00, 0xEE];
+        let disassembly = Disassembly::new(&rom);
+
+        assert_eq!(disassembly.instruction_at(0x204), Some(Instruction::Exit));
+        assert_eq!(disassembly.instruction_at(0x206), None);
+        assert_eq!(disassembly.instruction_at(0x208), Some(Instruction::Ret));
+        assert!(disassembly
+            .lines()
+            .contains(&Line::Data(0x206, &[0x12, 0x34])));
+    }
+
+    #[test]
+    fn long_load() {
+        // se v0, 0x00, ld i, long 0x0208, exit, 0x208: data
+        let rom = [0x30, 0x00, 0xF0, 0x00, 0x02, 0x08, 0x00, 0xFD, 0xAA];
+        let disassembly = Disassembly::new(&rom);
+        let text = disassembly.render(Syntax::Cowgod);
+
+        // The skip jumps over all 4 bytes
+        assert_eq!(disassembly.instruction_at(0x204), None);
+        assert!(text.contains("0x202: F0000208  ld i, long data_208\n"));
+        assert!(text.contains("data_208:\n0x208: AA  db 0xAA\n"));
+        assert!(disassembly
+            .render(Syntax::Octo)
+            .contains("\ti := long data_208"));
+    }
+
+    #[test]
+    fn jump_into_instruction() {
+        // 0x200: jp 0x203, 0x202: ld v0, 0x00 whose second byte is 0x203:
+        // ret
+        let rom = [0x12, 0x03, 0x60, 0x00, 0xEE];
+        let disassembly = Disassembly::new(&rom);
+
+        assert_eq!(
+            disassembly.lines()[1..],
+            [
+                Line::Data(0x202, &[0x60]),
+                Line::Label("label_203"),
+                Line::Code(0x203, &[0x00, 0xEE], Instruction::Ret),
+            ]
+        );
+    }
+
+    #[test]
+    fn targets_outside_the_rom() {
+        // call 0x300, sys 0x123
+        let rom = [0x23, 0x00, 0x01, 0x23];
+        let text = Disassembly::new(&rom).render(Syntax::Cowgod);
+
+        assert_eq!(
+            text,
+            "0x200: 2300  call 0x300\n0x202: 0123  db 0x01, 0x23\n"
+        );
+        assert!(Disassembly::new(&rom)
+            .render(Syntax::Octo)
+            .starts_with("\t:call 0x300"));
+    }
+
+    #[test]
+    fn syntax_from_str() {
+        assert_eq!("octo".parse(), Ok(Syntax::Octo));
+        assert_eq!(Syntax::Cowgod.to_string(), "cowgod");
+        assert!("nasm".parse::<Syntax>().is_err());
+    }
+}
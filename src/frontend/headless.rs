@@ -0,0 +1,105 @@
+use crate::{
+    chip8::{
+        Chip8,
+        Chip8Error,
+    },
+    movie::Movie,
+    trace::Tracer,
+};
+
+/// Runs a [`Chip8`] as fast as it can without a window, audio or keyboard
+pub struct HeadlessFrontend {
+    /// Movie fed to the keypad, the run ends with it
+    playback: Option<Movie>,
+    recording: Option<Movie>,
+    tracer: Option<Tracer>,
+    /// Frames to run, unlimited by default
+    frames: Option<usize>,
+    /// Frames run since the start, the position in the movies
+    frame: usize,
+}
+
+impl HeadlessFrontend {
+    pub fn new() -> Self {
+        let playback = None;
+        let recording = None;
+        let tracer = None;
+        let frames = None;
+        let frame = 0;
+
+        Self {
+            playback,
+            recording,
+            tracer,
+            frames,
+            frame,
+        }
+    }
+
+    pub fn set_playback(&mut self, movie: Movie) {
+        self.playback = Some(movie);
+    }
+
+    /// Record the keypad into `movie`, see [`HeadlessFrontend::take_recording`]
+    pub fn set_recording(&mut self, movie: Movie) {
+        self.recording = Some(movie);
+    }
+
+    pub fn take_recording(&mut self) -> Option<Movie> {
+        self.recording.take()
+    }
+
+    /// Trace every frame run, see [`HeadlessFrontend::take_tracer`]
+    pub fn set_tracer(&mut self, tracer: Tracer) {
+        self.tracer = Some(tracer);
+    }
+
+    pub fn take_tracer(&mut self) -> Option<Tracer> {
+        self.tracer.take()
+    }
+
+    /// Stop after `frames` frames
+    pub fn set_frames(&mut self, frames: usize) {
+        self.frames = Some(frames);
+    }
+
+    /// Frames run so far
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    /// Run until the frame limit, the end of the movie, the program exits or
+    /// fails. `on_frame` gets the number of frames run and the machine after
+    /// every frame.
+    pub fn run(
+        &mut self,
+        chip8: &mut Chip8,
+        mut on_frame: impl FnMut(usize, &Chip8),
+    ) -> Result<(), Chip8Error> {
+        while !chip8.is_halted() && self.frames.is_none_or(|frames| self.frame < frames) {
+            if let Some(movie) = &self.playback {
+                if !movie.feed(self.frame, chip8) {
+                    break;
+                }
+            }
+            if let Some(recording) = &mut self.recording {
+                recording.push(chip8);
+            }
+
+            match &mut self.tracer {
+                Some(tracer) => tracer.run_frame(chip8)?,
+                None => chip8.run_frame()?,
+            }
+            self.frame += 1;
+            on_frame(self.frame, chip8);
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for HeadlessFrontend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,6 @@
+pub mod headless;
+#[cfg(feature = "sdl")]
+pub mod sdl;
+#[cfg(feature = "terminal")]
+pub mod terminal;
+mod tests;
@@ -0,0 +1,83 @@
+use sdl2::{
+    audio::{
+        AudioCallback,
+        AudioSpecDesired,
+    },
+    Sdl,
+};
+
+/// Plays the XO-CHIP 1-bit audio pattern, or a plain square wave for
+/// programs that never loaded one
+pub struct Beeper {
+    freq: f32,
+    phase: f32,
+    volume: f32,
+    pattern: Option<[u8; 16]>,
+    pitch: u8,
+}
+
+impl Beeper {
+    pub fn set_pattern(&mut self, pattern: Option<[u8; 16]>, pitch: u8) {
+        self.pattern = pattern;
+        self.pitch = pitch;
+    }
+
+    /// Pattern bits played per second
+    fn pattern_rate(&self) -> f32 {
+        4000.0 * 2f32.powf((self.pitch as f32 - 64.0) / 48.0)
+    }
+}
+
+impl AudioCallback for Beeper {
+    type Channel = f32;
+
+    fn callback(&mut self, out: &mut [f32]) {
+        match self.pattern {
+            Some(pattern) => {
+                // Phase counts pattern bits
+                let phase_inc = self.pattern_rate() / self.freq;
+                for x in out.iter_mut() {
+                    let bit = self.phase as usize % 128;
+                    let on = (pattern[bit / 8] >> (7 - bit % 8)) & 0x1 == 1;
+                    *x = if on { self.volume } else { -self.volume };
+                    self.phase = (self.phase + phase_inc) % 128.0;
+                }
+            }
+            None => {
+                // Generate a square wave
+                let phase_inc = 440.0 / self.freq;
+                for x in out.iter_mut() {
+                    *x = if self.phase >= 0.0 && self.phase <= 0.5 {
+                        self.volume
+                    } else {
+                        -self.volume
+                    };
+                    self.phase = (self.phase + phase_inc) % 1.0;
+                }
+            }
+        }
+    }
+}
+
+pub fn init(sdl_cxt: &Sdl) -> sdl2::audio::AudioDevice<Beeper> {
+    let sdl_audio_ss = sdl_cxt.audio().unwrap();
+
+    let desired_spec = AudioSpecDesired {
+        freq: Some(44100),
+        channels: Some(1), // mono
+        samples: None,     // default sample size
+    };
+
+    sdl_audio_ss
+        .open_playback(None, &desired_spec, |spec| {
+            // initialize the audio callback
+            Beeper {
+                freq: spec.freq as f32,
+                phase: 0.0,
+                volume: 0.25,
+                pattern: None,
+                pitch: 64,
+            }
+        })
+        .unwrap()
+}
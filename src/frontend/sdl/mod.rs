@@ -0,0 +1,404 @@
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::BufWriter,
+    path::PathBuf,
+    thread,
+    time::{
+        Duration,
+        Instant,
+        SystemTime,
+    },
+};
+
+use sdl2::{
+    event::Event,
+    keyboard::{
+        Keycode,
+        Mod,
+        Scancode,
+    },
+    pixels::Color,
+    rect::Rect,
+    render::Canvas,
+    video::Window,
+    EventPump,
+    Sdl,
+};
+
+use crate::{
+    chip8::{
+        Chip8,
+        Chip8Error,
+        FRAMES_PER_SECOND,
+        HIRES_HEIGHT,
+        HIRES_WIDTH,
+    },
+    movie::Movie,
+    palette::Palette,
+    rewind::Rewind,
+    save_state::SaveSlots,
+    trace::Tracer,
+};
+
+use self::hex_to_key::hex_to_key;
+
+mod audio;
+mod hex_to_key;
+
+/// SDL window, audio and keyboard driving a [`Chip8`]
+pub struct SdlFrontend {
+    sdl_cxt: Sdl,
+    events: EventPump,
+    canvas: Canvas<Window>,
+    palette: Palette,
+    save_slots: Option<SaveSlots>,
+    rewind: Rewind,
+    /// Movie fed to the keypad instead of the keyboard
+    playback: Option<Movie>,
+    recording: Option<Movie>,
+    tracer: Option<Tracer>,
+    /// Frames run since the start, the position in the movies
+    frame: usize,
+}
+
+impl SdlFrontend {
+    pub fn new() -> Self {
+        let sdl_cxt = sdl2::init().unwrap();
+        let events = sdl_cxt.event_pump().unwrap();
+        let sdl_video_ss = sdl_cxt.video().unwrap();
+
+        // Low resolution pixels are 10x10, high resolution ones 5x5
+        let scale = 5;
+        let sdl_window = sdl_video_ss
+            .window(
+                "Chip-8 emulator",
+                HIRES_WIDTH as u32 * scale,
+                HIRES_HEIGHT as u32 * scale,
+            )
+            .position_centered()
+            .build()
+            .unwrap();
+
+        let canvas = sdl_window.into_canvas().build().unwrap();
+        let palette = Palette::default();
+        let save_slots = None;
+        let rewind = Rewind::new(0);
+        let playback = None;
+        let recording = None;
+        let tracer = None;
+        let frame = 0;
+
+        Self {
+            sdl_cxt,
+            events,
+            canvas,
+            palette,
+            save_slots,
+            rewind,
+            playback,
+            recording,
+            tracer,
+            frame,
+        }
+    }
+
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.palette = palette;
+    }
+
+    /// History played backwards while Backspace is held
+    pub fn set_rewind(&mut self, rewind: Rewind) {
+        self.rewind = rewind;
+    }
+
+    /// Play `movie` back, the keyboard takes over after its last frame
+    pub fn set_playback(&mut self, movie: Movie) {
+        self.playback = Some(movie);
+    }
+
+    /// Record the keypad into `movie`, see [`SdlFrontend::take_recording`]
+    pub fn set_recording(&mut self, movie: Movie) {
+        self.recording = Some(movie);
+    }
+
+    pub fn take_recording(&mut self) -> Option<Movie> {
+        self.recording.take()
+    }
+
+    /// Trace every frame run, see [`SdlFrontend::take_tracer`]
+    pub fn set_tracer(&mut self, tracer: Tracer) {
+        self.tracer = Some(tracer);
+    }
+
+    pub fn take_tracer(&mut self) -> Option<Tracer> {
+        self.tracer.take()
+    }
+
+    /// Enable the save state hotkeys
+    pub fn set_save_slots(&mut self, save_slots: SaveSlots) {
+        self.save_slots = Some(save_slots);
+    }
+}
+
+impl Default for SdlFrontend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SdlFrontend {
+    /// Run until the window is closed, the program exits or fails.
+    ///
+    /// Every 1/60 s the frontend runs one [`Chip8::run_frame`] and presents
+    /// the screen. PageUp and PageDown change the instructions per frame, F1
+    /// to F9 save to a slot and Shift+F1 to F9 load from it. Holding Backspace
+    /// rewinds. F12 saves a PNG screenshot into the working directory.
+    ///
+    /// While a movie plays or records, changing the speed and loading states
+    /// are disabled since the movie couldn't reproduce them.
+    pub fn run(&mut self, chip8: &mut Chip8) -> Result<(), Chip8Error> {
+        let mut device = audio::init(&self.sdl_cxt);
+
+        let frame_duration = Duration::from_secs(1) / FRAMES_PER_SECOND;
+        let mut next_frame = Instant::now();
+
+        loop {
+            let has_movie = self.has_movie();
+            for event in self.events.poll_iter() {
+                match event {
+                    Event::Quit { .. }
+                    | Event::KeyDown {
+                        keycode: Some(Keycode::Escape),
+                        ..
+                    } => return Ok(()),
+                    Event::KeyDown {
+                        keycode: Some(Keycode::PageUp | Keycode::PageDown),
+                        ..
+                    } if has_movie => log::warn!("Speed is fixed during movies"),
+                    Event::KeyDown {
+                        keycode: Some(Keycode::PageUp),
+                        ..
+                    } => Self::change_speed(chip8, 1),
+                    Event::KeyDown {
+                        keycode: Some(Keycode::PageDown),
+                        ..
+                    } => Self::change_speed(chip8, -1),
+                    Event::KeyDown {
+                        keycode: Some(Keycode::F12),
+                        ..
+                    } => Self::screenshot(&self.canvas, &self.palette, chip8),
+                    Event::KeyDown {
+                        keycode: Some(keycode),
+                        keymod,
+                        ..
+                    } => {
+                        if let Some(slot) = save_slot(keycode) {
+                            let load = keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD);
+                            if load && has_movie {
+                                log::warn!("States can't be loaded during movies");
+                                continue;
+                            }
+                            Self::save_or_load(self.save_slots.as_ref(), chip8, slot, load);
+                            if load {
+                                self.rewind.clear();
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            let rewinding = self
+                .events
+                .keyboard_state()
+                .is_scancode_pressed(Scancode::Backspace);
+            if rewinding {
+                if self.rewind.rewind(chip8) {
+                    self.frame -= 1;
+                    if let Some(recording) = &mut self.recording {
+                        recording.frames.truncate(self.frame);
+                    }
+                }
+            } else {
+                let playing = self
+                    .playback
+                    .as_ref()
+                    .is_some_and(|movie| movie.feed(self.frame, chip8));
+                if !playing {
+                    self.update_keypad(chip8);
+                }
+                if let Some(recording) = &mut self.recording {
+                    recording.push(chip8);
+                }
+
+                match &mut self.tracer {
+                    Some(tracer) => tracer.run_frame(chip8)?,
+                    None => chip8.run_frame()?,
+                }
+                self.frame += 1;
+                if chip8.is_halted() {
+                    return Ok(());
+                }
+                self.rewind.push(chip8);
+            }
+
+            if chip8.take_redraw() {
+                self.draw(chip8);
+            }
+
+            device
+                .lock()
+                .set_pattern(chip8.audio_pattern(), chip8.pitch());
+            if chip8.timers().sound() > 0 {
+                device.resume();
+            } else {
+                device.pause();
+            }
+
+            next_frame += frame_duration;
+            let now = Instant::now();
+            if next_frame > now {
+                thread::sleep(next_frame - now);
+            } else {
+                // Running late, don't try to catch up on missed frames
+                next_frame = now;
+            }
+        }
+    }
+
+    fn has_movie(&self) -> bool {
+        self.playback.is_some() || self.recording.is_some()
+    }
+
+    fn change_speed(chip8: &mut Chip8, delta: isize) {
+        let instructions_per_frame = chip8.instructions_per_frame().saturating_add_signed(delta);
+        chip8.set_instructions_per_frame(instructions_per_frame);
+        log::info!("Instructions per frame: {}", chip8.instructions_per_frame());
+    }
+
+    fn save_or_load(save_slots: Option<&SaveSlots>, chip8: &mut Chip8, slot: u8, load: bool) {
+        let Some(save_slots) = save_slots else {
+            return;
+        };
+
+        let result = if load {
+            save_slots.load(slot, chip8)
+        } else {
+            save_slots.save(slot, chip8)
+        };
+        match result {
+            Ok(()) if load => log::info!("Loaded slot {slot}"),
+            Ok(()) => log::info!("Saved slot {slot}"),
+            Err(err) => log::warn!("Slot {slot}: {err}"),
+        }
+    }
+
+    fn screenshot(canvas: &Canvas<Window>, palette: &Palette, chip8: &Chip8) {
+        let screen = chip8.screen();
+        let (window_width, _) = canvas.output_size().unwrap();
+        let scale = window_width as usize / screen.width();
+
+        let path = screenshot_path();
+        let result = File::create(&path)
+            .and_then(|file| screen.write_png(BufWriter::new(file), palette, scale));
+        match result {
+            Ok(()) => log::info!("Saved {}", path.display()),
+            Err(err) => log::warn!("{}: {err}", path.display()),
+        }
+    }
+
+    fn get_pressed_keys(&self) -> HashSet<Keycode> {
+        let keys: HashSet<Keycode> = self
+            .events
+            .keyboard_state()
+            .pressed_scancodes()
+            .filter_map(Keycode::from_scancode)
+            .collect();
+
+        keys
+    }
+
+    fn update_keypad(&self, chip8: &mut Chip8) {
+        let pressed_keys = self.get_pressed_keys();
+        log::debug!("Pressed keys: {:?}", pressed_keys);
+
+        let mut keys = [false; 16];
+        for (hex, pressed) in keys.iter_mut().enumerate() {
+            if let Some(key) = hex_to_key(hex as u8) {
+                *pressed = pressed_keys.contains(&key);
+            }
+        }
+        chip8.keypad_mut().set_keys(keys);
+    }
+
+    fn draw(&mut self, chip8: &Chip8) {
+        let [r, g, b] = self.palette.color(0);
+        let screen = chip8.screen();
+        let (window_width, _) = self.canvas.output_size().unwrap();
+        let scale = window_width / screen.width() as u32;
+
+        self.canvas.set_draw_color(Color::RGB(r, g, b));
+        self.canvas.clear();
+
+        for (py, row) in screen.rows().enumerate() {
+            for (px, &pixel) in row.iter().enumerate() {
+                if pixel != 0 {
+                    let [r, g, b] = self.palette.color(pixel);
+                    self.canvas.set_draw_color(Color::RGB(r, g, b));
+                    let x = ((px as u32) * scale) as i32;
+                    let y = ((py as u32) * scale) as i32;
+                    let rect = Rect::new(x, y, scale, scale);
+                    self.canvas.fill_rect(rect).unwrap();
+                }
+            }
+        }
+
+        self.canvas.present();
+    }
+}
+
+/// Save state slot of the function keys F1 to F9
+fn save_slot(keycode: Keycode) -> Option<u8> {
+    let slot = match keycode {
+        Keycode::F1 => 1,
+        Keycode::F2 => 2,
+        Keycode::F3 => 3,
+        Keycode::F4 => 4,
+        Keycode::F5 => 5,
+        Keycode::F6 => 6,
+        Keycode::F7 => 7,
+        Keycode::F8 => 8,
+        Keycode::F9 => 9,
+        _ => return None,
+    };
+
+    Some(slot)
+}
+
+/// `chip-8-YYYYMMDD-HHMMSS.png` with the current UTC time
+fn screenshot_path() -> PathBuf {
+    let seconds = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let (days, time) = (seconds / 86400, seconds % 86400);
+
+    // Days since 1970-01-01 to a civil date, from Howard Hinnant's algorithms
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+
+    PathBuf::from(format!(
+        "chip-8-{year:04}{month:02}{day:02}-{:02}{:02}{:02}.png",
+        time / 3600,
+        time / 60 % 60,
+        time % 60
+    ))
+}
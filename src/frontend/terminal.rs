@@ -0,0 +1,298 @@
+use std::{
+    error::Error,
+    fmt::{
+        self,
+        Write as _,
+    },
+    io::{
+        self,
+        Write,
+    },
+    thread,
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+use termion::{
+    cursor::HideCursor,
+    event::Key,
+    input::TermRead,
+    raw::IntoRawMode,
+    screen::IntoAlternateScreen,
+};
+
+use crate::{
+    chip8::{
+        Chip8,
+        Chip8Error,
+        Screen,
+        FRAMES_PER_SECOND,
+    },
+    movie::Movie,
+    palette::Palette,
+    trace::Tracer,
+};
+
+/// Frames a key stays pressed after the terminal sent it.
+///
+/// Terminals only report presses, a held key repeats after a delay of about
+/// a quarter second. Holding for longer than that keeps the key down
+/// between the first press and the repeats.
+const HOLD_FRAMES: u8 = 20;
+
+/// Running the program or talking to the terminal failed
+#[derive(Debug)]
+pub enum TerminalError {
+    Chip8(Chip8Error),
+    /// Stdout isn't a terminal, or reading keys or drawing failed
+    Io(io::Error),
+}
+
+/// Terminal window and keyboard driving a [`Chip8`], for SSH sessions
+pub struct TerminalFrontend {
+    palette: Palette,
+    /// Movie fed to the keypad instead of the keyboard
+    playback: Option<Movie>,
+    recording: Option<Movie>,
+    tracer: Option<Tracer>,
+    /// Frames left each key stays pressed
+    held: [u8; 16],
+    /// Frames run since the start, the position in the movies
+    frame: usize,
+}
+
+impl TerminalFrontend {
+    pub fn new() -> Self {
+        let palette = Palette::default();
+        let playback = None;
+        let recording = None;
+        let tracer = None;
+        let held = [0; 16];
+        let frame = 0;
+
+        Self {
+            palette,
+            playback,
+            recording,
+            tracer,
+            held,
+            frame,
+        }
+    }
+
+    pub fn set_palette(&mut self, palette: Palette) {
+        self.palette = palette;
+    }
+
+    /// Play `movie` back, the keyboard takes over after its last frame
+    pub fn set_playback(&mut self, movie: Movie) {
+        self.playback = Some(movie);
+    }
+
+    /// Record the keypad into `movie`, see [`TerminalFrontend::take_recording`]
+    pub fn set_recording(&mut self, movie: Movie) {
+        self.recording = Some(movie);
+    }
+
+    pub fn take_recording(&mut self) -> Option<Movie> {
+        self.recording.take()
+    }
+
+    /// Trace every frame run, see [`TerminalFrontend::take_tracer`]
+    pub fn set_tracer(&mut self, tracer: Tracer) {
+        self.tracer = Some(tracer);
+    }
+
+    pub fn take_tracer(&mut self) -> Option<Tracer> {
+        self.tracer.take()
+    }
+}
+
+impl Default for TerminalFrontend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TerminalFrontend {
+    /// Run until Escape or Ctrl+C, the program exits or fails.
+    ///
+    /// The terminal switches to raw mode and its alternate screen, both are
+    /// restored on return. Every 1/60 s the frontend runs one frame and
+    /// redraws the screen when it changed. Beeps ring the terminal bell and
+    /// show a note below the screen while they last.
+    pub fn run(&mut self, chip8: &mut Chip8) -> Result<(), TerminalError> {
+        if !termion::is_tty(&io::stdout()) {
+            let err = io::Error::new(io::ErrorKind::Unsupported, "stdout is not a terminal");
+            return Err(TerminalError::Io(err));
+        }
+        let stdout = io::stdout().into_raw_mode()?.into_alternate_screen()?;
+        let mut stdout = HideCursor::from(stdout);
+        let mut keys = termion::async_stdin().keys();
+
+        let frame_duration = Duration::from_secs(1) / FRAMES_PER_SECOND;
+        let mut next_frame = Instant::now();
+        let mut beeping = false;
+        write!(stdout, "{}", render(chip8.screen(), &self.palette))?;
+
+        loop {
+            for key in keys.by_ref() {
+                match key? {
+                    Key::Esc | Key::Ctrl('c') => return Ok(()),
+                    Key::Char(c) => {
+                        if let Some(hex) = char_to_hex(c) {
+                            self.held[hex as usize] = HOLD_FRAMES;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            let playing = self
+                .playback
+                .as_ref()
+                .is_some_and(|movie| movie.feed(self.frame, chip8));
+            if !playing {
+                for (key, held) in self.held.iter().enumerate() {
+                    chip8.set_key(key as u8, *held > 0);
+                }
+            }
+            if let Some(recording) = &mut self.recording {
+                recording.push(chip8);
+            }
+
+            match &mut self.tracer {
+                Some(tracer) => tracer.run_frame(chip8)?,
+                None => chip8.run_frame()?,
+            }
+            self.frame += 1;
+            if chip8.is_halted() {
+                return Ok(());
+            }
+            for held in &mut self.held {
+                *held = held.saturating_sub(1);
+            }
+
+            let redraw = chip8.take_redraw();
+            if redraw {
+                write!(stdout, "{}", render(chip8.screen(), &self.palette))?;
+            }
+            let sound = chip8.timers().sound() > 0;
+            if sound && !beeping {
+                write!(stdout, "\x07")?;
+            }
+            if redraw || sound != beeping {
+                let note = if sound { "\u{266a}" } else { " " };
+                let row = chip8.screen().height() / 2 + 1;
+                write!(stdout, "\x1b[{row};1H{note}")?;
+            }
+            beeping = sound;
+            stdout.flush()?;
+
+            next_frame += frame_duration;
+            let now = Instant::now();
+            if next_frame > now {
+                thread::sleep(next_frame - now);
+            } else {
+                // Running late, don't try to catch up on missed frames
+                next_frame = now;
+            }
+        }
+    }
+}
+
+/// Hex keypad key of a character, with the layout of the SDL frontend:
+///
+/// ```text
+/// 1 2 3 C      1 2 3 4
+/// 4 5 6 D  ->  Q W E R
+/// 7 8 9 E      A S D F
+/// A 0 B F      Z X C V
+/// ```
+pub fn char_to_hex(c: char) -> Option<u8> {
+    let hex = match c.to_ascii_lowercase() {
+        '1' => 0x1,
+        '2' => 0x2,
+        '3' => 0x3,
+        '4' => 0xC,
+
+        'q' => 0x4,
+        'w' => 0x5,
+        'e' => 0x6,
+        'r' => 0xD,
+
+        'a' => 0x7,
+        's' => 0x8,
+        'd' => 0x9,
+        'f' => 0xE,
+
+        'z' => 0xA,
+        'x' => 0x0,
+        'c' => 0xB,
+        'v' => 0xF,
+        _ => return None,
+    };
+
+    Some(hex)
+}
+
+/// ANSI escapes drawing `screen` from the top left corner of the terminal.
+///
+/// Every character is an upper half block `▀` showing two pixels, the
+/// foreground color is the upper pixel and the background the lower one.
+pub fn render(screen: &Screen, palette: &Palette) -> String {
+    let mut text = String::from("\x1b[H");
+    let rows: Vec<&[u8]> = screen.rows().collect();
+
+    for pair in rows.chunks(2) {
+        let mut colors = None;
+        for (x, &upper) in pair[0].iter().enumerate() {
+            let lower = pair.get(1).map_or(0, |row| row[x]);
+            let pixel_colors = (palette.color(upper), palette.color(lower));
+            if colors != Some(pixel_colors) {
+                let ([r, g, b], [br, bg, bb]) = pixel_colors;
+                write!(text, "\x1b[38;2;{r};{g};{b}m\x1b[48;2;{br};{bg};{bb}m").unwrap();
+                colors = Some(pixel_colors);
+            }
+            text.push('\u{2580}');
+        }
+        // Raw mode doesn't return the carriage on a line feed
+        text.push_str("\x1b[0m\x1b[K\r\n");
+    }
+    // Rows left over from a larger resolution
+    text.push_str("\x1b[J");
+
+    text
+}
+
+impl fmt::Display for TerminalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Chip8(err) => write!(f, "{err}"),
+            Self::Io(err) => write!(f, "Terminal failed: {err}"),
+        }
+    }
+}
+
+impl Error for TerminalError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Chip8(err) => Some(err),
+            Self::Io(err) => Some(err),
+        }
+    }
+}
+
+impl From<Chip8Error> for TerminalError {
+    fn from(err: Chip8Error) -> Self {
+        Self::Chip8(err)
+    }
+}
+
+impl From<io::Error> for TerminalError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
@@ -0,0 +1,97 @@
+#[cfg(test)]
+mod headless {
+    use crate::{
+        chip8::Chip8,
+        frontend::headless::HeadlessFrontend,
+        movie::Movie,
+    };
+
+    /// 0x200: ld f, v0, 0x202: drw v1, v1, 5, 0x204: jp 0x204
+    const ROM: [u8; 6] = [0xF0, 0x29, 0xD1, 0x15, 0x12, 0x04];
+
+    #[test]
+    fn frame_limit() {
+        let mut chip8 = Chip8::new();
+        chip8.load_rom(&ROM).unwrap();
+        let mut frontend = HeadlessFrontend::new();
+        frontend.set_frames(3);
+
+        let mut frames = vec![];
+        frontend
+            .run(&mut chip8, |frame, _| frames.push(frame))
+            .unwrap();
+        assert_eq!(frames, [1, 2, 3]);
+        assert_eq!(frontend.frame(), 3);
+
+        let rows: Vec<String> = chip8
+            .screen()
+            .to_string()
+            .lines()
+            .map(str::to_string)
+            .collect();
+        assert_eq!(rows.len(), 32);
+        assert!(rows.iter().all(|row| row.len() == 64));
+        assert_eq!(&rows[0][..4], "1111");
+        assert_eq!(&rows[1][..4], "1001");
+    }
+
+    #[test]
+    fn movie_end() {
+        let mut chip8 = Chip8::new();
+        chip8.load_rom(&ROM).unwrap();
+        let mut movie = Movie::record(String::new(), &chip8);
+        movie.frames = vec![0; 2];
+        let mut frontend = HeadlessFrontend::new();
+        frontend.set_playback(movie);
+        frontend.set_recording(Movie::record(String::new(), &chip8));
+
+        frontend.run(&mut chip8, |_, _| {}).unwrap();
+        assert_eq!(frontend.frame(), 2);
+        assert_eq!(frontend.take_recording().unwrap().frames, [0, 0]);
+    }
+}
+
+#[cfg(all(test, feature = "terminal"))]
+mod terminal {
+    use crate::{
+        chip8::Screen,
+        frontend::terminal::{
+            char_to_hex,
+            render,
+        },
+        palette::Palette,
+    };
+
+    #[test]
+    fn keys() {
+        let keys: Vec<Option<u8>> = "1234qwerASDFzxcv".chars().map(char_to_hex).collect();
+        let hex: Vec<Option<u8>> = [1, 2, 3, 0xC, 4, 5, 6, 0xD, 7, 8, 9, 0xE, 0xA, 0, 0xB, 0xF]
+            .into_iter()
+            .map(Some)
+            .collect();
+        assert_eq!(keys, hex);
+        assert_eq!(char_to_hex('5'), None);
+    }
+
+    #[test]
+    fn half_blocks() {
+        let mut screen = Screen::new();
+        screen.set_xy(0, 0, 1);
+        screen.set_xy(1, 1, 1);
+        let palette: Palette = "#000000,#FFFFFF".parse().unwrap();
+
+        let text = render(&screen, &palette);
+        let lines: Vec<&str> = text.split("\r\n").collect();
+        // 16 text lines for 32 pixel rows, then clearing the rest
+        assert_eq!(lines.len(), 17);
+        assert!(lines[0].starts_with(
+            "\x1b[H\x1b[38;2;255;255;255m\x1b[48;2;0;0;0m\u{2580}\
+             \x1b[38;2;0;0;0m\x1b[48;2;255;255;255m\u{2580}\
+             \x1b[38;2;0;0;0m\x1b[48;2;0;0;0m\u{2580}\u{2580}"
+        ));
+        // Colors are only written where they change
+        assert_eq!(lines[1].matches('\u{2580}').count(), 64);
+        assert_eq!(lines[1].matches("\x1b[38;").count(), 1);
+        assert_eq!(lines[16], "\x1b[J");
+    }
+}
@@ -0,0 +1,32 @@
+//! Chip8 interpreter.
+//!
+//! The [`Chip8`] core has no window, audio or real-time dependencies, so it
+//! can be embedded in tools and tests. The SDL frontend lives behind the `sdl`
+//! feature.
+//!
+//! ```
+//! use chip_8::Chip8;
+//!
+//! let mut chip8 = Chip8::new();
+//! // LD V0, 0x2A
+//! chip8.load_rom(&[0x60, 0x2A])?;
+//! chip8.step()?;
+//!
+//! assert_eq!(chip8.v()[0], 0x2A);
+//! assert_eq!(chip8.pc(), 0x202);
+//! # Ok::<(), chip_8::chip8::Chip8Error>(())
+//! ```
+
+pub mod asm;
+pub mod chip8;
+pub mod database;
+pub mod debugger;
+pub mod disasm;
+pub mod frontend;
+pub mod movie;
+pub mod palette;
+pub mod rewind;
+pub mod save_state;
+pub mod trace;
+
+pub use chip8::Chip8;
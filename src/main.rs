@@ -1,29 +1,870 @@
 use std::{
-    fs::File,
-    path::Path,
+    env,
+    fmt,
+    fs,
+    io::{
+        self,
+        BufRead,
+        Write,
+    },
+    ops::RangeInclusive,
+    path::{
+        Path,
+        PathBuf,
+    },
+    process::exit,
 };
 
-use chip8::Chip8;
-mod chip8;
+#[cfg(feature = "terminal")]
+use chip_8::frontend::terminal::TerminalError;
 
-use clap::Parser;
+use {
+    chip_8::{
+        asm::{
+            assemble,
+            AsmError,
+        },
+        chip8::{
+            Chip8Error,
+            Platform,
+            Screen,
+            ScreenFormat,
+            UnknownOpcodePolicy,
+            FRAMES_PER_SECOND,
+        },
+        database::{
+            sha1_hex,
+            DatabaseError,
+            RomDatabase,
+            RomInfo,
+        },
+        debugger::{
+            DapServer,
+            Debugger,
+            Repl,
+        },
+        disasm::{
+            Disassembly,
+            Syntax,
+        },
+        frontend::headless::HeadlessFrontend,
+        movie::{
+            Movie,
+            MovieError,
+        },
+        palette::Palette,
+        rewind::Rewind,
+        save_state::SaveSlots,
+        trace::{
+            parse_address_range,
+            parse_frame_range,
+            DiffOutcome,
+            OpcodeClass,
+            TraceDiff,
+            TraceFilter,
+            TraceFormat,
+            Tracer,
+        },
+        Chip8,
+    },
+    clap::{
+        Args,
+        Parser,
+        Subcommand,
+    },
+};
+
+/// Default of `--rewind`
+const REWIND_SECONDS: usize = 10;
+/// Default of `--scale`
+const PNG_SCALE: usize = 10;
 
+/// CHIP-8, SUPER-CHIP and XO-CHIP interpreter.
+///
+/// `chip-8 <ROM>` is short for `chip-8 run <ROM>`.
 #[derive(Parser, Debug)]
-struct Args {
-    program: String,
+#[clap(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+struct Cli {
+    #[clap(subcommand)]
+    command: Option<Command>,
+    #[clap(flatten)]
+    run: RunArgs,
 }
 
-fn main() {
-    env_logger::init();
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Play a ROM
+    Run(RunArgs),
+    /// Step through a ROM in an interactive debugger
+    Debug(MachineArgs),
+    /// Serve the Debug Adapter Protocol over stdio for editors, the ROM and
+    /// platform come from the launch request
+    Dap,
+    /// Print a disassembly of a ROM
+    Disasm(DisasmArgs),
+    /// Assemble Octo source into a ROM
+    Asm(AsmArgs),
+    /// Run a ROM under two configurations, or against a trace file, and
+    /// show the first instruction where they differ
+    TraceDiff(TraceDiffArgs),
+}
 
-    let mut chip8 = Chip8::new();
+/// Program and the machine running it
+#[derive(Args, Clone, Debug, Default)]
+struct MachineArgs {
+    /// ROM file
+    // Optional for clap, which doesn't require it when a subcommand is given
+    #[clap(required = true)]
+    program: Option<PathBuf>,
+    /// Platform preset: cosmac-vip, chip-48, schip-1.0, schip-1.1,
+    /// schip-modern or xo-chip. --ipf, --hz, --memory-size and --quirk
+    /// override its settings
+    #[clap(long)]
+    platform: Option<Platform>,
+    /// Instructions executed per 60 Hz frame, 10 without a platform
+    #[clap(long)]
+    ipf: Option<usize>,
+    /// CPU speed in instructions per second, overrides --ipf
+    #[clap(long, conflicts_with = "ipf")]
+    hz: Option<usize>,
+    /// What to do on unknown opcodes: ignore, warn or halt
+    #[clap(long, default_value_t = UnknownOpcodePolicy::default())]
+    unknown_opcodes: UnknownOpcodePolicy,
+    /// Enable or disable a quirk, e.g. `--quirk shift-uses-vy` or `--quirk
+    /// clip-sprites=false`. Quirks: shift-uses-vy, load-store-increments-i,
+    /// load-store-increments-i-by-x, jump-uses-vx, logic-resets-vf,
+    /// clip-sprites, display-wait, add-i-overflow-sets-vf
+    #[clap(long = "quirk", value_name = "NAME[=BOOL]")]
+    quirks: Vec<String>,
+    /// Memory size in bytes, XO-CHIP programs use up to 65536, 4096 without
+    /// a platform
+    #[clap(long)]
+    memory_size: Option<usize>,
+    /// Local ROM database in chip-8-database `programs.json` format, its
+    /// entries replace bundled ones [default: ~/.config/chip-8/programs.json]
+    #[clap(long, value_name = "FILE")]
+    database: Option<PathBuf>,
+    /// Seed for the random number generator, random by default
+    #[clap(long)]
+    seed: Option<u64>,
+}
+
+#[derive(Args, Debug)]
+struct RunArgs {
+    #[clap(flatten)]
+    machine: MachineArgs,
+    /// Seconds of history kept for rewinding with Backspace, 0 disables it
+    #[clap(long, value_name = "SECONDS", default_value_t = REWIND_SECONDS)]
+    rewind: usize,
+    /// Record the keypad of every frame into a movie file
+    #[clap(long, value_name = "FILE", conflicts_with = "play")]
+    record: Option<PathBuf>,
+    /// Play a recorded movie or input script, its settings and seed replace
+    /// the ones above
+    #[clap(long, value_name = "FILE")]
+    play: Option<PathBuf>,
+    /// Write every executed instruction and the registers after it to a file
+    #[clap(long, value_name = "FILE")]
+    trace: Option<PathBuf>,
+    /// Trace file format: text or jsonl
+    #[clap(long, default_value_t = TraceFormat::default(), requires = "trace")]
+    trace_format: TraceFormat,
+    /// Only trace instructions at hex addresses START-END
+    #[clap(long, value_name = "START-END", value_parser = parse_address_range, requires = "trace")]
+    trace_addresses: Option<RangeInclusive<usize>>,
+    /// Only trace these opcode classes: jump, call, skip, load, alu, memory,
+    /// display, timer, key, audio or system
+    #[clap(
+        long,
+        value_name = "CLASS",
+        use_value_delimiter = true,
+        requires = "trace"
+    )]
+    trace_opcodes: Vec<OpcodeClass>,
+    /// Only trace frames START-END, counted from 0
+    #[clap(long, value_name = "START-END", value_parser = parse_frame_range, requires = "trace")]
+    trace_frames: Option<RangeInclusive<u64>>,
+    /// Screen colors as comma separated #RRGGBB: background, plane 1, plane 2
+    /// and both planes [default: the database's]
+    #[clap(long, value_name = "COLORS")]
+    palette: Option<Palette>,
+    /// Run as fast as possible without a window, audio or keyboard and print
+    /// the screen at exit
+    #[clap(long)]
+    headless: bool,
+    /// Draw in the terminal with Unicode blocks and read keys from it, Escape
+    /// quits
+    #[clap(long, conflicts_with = "headless")]
+    terminal: bool,
+    /// Stop after this many frames
+    #[clap(long, value_name = "N", requires = "headless")]
+    frames: Option<usize>,
+    /// Write the screen at exit to a file instead of printing it
+    #[clap(long, value_name = "FILE", requires = "headless")]
+    dump: Option<PathBuf>,
+    /// Also write the screen every K frames, to the --dump file with the
+    /// frame number before its extension
+    #[clap(long, value_name = "K", requires = "dump")]
+    dump_every: Option<usize>,
+    /// Screen dump format: text with a color index digit per pixel, ascii,
+    /// pbm or png [default: from the --dump extension, text]
+    #[clap(long, value_name = "FORMAT", requires = "headless")]
+    dump_format: Option<ScreenFormat>,
+    /// Image pixels per screen pixel of PNG dumps
+    #[clap(long, default_value_t = PNG_SCALE, requires = "headless")]
+    scale: usize,
+}
+
+/// Settings of a plain `chip-8 ROM`, without a ROM
+impl Default for RunArgs {
+    fn default() -> Self {
+        Self {
+            machine: MachineArgs::default(),
+            rewind: REWIND_SECONDS,
+            record: None,
+            play: None,
+            trace: None,
+            trace_format: TraceFormat::default(),
+            trace_addresses: None,
+            trace_opcodes: vec![],
+            trace_frames: None,
+            palette: None,
+            headless: false,
+            terminal: false,
+            frames: None,
+            dump: None,
+            dump_every: None,
+            dump_format: None,
+            scale: PNG_SCALE,
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+struct DisasmArgs {
+    /// ROM file
+    program: PathBuf,
+    /// Output syntax: cowgod or octo
+    #[clap(long, default_value_t = Syntax::default())]
+    syntax: Syntax,
+}
+
+#[derive(Args, Debug)]
+struct AsmArgs {
+    /// Octo source file
+    source: PathBuf,
+    /// ROM file to write [default: the source with a .ch8 extension]
+    #[clap(short, long, value_name = "FILE")]
+    output: Option<PathBuf>,
+    /// Play the ROM once it's assembled
+    #[clap(long)]
+    run: bool,
+    /// Platform preset for --run
+    #[clap(long, requires = "run")]
+    platform: Option<Platform>,
+}
+
+#[derive(Args, Debug)]
+struct TraceDiffArgs {
+    #[clap(flatten)]
+    machine: MachineArgs,
+    /// Platform preset of the second run [default: the first run's]
+    #[clap(long, value_name = "PLATFORM")]
+    against_platform: Option<Platform>,
+    /// Quirk of the second run on top of the first run's, like --quirk
+    #[clap(long = "against-quirk", value_name = "NAME[=BOOL]")]
+    against_quirks: Vec<String>,
+    /// Instructions per frame of the second run [default: the first run's]
+    #[clap(long, value_name = "IPF")]
+    against_ipf: Option<usize>,
+    /// Compare with a trace file in text or jsonl format, as written by
+    /// --trace, instead of a second run
+    #[clap(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = &["against-platform", "against-quirks", "against-ipf"]
+    )]
+    against_trace: Option<PathBuf>,
+    /// Keypad input of a movie or input script, its seed is used without
+    /// --seed and its settings are ignored
+    #[clap(long, value_name = "FILE")]
+    play: Option<PathBuf>,
+    /// Instructions compared before giving up
+    #[clap(long, default_value_t = 10_000_000)]
+    cycles: u64,
+    /// Instructions shown before the first difference
+    #[clap(long, default_value_t = 10)]
+    context: usize,
+}
+
+impl MachineArgs {
+    fn program(&self) -> &Path {
+        self.program.as_deref().expect("clap requires a program")
+    }
+}
+
+/// Frontend settings that don't affect the machine
+#[cfg_attr(not(feature = "sdl"), allow(dead_code))]
+struct Frontend {
+    palette: Palette,
+    save_slots: SaveSlots,
+    rewind: Rewind,
+    playback: Option<Movie>,
+    headless: bool,
+    terminal: bool,
+    frames: Option<usize>,
+    dump: Option<PathBuf>,
+    dump_every: Option<usize>,
+    dump_format: ScreenFormat,
+    scale: usize,
+}
+
+/// Everything a command can fail with, reported once by `main`
+#[derive(Debug)]
+enum CliError {
+    Chip8(Chip8Error),
+    /// `--quirk` names no quirk or has a bad value
+    Quirk(String),
+    Database(PathBuf, DatabaseError),
+    Movie(PathBuf, MovieError),
+    Asm(PathBuf, AsmError),
+    /// Creating or reading a file other than the ROM failed
+    File(PathBuf, io::Error),
+    /// Line of a `--against-trace` file that isn't a trace line
+    TraceLine(PathBuf, usize, String),
+    #[cfg(feature = "terminal")]
+    Terminal(TerminalError),
+    /// Option of a feature this binary was built without
+    #[cfg_attr(feature = "terminal", allow(dead_code))]
+    MissingFeature {
+        option: &'static str,
+        feature: &'static str,
+    },
+}
+
+impl CliError {
+    /// Usage errors exit with 2, failures of the program with 1
+    fn exit_code(&self) -> i32 {
+        match self {
+            Self::Chip8(_) | Self::Asm(..) => 1,
+            Self::Quirk(_)
+            | Self::Database(..)
+            | Self::Movie(..)
+            | Self::File(..)
+            | Self::TraceLine(..)
+            | Self::MissingFeature { .. } => 2,
+            #[cfg(feature = "terminal")]
+            Self::Terminal(_) => 1,
+        }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Chip8(err) => write!(f, "{err}"),
+            Self::Quirk(err) => write!(f, "{err}"),
+            Self::Database(path, err) => write!(f, "{}: {err}", path.display()),
+            Self::Movie(path, err) => write!(f, "{}: {err}", path.display()),
+            Self::Asm(path, err) => write!(f, "{}:{err}", path.display()),
+            Self::File(path, err) => write!(f, "{}: {err}", path.display()),
+            Self::TraceLine(path, number, err) => write!(f, "{}:{number}: {err}", path.display()),
+            #[cfg(feature = "terminal")]
+            Self::Terminal(err) => write!(f, "{err}"),
+            Self::MissingFeature { option, feature } => {
+                write!(f, "{option} needs the {feature} feature")
+            }
+        }
+    }
+}
+
+impl From<Chip8Error> for CliError {
+    fn from(err: Chip8Error) -> Self {
+        Self::Chip8(err)
+    }
+}
+
+#[cfg(feature = "terminal")]
+impl From<TerminalError> for CliError {
+    fn from(err: TerminalError) -> Self {
+        Self::Terminal(err)
+    }
+}
+
+impl From<io::Error> for CliError {
+    fn from(err: io::Error) -> Self {
+        Self::Chip8(err.into())
+    }
+}
+
+fn main() {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
 
     // let path_to_roms = Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/ROMs"));
     // let path_to_program = path_to_roms.join("TETRIS");
-    let args = Args::parse();
-    let path_to_program = Path::new(&args.program);
-    let mut file = File::open(path_to_program).unwrap();
-    chip8.load_from_file(&mut file);
+    let cli = Cli::parse();
+
+    let result = match &cli.command {
+        None => run(&cli.run),
+        Some(Command::Run(args)) => run(args),
+        Some(Command::Debug(args)) => debug(args),
+        Some(Command::Dap) => DapServer::new(io::stdout())
+            .run(io::BufReader::new(io::stdin()))
+            .map_err(CliError::from),
+        Some(Command::Disasm(args)) => disasm(args),
+        Some(Command::Asm(args)) => asm(args),
+        Some(Command::TraceDiff(args)) => trace_diff(args),
+    };
+    if let Err(err) = result {
+        eprintln!("Error: {err}");
+        exit(err.exit_code());
+    }
+}
+
+fn run(args: &RunArgs) -> Result<(), CliError> {
+    let (rom, mut chip8, info) = setup(&args.machine)?;
+    if let Some(info) = &info {
+        print_rom_info(info);
+    }
+
+    let rom_sha1 = sha1_hex(&rom);
+    let playback = args
+        .play
+        .as_ref()
+        .map(|path| {
+            let movie = Movie::read(path).and_then(|movie| {
+                movie.setup(&mut chip8, &rom_sha1)?;
+                Ok(movie)
+            });
+            movie.map_err(|err| CliError::Movie(path.clone(), err))
+        })
+        .transpose()?;
+    let mut recording = args
+        .record
+        .as_ref()
+        .map(|_| Movie::record(rom_sha1.clone(), &chip8));
+    let mut tracer = match &args.trace {
+        Some(path) => {
+            let file = fs::File::create(path).map_err(|err| CliError::File(path.clone(), err))?;
+            let filter = TraceFilter {
+                addresses: args.trace_addresses.clone(),
+                classes: args.trace_opcodes.clone(),
+                frames: args.trace_frames.clone(),
+            };
+            Some(Tracer::new(
+                io::BufWriter::new(file),
+                args.trace_format,
+                filter,
+            ))
+        }
+        None => None,
+    };
+
+    chip8.load_rom(&rom)?;
+
+    let palette = args
+        .palette
+        .or(info.and_then(|info| info.palette))
+        .unwrap_or_default();
+    let dump_format = args
+        .dump_format
+        .or(args.dump.as_deref().and_then(ScreenFormat::from_path))
+        .unwrap_or_default();
+    let save_slots = SaveSlots::new(args.machine.program(), rom_sha1);
+    let frontend = Frontend {
+        palette,
+        save_slots,
+        rewind: Rewind::new(args.rewind),
+        playback,
+        headless: args.headless,
+        terminal: args.terminal,
+        frames: args.frames,
+        dump: args.dump.clone(),
+        dump_every: args.dump_every,
+        dump_format,
+        scale: args.scale,
+    };
+    let mut result = play(&mut chip8, frontend, &mut recording, &mut tracer);
+    print_unknown_opcodes(&chip8);
+
+    // An error of the run itself is the one reported
+    if let (Some(path), Some(tracer)) = (&args.trace, tracer) {
+        if let Err(err) = tracer.finish() {
+            result = result.and(Err(CliError::File(path.clone(), err)));
+        }
+    }
+
+    // Written on errors too, a recording of a crash is a bug report
+    if let (Some(path), Some(recording)) = (&args.record, &recording) {
+        if let Err(err) = recording.write(path) {
+            result = result.and(Err(CliError::Movie(path.clone(), err)));
+        }
+    }
+
+    result
+}
+
+fn debug(args: &MachineArgs) -> Result<(), CliError> {
+    let (rom, mut chip8, info) = setup(args)?;
+    if let Some(info) = &info {
+        print_rom_info(info);
+    }
+    chip8.load_rom(&rom)?;
+
+    let mut repl = Repl::new(Debugger::new(chip8));
+    repl.run(io::stdin().lock(), io::stdout())?;
+
+    Ok(())
+}
+
+fn disasm(args: &DisasmArgs) -> Result<(), CliError> {
+    let rom = fs::read(&args.program)?;
+    print!("{}", Disassembly::new(&rom).render(args.syntax));
+
+    Ok(())
+}
+
+fn asm(args: &AsmArgs) -> Result<(), CliError> {
+    let source = fs::read_to_string(&args.source)?;
+    let rom = assemble(&source).map_err(|err| CliError::Asm(args.source.clone(), err))?;
+
+    let output = args
+        .output
+        .clone()
+        .unwrap_or_else(|| args.source.with_extension("ch8"));
+    fs::write(&output, rom)?;
+    if !args.run {
+        return Ok(());
+    }
+
+    let mut run_args = RunArgs::default();
+    run_args.machine.program = Some(output);
+    run_args.machine.platform = args.platform;
+    run(&run_args)
+}
+
+fn trace_diff(args: &TraceDiffArgs) -> Result<(), CliError> {
+    let input = args
+        .play
+        .as_ref()
+        .map(|path| Movie::read(path).map_err(|err| CliError::Movie(path.clone(), err)))
+        .transpose()?;
+
+    // Both runs and the trace need the same random numbers
+    let mut machine = args.machine.clone();
+    machine.seed = machine.seed.or(input.as_ref().and_then(|movie| movie.seed));
+    let (rom, mut left, info) = setup(&machine)?;
+    if let Some(info) = &info {
+        print_rom_info(info);
+    }
+    left.load_rom(&rom)?;
+
+    let mut diff = TraceDiff::new(args.cycles);
+    diff.set_context(args.context);
+    if let Some(input) = input {
+        diff.set_input(input);
+    }
+
+    let outcome = match &args.against_trace {
+        Some(path) => {
+            let file = fs::File::open(path).map_err(|err| CliError::File(path.clone(), err))?;
+            // The comparison ends at the first unreadable line, reported after it
+            let mut error = None;
+            let trace = io::BufReader::new(file)
+                .lines()
+                .enumerate()
+                .map_while(|(number, line)| {
+                    let line = line
+                        .map_err(|err| CliError::File(path.clone(), err))
+                        .and_then(|line| {
+                            line.parse()
+                                .map_err(|err| CliError::TraceLine(path.clone(), number + 1, err))
+                        });
+                    line.map_err(|err| error = Some(err)).ok()
+                });
+            let outcome = diff.against(&mut left, trace);
+            if let Some(err) = error {
+                return Err(err);
+            }
+            outcome
+        }
+        None => {
+            machine.seed = Some(left.seed());
+            machine.platform = args.against_platform.or(machine.platform);
+            machine.quirks.extend(args.against_quirks.iter().cloned());
+            if args.against_ipf.is_some() {
+                machine.ipf = args.against_ipf;
+                machine.hz = None;
+            }
+            let (_, mut right, _) = setup(&machine)?;
+            right.load_rom(&rom)?;
+            diff.lockstep(&mut left, &mut right)
+        }
+    };
+
+    match outcome {
+        DiffOutcome::Same { cycles } => {
+            println!("No difference in {cycles} instructions");
+            Ok(())
+        }
+        DiffOutcome::Diverged(divergence) => {
+            print!("{divergence}");
+            exit(1);
+        }
+    }
+}
+
+/// Read the ROM and build the machine for it from the database and the
+/// command line, the ROM isn't loaded yet
+fn setup(args: &MachineArgs) -> Result<(Vec<u8>, Chip8, Option<RomInfo>), CliError> {
+    let rom = fs::read(args.program())?;
+    let database = load_database(args)?;
+    let info = database.identify(&rom).cloned();
+
+    // A platform on the command line replaces the database settings
+    let mut chip8 = match (args.platform, &info) {
+        (Some(platform), _) => Chip8::with_platform(platform),
+        (None, Some(info)) => chip8_from_database(info),
+        (None, None) => Chip8::new(),
+    };
+    if let Some(memory_size) = args.memory_size {
+        chip8.set_memory_size(memory_size);
+    }
+
+    let instructions_per_frame = match (args.hz, args.ipf) {
+        (Some(hz), _) => Some(hz / FRAMES_PER_SECOND as usize),
+        (None, ipf) => ipf,
+    };
+    if let Some(instructions_per_frame) = instructions_per_frame {
+        chip8.set_instructions_per_frame(instructions_per_frame);
+    }
+    chip8.set_unknown_opcode_policy(args.unknown_opcodes);
+    if let Some(seed) = args.seed {
+        chip8.set_seed(seed);
+    }
+    log::info!("Seed: {}", chip8.seed());
+
+    let mut quirks = chip8.quirks();
+    for setting in &args.quirks {
+        quirks.apply(setting).map_err(CliError::Quirk)?;
+    }
+    chip8.set_quirks(quirks);
+
+    Ok((rom, chip8, info))
+}
+
+/// Bundled database with the entries of the local one on top
+fn load_database(args: &MachineArgs) -> Result<RomDatabase, CliError> {
+    let mut database = RomDatabase::bundled();
+
+    let path = args.database.clone().or_else(|| {
+        let config = env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(config.join("chip-8").join("programs.json")).filter(|path| path.exists())
+    });
+    if let Some(path) = path {
+        match RomDatabase::from_file(&path) {
+            Ok(local) => database.extend(local),
+            Err(err) => return Err(CliError::Database(path, err)),
+        }
+    }
+
+    Ok(database)
+}
+
+fn chip8_from_database(info: &RomInfo) -> Chip8 {
+    let mut chip8 = match info.platform {
+        Some(platform) => Chip8::with_platform(platform),
+        None => Chip8::new(),
+    };
+    if let Some(quirks) = info.quirks {
+        chip8.set_quirks(quirks);
+    }
+    if let Some(instructions_per_frame) = info.instructions_per_frame {
+        chip8.set_instructions_per_frame(instructions_per_frame);
+    }
+
+    chip8
+}
+
+fn print_rom_info(info: &RomInfo) {
+    match info.platform {
+        Some(platform) => eprintln!("{} ({platform})", info.title),
+        None => eprintln!("{}", info.title),
+    }
+
+    if !info.keys.is_empty() {
+        let keys: Vec<String> = info
+            .keys
+            .iter()
+            .map(|(role, key)| format!("{role}: {key:X}"))
+            .collect();
+        eprintln!("Keys: {}", keys.join(", "));
+    }
+}
+
+fn print_unknown_opcodes(chip8: &Chip8) {
+    if chip8.unknown_opcodes().is_empty() {
+        return;
+    }
+
+    eprintln!("Unknown opcodes:");
+    for (opcode, count) in chip8.unknown_opcodes() {
+        eprintln!("    {opcode:04X}: {count}");
+    }
+}
+
+#[cfg(feature = "sdl")]
+fn play(
+    chip8: &mut Chip8,
+    settings: Frontend,
+    recording: &mut Option<Movie>,
+    tracer: &mut Option<Tracer>,
+) -> Result<(), CliError> {
+    if settings.headless {
+        return play_headless(chip8, settings, recording, tracer);
+    }
+    if settings.terminal {
+        return play_terminal(chip8, settings, recording, tracer);
+    }
+
+    let mut frontend = chip_8::frontend::sdl::SdlFrontend::new();
+    frontend.set_palette(settings.palette);
+    frontend.set_save_slots(settings.save_slots);
+    frontend.set_rewind(settings.rewind);
+    if let Some(movie) = settings.playback {
+        frontend.set_playback(movie);
+    }
+    if let Some(movie) = recording.take() {
+        frontend.set_recording(movie);
+    }
+    if let Some(tracer) = tracer.take() {
+        frontend.set_tracer(tracer);
+    }
+
+    let result = frontend.run(chip8);
+    *recording = frontend.take_recording();
+    *tracer = frontend.take_tracer();
+    Ok(result?)
+}
+
+#[cfg(not(feature = "sdl"))]
+fn play(
+    chip8: &mut Chip8,
+    settings: Frontend,
+    recording: &mut Option<Movie>,
+    tracer: &mut Option<Tracer>,
+) -> Result<(), CliError> {
+    if settings.terminal {
+        return play_terminal(chip8, settings, recording, tracer);
+    }
+
+    play_headless(chip8, settings, recording, tracer)
+}
+
+#[cfg(feature = "terminal")]
+fn play_terminal(
+    chip8: &mut Chip8,
+    settings: Frontend,
+    recording: &mut Option<Movie>,
+    tracer: &mut Option<Tracer>,
+) -> Result<(), CliError> {
+    let mut frontend = chip_8::frontend::terminal::TerminalFrontend::new();
+    frontend.set_palette(settings.palette);
+    if let Some(movie) = settings.playback {
+        frontend.set_playback(movie);
+    }
+    if let Some(movie) = recording.take() {
+        frontend.set_recording(movie);
+    }
+    if let Some(tracer) = tracer.take() {
+        frontend.set_tracer(tracer);
+    }
+
+    let result = frontend.run(chip8);
+    *recording = frontend.take_recording();
+    *tracer = frontend.take_tracer();
+    Ok(result?)
+}
+
+#[cfg(not(feature = "terminal"))]
+fn play_terminal(
+    _: &mut Chip8,
+    _: Frontend,
+    _: &mut Option<Movie>,
+    _: &mut Option<Tracer>,
+) -> Result<(), CliError> {
+    Err(CliError::MissingFeature {
+        option: "--terminal",
+        feature: "terminal",
+    })
+}
+
+fn play_headless(
+    chip8: &mut Chip8,
+    mut settings: Frontend,
+    recording: &mut Option<Movie>,
+    tracer: &mut Option<Tracer>,
+) -> Result<(), CliError> {
+    let mut frontend = HeadlessFrontend::new();
+    if let Some(frames) = settings.frames {
+        frontend.set_frames(frames);
+    }
+    if let Some(movie) = settings.playback.take() {
+        frontend.set_playback(movie);
+    }
+    if let Some(movie) = recording.take() {
+        frontend.set_recording(movie);
+    }
+    if let Some(tracer) = tracer.take() {
+        frontend.set_tracer(tracer);
+    }
+
+    // The run goes on after a failed dump, later dumps are skipped and the
+    // error is reported after it
+    let mut dump_error = None;
+    let result = frontend.run(chip8, |frame, chip8| {
+        if let (Some(every), Some(path)) = (settings.dump_every, &settings.dump) {
+            if frame % every == 0 && dump_error.is_none() {
+                let path = numbered_path(path, frame);
+                dump_error = dump_screen(chip8.screen(), Some(&path), &settings).err();
+            }
+        }
+    });
+    *recording = frontend.take_recording();
+    *tracer = frontend.take_tracer();
+
+    // Dumped on errors too, the screen often shows what went wrong
+    let mut dumped = Ok(());
+    if settings.headless {
+        dumped = dump_screen(chip8.screen(), settings.dump.as_deref(), &settings);
+    }
+    result?;
+    if let Some(err) = dump_error {
+        return Err(err);
+    }
+    dumped
+}
+
+/// Write `screen` to `path`, or print it without one
+fn dump_screen(screen: &Screen, path: Option<&Path>, settings: &Frontend) -> Result<(), CliError> {
+    let image = screen.export(settings.dump_format, &settings.palette, settings.scale);
+    let result = match path {
+        Some(path) => fs::write(path, image),
+        None => io::stdout().write_all(&image),
+    };
+
+    result.map_err(|err| CliError::File(path.unwrap_or(Path::new("stdout")).to_path_buf(), err))
+}
 
-    chip8.run();
+/// `path` with `frame` before its extension, `screen.txt` becomes
+/// `screen.000060.txt`
+fn numbered_path(path: &Path, frame: usize) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(extension) => format!("{stem}.{frame:06}.{}", extension.to_string_lossy()),
+        None => format!("{stem}.{frame:06}"),
+    };
+    path.with_file_name(name)
 }
@@ -0,0 +1,376 @@
+use std::{
+    error::Error,
+    fmt::{
+        self,
+        Write as _,
+    },
+    fs,
+    io,
+    path::Path,
+};
+
+use crate::chip8::{
+    Chip8,
+    Chip8Error,
+    Font,
+    InstructionSet,
+    Quirks,
+};
+
+mod tests;
+
+/// First line of a recorded movie
+const HEADER: &str = "chip-8 movie 1";
+
+/// Keypad state of every frame together with everything else a run depends
+/// on, so playing it back reproduces the run exactly.
+///
+/// Movies are text. A recording has settings lines followed by `frames` and
+/// one hex key mask per frame, bit n set while key n is held:
+///
+/// ```text
+/// chip-8 movie 1
+/// rom 2f3c...
+/// seed 42
+/// ipf 10
+/// frames
+/// 0000
+/// 0020
+/// ```
+///
+/// Hand written input scripts list key changes instead of masks, keys stay
+/// held until released:
+///
+/// ```text
+/// frame 120: press 5
+/// frame 130: release 5
+/// frame 200: press 4 6
+/// frame 260: release all
+/// ```
+///
+/// Every line is optional, settings that are missing are left as they are.
+/// Unknown settings are ignored, `#` starts a comment.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Movie {
+    /// SHA-1 of the ROM the movie was recorded with
+    pub rom_sha1: Option<String>,
+    pub seed: Option<u64>,
+    pub settings: MovieSettings,
+    /// Key mask of each frame
+    pub frames: Vec<u16>,
+}
+
+/// Machine settings that change how a program runs
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MovieSettings {
+    pub instructions_per_frame: Option<usize>,
+    pub memory_size: Option<usize>,
+    pub instruction_set: Option<InstructionSet>,
+    pub font: Option<Font>,
+    pub quirks: Option<Quirks>,
+}
+
+/// Reading, parsing or playing a movie failed
+#[derive(Debug)]
+pub enum MovieError {
+    Io(io::Error),
+    Parse {
+        line: usize,
+        message: String,
+    },
+    /// Movie was recorded with another ROM
+    RomMismatch {
+        expected: String,
+        found: String,
+    },
+}
+
+impl Movie {
+    /// Empty recording of `chip8` running the ROM with SHA-1 `rom_sha1`
+    pub fn record(rom_sha1: String, chip8: &Chip8) -> Self {
+        Self {
+            rom_sha1: Some(rom_sha1),
+            seed: Some(chip8.seed()),
+            settings: MovieSettings::of(chip8),
+            frames: vec![],
+        }
+    }
+
+    /// Append the keypad state of the next frame
+    pub fn push(&mut self, chip8: &Chip8) {
+        self.frames.push(chip8.keypad().bits());
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Check the ROM and put the movie's settings and seed into `chip8`,
+    /// before the ROM is loaded
+    pub fn setup(&self, chip8: &mut Chip8, rom_sha1: &str) -> Result<(), MovieError> {
+        if let Some(expected) = &self.rom_sha1 {
+            if expected != rom_sha1 {
+                return Err(MovieError::RomMismatch {
+                    expected: expected.clone(),
+                    found: rom_sha1.to_string(),
+                });
+            }
+        }
+
+        self.settings.apply(chip8);
+        // Scripts without a seed still have to be reproducible
+        chip8.set_seed(self.seed.unwrap_or(0));
+
+        Ok(())
+    }
+
+    /// Set the keypad for `frame`, `false` after the last frame
+    pub fn feed(&self, frame: usize, chip8: &mut Chip8) -> bool {
+        match self.frames.get(frame) {
+            Some(&bits) => {
+                chip8.keypad_mut().set_bits(bits);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Run every frame of the movie without a frontend
+    pub fn play(&self, chip8: &mut Chip8) -> Result<(), Chip8Error> {
+        let mut frame = 0;
+        while self.feed(frame, chip8) && !chip8.is_halted() {
+            chip8.run_frame()?;
+            frame += 1;
+        }
+
+        Ok(())
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{HEADER}\n");
+        if let Some(rom_sha1) = &self.rom_sha1 {
+            writeln!(text, "rom {rom_sha1}").unwrap();
+        }
+        if let Some(seed) = self.seed {
+            writeln!(text, "seed {seed}").unwrap();
+        }
+        text += &self.settings.to_text();
+
+        text += "frames\n";
+        for bits in &self.frames {
+            writeln!(text, "{bits:04X}").unwrap();
+        }
+
+        text
+    }
+
+    pub fn parse(text: &str) -> Result<Self, MovieError> {
+        let mut movie = Self::default();
+        let mut in_frames = false;
+        // Keys held by the script
+        let mut held = 0u16;
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let error = |message: String| MovieError::Parse {
+                line: index + 1,
+                message,
+            };
+
+            if in_frames {
+                let bits = u16::from_str_radix(line, 16)
+                    .map_err(|_| error(format!("invalid key mask `{line}`")))?;
+                movie.frames.push(bits);
+                continue;
+            }
+
+            if let Some(event) = line.strip_prefix("frame ") {
+                let (frame, action) = event
+                    .split_once(':')
+                    .ok_or_else(|| error("expected `frame N: action`".to_string()))?;
+                let frame: usize = frame
+                    .trim()
+                    .parse()
+                    .map_err(|_| error(format!("invalid frame `{}`", frame.trim())))?;
+                if frame + 1 < movie.frames.len() {
+                    return Err(error(format!("frame {frame} is out of order")));
+                }
+                // Several actions can happen on the same frame
+                movie.frames.truncate(frame);
+                movie.frames.resize(frame, held);
+                held = apply_action(held, action.trim()).map_err(error)?;
+                movie.frames.push(held);
+                continue;
+            }
+
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            let value = value.trim();
+            match key {
+                "chip-8" if value.starts_with("movie ") => {}
+                "frames" => in_frames = true,
+                "rom" => movie.rom_sha1 = Some(value.to_lowercase()),
+                "seed" => movie.seed = Some(parse(value).map_err(error)?),
+                _ => {
+                    if !movie.settings.parse(key, value).map_err(error)? {
+                        log::debug!("Ignoring movie setting `{key}`");
+                    }
+                }
+            }
+        }
+
+        Ok(movie)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), MovieError> {
+        fs::write(path, self.to_text())?;
+
+        Ok(())
+    }
+
+    pub fn read(path: &Path) -> Result<Self, MovieError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+}
+
+impl MovieSettings {
+    /// Current settings of `chip8`
+    pub fn of(chip8: &Chip8) -> Self {
+        Self {
+            instructions_per_frame: Some(chip8.instructions_per_frame()),
+            memory_size: Some(chip8.memory().len()),
+            instruction_set: Some(chip8.instruction_set()),
+            font: Some(chip8.font()),
+            quirks: Some(chip8.quirks()),
+        }
+    }
+
+    pub fn apply(&self, chip8: &mut Chip8) {
+        if let Some(instructions_per_frame) = self.instructions_per_frame {
+            chip8.set_instructions_per_frame(instructions_per_frame);
+        }
+        if let Some(memory_size) = self.memory_size {
+            chip8.set_memory_size(memory_size);
+        }
+        if let Some(instruction_set) = self.instruction_set {
+            chip8.set_instruction_set(instruction_set);
+        }
+        if let Some(font) = self.font {
+            chip8.set_font(font);
+        }
+        if let Some(quirks) = self.quirks {
+            chip8.set_quirks(quirks);
+        }
+    }
+
+    fn to_text(&self) -> String {
+        let mut text = String::new();
+        if let Some(instructions_per_frame) = self.instructions_per_frame {
+            writeln!(text, "ipf {instructions_per_frame}").unwrap();
+        }
+        if let Some(memory_size) = self.memory_size {
+            writeln!(text, "memory {memory_size}").unwrap();
+        }
+        if let Some(instruction_set) = self.instruction_set {
+            writeln!(text, "instructions {instruction_set}").unwrap();
+        }
+        if let Some(font) = self.font {
+            writeln!(text, "font {font}").unwrap();
+        }
+        if let Some(quirks) = self.quirks {
+            let enabled: Vec<&str> = Quirks::NAMES
+                .into_iter()
+                .filter(|name| quirks.get(name) == Some(true))
+                .collect();
+            let line = format!("quirks {}", enabled.join(" "));
+            writeln!(text, "{}", line.trim_end()).unwrap();
+        }
+
+        text
+    }
+
+    /// Parse a setting line, `false` if `key` isn't a setting
+    fn parse(&mut self, key: &str, value: &str) -> Result<bool, String> {
+        match key {
+            "ipf" => self.instructions_per_frame = Some(parse(value)?),
+            "memory" => self.memory_size = Some(parse(value)?),
+            "instructions" => self.instruction_set = Some(value.parse()?),
+            "font" => self.font = Some(value.parse()?),
+            "quirks" => {
+                let mut quirks = Quirks::default();
+                for name in value.split_whitespace() {
+                    quirks.set(name, true)?;
+                }
+                self.quirks = Some(quirks);
+            }
+            _ => return Ok(false),
+        }
+
+        Ok(true)
+    }
+}
+
+fn parse<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid number `{value}`"))
+}
+
+/// Keys held after a `press` or `release` script action
+fn apply_action(held: u16, action: &str) -> Result<u16, String> {
+    let (verb, keys) = action.split_once(' ').unwrap_or((action, ""));
+    if verb == "release" && keys.trim() == "all" {
+        return Ok(0);
+    }
+
+    let mut mask = 0;
+    for key in keys.split([' ', ',']).filter(|key| !key.is_empty()) {
+        let key = u8::from_str_radix(key, 16)
+            .ok()
+            .filter(|&key| key < 16)
+            .ok_or_else(|| format!("invalid key `{key}`, expected 0 to F"))?;
+        mask |= 1 << key;
+    }
+
+    match verb {
+        "press" => Ok(held | mask),
+        "release" => Ok(held & !mask),
+        _ => Err(format!(
+            "unknown action `{verb}`, expected press or release"
+        )),
+    }
+}
+
+impl fmt::Display for MovieError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "Failed to access movie: {err}"),
+            Self::Parse { line, message } => write!(f, "Movie line {line}: {message}"),
+            Self::RomMismatch { expected, found } => write!(
+                f,
+                "Movie was recorded with ROM {expected}, not with {found}"
+            ),
+        }
+    }
+}
+
+impl Error for MovieError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for MovieError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
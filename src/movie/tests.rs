@@ -0,0 +1,107 @@
+#[cfg(test)]
+mod movie {
+    use crate::{
+        chip8::{
+            Chip8,
+            Font,
+            Platform,
+        },
+        movie::{
+            Movie,
+            MovieError,
+        },
+    };
+
+    /// Waits for a key, then draws a random digit at the key's position
+    const ROM: [u8; 12] = [
+        0xF0, 0x0A, 0xC1, 0x0F, 0xF1, 0x29, 0xD0, 0x05, 0x12, 0x00, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn text_round_trip() {
+        let mut chip8 = Chip8::with_platform(Platform::CosmacVip);
+        chip8.set_seed(99);
+        let mut movie = Movie::record("abcd".to_string(), &chip8);
+        chip8.keypad_mut().set_bits(0x8001);
+        movie.push(&chip8);
+        chip8.keypad_mut().set_bits(0);
+        movie.push(&chip8);
+
+        let text = movie.to_text();
+        assert!(text.starts_with("chip-8 movie 1\nrom abcd\nseed 99\nipf 15\n"));
+        assert!(text.ends_with("frames\n8001\n0000\n"));
+        assert_eq!(Movie::parse(&text).unwrap(), movie);
+    }
+
+    #[test]
+    fn script() {
+        let movie = Movie::parse(
+            "# Hand written
+             seed 5
+             font cosmac-vip
+             frame 2: press 5
+             frame 4: press a, 1
+             frame 4: release 5
+             frame 6: release all
+             some-future-setting on",
+        )
+        .unwrap();
+
+        assert_eq!(movie.seed, Some(5));
+        assert_eq!(movie.settings.font, Some(Font::CosmacVip));
+        assert_eq!(movie.frames, [0, 0, 0x0020, 0x0020, 0x0402, 0x0402, 0x0000]);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let cases = [
+            ("frame 2: press 5\nframe 1: press 6", 2),
+            ("frame x: press 5", 1),
+            ("\nframe 1: push 5", 2),
+            ("frame 1: press 10", 1),
+            ("quirks bogus", 1),
+            ("frames\n0001\nxyz", 3),
+        ];
+
+        for (text, expected) in cases {
+            match Movie::parse(text) {
+                Err(MovieError::Parse { line, .. }) => assert_eq!(line, expected, "{text}"),
+                other => panic!("{text}: {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn playback_reproduces_run() {
+        let mut chip8 = Chip8::new();
+        chip8.set_seed(1234);
+        chip8.load_rom(&ROM).unwrap();
+        let mut movie = Movie::record("rom".to_string(), &chip8);
+        for frame in 0..120u16 {
+            chip8
+                .keypad_mut()
+                .set_bits(if frame % 7 == 0 { 1 << (frame % 16) } else { 0 });
+            movie.push(&chip8);
+            chip8.run_frame().unwrap();
+        }
+
+        let movie = Movie::parse(&movie.to_text()).unwrap();
+        let mut replay = Chip8::new();
+        movie.setup(&mut replay, "rom").unwrap();
+        replay.load_rom(&ROM).unwrap();
+        movie.play(&mut replay).unwrap();
+
+        assert_eq!(replay.snapshot(), chip8.snapshot());
+    }
+
+    #[test]
+    fn rom_mismatch() {
+        let movie = Movie::parse("rom abcd").unwrap();
+
+        assert!(matches!(
+            movie.setup(&mut Chip8::new(), "ef01"),
+            Err(MovieError::RomMismatch { .. })
+        ));
+        assert!(movie.setup(&mut Chip8::new(), "abcd").is_ok());
+    }
+}
@@ -0,0 +1,56 @@
+use std::str::FromStr;
+
+/// Colors for the four pixel values of a [`Screen`](crate::chip8::Screen):
+/// background, plane 1, plane 2 and both planes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Palette {
+    pub colors: [[u8; 3]; 4],
+}
+
+impl Palette {
+    pub fn new(colors: [[u8; 3]; 4]) -> Self {
+        Self { colors }
+    }
+
+    pub fn color(&self, pixel: u8) -> [u8; 3] {
+        self.colors[(pixel & 0b11) as usize]
+    }
+}
+
+/// Two to four comma separated `#RRGGBB` colors, the ones left out keep
+/// their default
+impl FromStr for Palette {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let colors: Vec<&str> = s.split(',').collect();
+        if !(2..=4).contains(&colors.len()) {
+            return Err(format!("palette `{s}` needs 2 to 4 colors"));
+        }
+
+        let mut palette = Self::default();
+        for (slot, color) in palette.colors.iter_mut().zip(colors) {
+            *slot = parse_color(color.trim())
+                .ok_or_else(|| format!("invalid color `{color}`, expected #RRGGBB"))?;
+        }
+        Ok(palette)
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::new([[0, 0, 0], [0, 255, 0], [255, 102, 0], [255, 204, 0]])
+    }
+}
+
+/// `#RRGGBB` color
+pub(crate) fn parse_color(color: &str) -> Option<[u8; 3]> {
+    let hex = color.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let value = u32::from_str_radix(hex, 16).ok()?;
+    let [_, r, g, b] = value.to_be_bytes();
+
+    Some([r, g, b])
+}
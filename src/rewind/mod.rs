@@ -0,0 +1,172 @@
+use std::collections::VecDeque;
+
+use crate::{
+    chip8::{
+        Chip8,
+        FRAMES_PER_SECOND,
+    },
+    save_state::SaveState,
+};
+
+mod tests;
+
+/// History of the last frames for playing a program backwards.
+///
+/// Only the newest frame is kept whole, in the save state encoding. Every
+/// older frame is stored as the difference to the frame after it: the XOR of
+/// both encodings with runs of zeros collapsed. Between two frames little
+/// changes, so a frame typically takes a few dozen bytes.
+pub struct Rewind {
+    /// Frames kept at most
+    capacity: usize,
+    latest: Option<Vec<u8>>,
+    /// Oldest first, the back turns `latest` into the frame before it
+    deltas: VecDeque<Delta>,
+}
+
+/// Difference from a frame to the one before it
+struct Delta {
+    /// Encoded length of the older frame
+    len: usize,
+    /// Runs of unchanged bytes and changed bytes
+    runs: Vec<u8>,
+}
+
+impl Rewind {
+    /// History of `seconds` seconds, 0 disables it
+    pub fn new(seconds: usize) -> Self {
+        let capacity = seconds * FRAMES_PER_SECOND as usize;
+        let latest = None;
+        let deltas = VecDeque::new();
+
+        Self {
+            capacity,
+            latest,
+            deltas,
+        }
+    }
+
+    /// Record the state after a frame
+    pub fn push(&mut self, chip8: &Chip8) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let frame = SaveState::new(String::new(), chip8.snapshot()).encode();
+        if let Some(latest) = self.latest.replace(frame) {
+            let newest = self.latest.as_ref().unwrap();
+            self.deltas.push_back(Delta::between(newest, &latest));
+            if self.deltas.len() >= self.capacity {
+                self.deltas.pop_front();
+            }
+        }
+    }
+
+    /// Go back one frame, `false` once the history is used up
+    pub fn rewind(&mut self, chip8: &mut Chip8) -> bool {
+        let (Some(latest), Some(delta)) = (&mut self.latest, self.deltas.pop_back()) else {
+            return false;
+        };
+        delta.apply(latest);
+
+        let state = SaveState::decode(latest).expect("rewind frames decode");
+        chip8.restore(&state.snapshot);
+
+        true
+    }
+
+    /// Frames that can be gone back
+    pub fn len(&self) -> usize {
+        self.deltas.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.deltas.is_empty()
+    }
+
+    /// Bytes used by the history
+    pub fn size(&self) -> usize {
+        let latest = self.latest.as_ref().map_or(0, Vec::len);
+        latest
+            + self
+                .deltas
+                .iter()
+                .map(|delta| delta.runs.len())
+                .sum::<usize>()
+    }
+
+    /// Forget the history, for example after loading a save state
+    pub fn clear(&mut self) {
+        self.latest = None;
+        self.deltas.clear();
+    }
+}
+
+impl Delta {
+    /// Delta turning `newer` into `older`.
+    ///
+    /// `runs` is a sequence of an unchanged run length, a changed run length,
+    /// both as LEB128, and the XOR of the changed bytes.
+    fn between(newer: &[u8], older: &[u8]) -> Self {
+        let len = newer.len().max(older.len());
+        let xor: Vec<u8> = (0..len)
+            .map(|i| newer.get(i).unwrap_or(&0) ^ older.get(i).unwrap_or(&0))
+            .collect();
+
+        let mut runs = vec![];
+        let mut rest = &xor[..];
+        while !rest.is_empty() {
+            let unchanged = rest.iter().take_while(|&&b| b == 0).count();
+            let changed = rest[unchanged..].iter().take_while(|&&b| b != 0).count();
+            write_len(&mut runs, unchanged);
+            write_len(&mut runs, changed);
+            runs.extend(&rest[unchanged..unchanged + changed]);
+            rest = &rest[unchanged + changed..];
+        }
+
+        Self {
+            len: older.len(),
+            runs,
+        }
+    }
+
+    fn apply(&self, frame: &mut Vec<u8>) {
+        frame.resize(frame.len().max(self.len), 0);
+
+        let mut pos = 0;
+        let mut runs = &self.runs[..];
+        while !runs.is_empty() {
+            pos += read_len(&mut runs);
+            let changed = read_len(&mut runs);
+            for (byte, xor) in frame[pos..pos + changed].iter_mut().zip(&runs[..changed]) {
+                *byte ^= xor;
+            }
+            pos += changed;
+            runs = &runs[changed..];
+        }
+
+        frame.truncate(self.len);
+    }
+}
+
+fn write_len(out: &mut Vec<u8>, mut len: usize) {
+    while len >= 0x80 {
+        out.push(len as u8 | 0x80);
+        len >>= 7;
+    }
+    out.push(len as u8);
+}
+
+fn read_len(runs: &mut &[u8]) -> usize {
+    let mut len = 0;
+    let mut shift = 0;
+    loop {
+        let byte = runs[0];
+        *runs = &runs[1..];
+        len |= ((byte & 0x7F) as usize) << shift;
+        if byte & 0x80 == 0 {
+            return len;
+        }
+        shift += 7;
+    }
+}
@@ -0,0 +1,90 @@
+#[cfg(test)]
+mod rewind {
+    use crate::{
+        chip8::Chip8,
+        rewind::Rewind,
+    };
+
+    /// Counts V0 up and draws a digit every frame
+    fn counter() -> Chip8 {
+        let mut chip8 = Chip8::new();
+        // ld f, v0; cls; drw v1, v1, 5; add v0, 1; ld st, v0; jp 0x200
+        chip8
+            .load_rom(&[
+                0xF0, 0x29, 0x00, 0xE0, 0xD1, 0x15, 0x70, 0x01, 0xF0, 0x18, 0x12, 0x00,
+            ])
+            .unwrap();
+        chip8.set_instructions_per_frame(6);
+        chip8
+    }
+
+    #[test]
+    fn play_backwards() {
+        let mut chip8 = counter();
+        let mut rewind = Rewind::new(1);
+
+        let mut snapshots = vec![];
+        for _ in 0..20 {
+            chip8.run_frame().unwrap();
+            rewind.push(&chip8);
+            snapshots.push(chip8.snapshot());
+        }
+        assert_eq!(rewind.len(), 19);
+
+        snapshots.pop();
+        while let Some(snapshot) = snapshots.pop() {
+            assert!(rewind.rewind(&mut chip8));
+            assert_eq!(chip8.snapshot(), snapshot);
+        }
+        assert!(!rewind.rewind(&mut chip8));
+    }
+
+    #[test]
+    fn continue_after_rewind() {
+        let mut chip8 = counter();
+        let mut rewind = Rewind::new(1);
+
+        for _ in 0..10 {
+            chip8.run_frame().unwrap();
+            rewind.push(&chip8);
+        }
+        for _ in 0..5 {
+            rewind.rewind(&mut chip8);
+        }
+        let branch = chip8.snapshot();
+        chip8.run_frame().unwrap();
+        rewind.push(&chip8);
+
+        assert!(rewind.rewind(&mut chip8));
+        assert_eq!(chip8.snapshot(), branch);
+    }
+
+    #[test]
+    fn bounded_depth() {
+        let mut chip8 = counter();
+        let mut rewind = Rewind::new(1);
+
+        for _ in 0..300 {
+            chip8.run_frame().unwrap();
+            rewind.push(&chip8);
+        }
+
+        assert_eq!(rewind.len(), 59);
+        // A full frame is over 12 KB, the deltas are tiny
+        let frame = chip8.memory().len() + 128 * 64;
+        assert!(rewind.size() < frame + 59 * 100);
+    }
+
+    #[test]
+    fn disabled() {
+        let mut chip8 = counter();
+        let mut rewind = Rewind::new(0);
+
+        chip8.run_frame().unwrap();
+        rewind.push(&chip8);
+        rewind.push(&chip8);
+
+        assert!(rewind.is_empty());
+        assert_eq!(rewind.size(), 0);
+    }
+}
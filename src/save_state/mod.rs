@@ -0,0 +1,376 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt,
+    fs,
+    io,
+    path::{
+        Path,
+        PathBuf,
+    },
+};
+
+use crate::chip8::{
+    Chip8,
+    Quirks,
+    Rng,
+    Screen,
+    Snapshot,
+    Stack,
+    Timers,
+    HIRES_HEIGHT,
+    HIRES_WIDTH,
+    MEMORY_SIZE,
+    STACK_DEPTH,
+    XO_MEMORY_SIZE,
+};
+
+mod tests;
+
+const MAGIC: &[u8; 4] = b"C8SS";
+/// Major and minor version of the file format. A newer minor version only
+/// adds sections or appends fields to sections, readers skip what they don't
+/// know. Files with a newer major version are rejected.
+pub const FORMAT_VERSION: (u8, u8) = (1, 1);
+
+/// [`Snapshot`] of a running program together with the SHA-1 of its ROM.
+///
+/// The file starts with `C8SS` and the format version, followed by sections
+/// of a four byte tag, a little endian `u32` length and the data.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SaveState {
+    pub rom_sha1: String,
+    pub snapshot: Snapshot,
+}
+
+/// Reading, writing or loading a save state failed
+#[derive(Debug)]
+pub enum SaveStateError {
+    Io(io::Error),
+    /// File doesn't start with the save state magic
+    NotASaveState,
+    /// File was written by a newer, incompatible version
+    UnsupportedVersion {
+        major: u8,
+        minor: u8,
+    },
+    /// Section missing, too short or out of the machine's limits
+    Corrupt {
+        section: String,
+    },
+    /// State belongs to another ROM
+    RomMismatch {
+        expected: String,
+        found: String,
+    },
+}
+
+impl SaveState {
+    pub fn new(rom_sha1: String, snapshot: Snapshot) -> Self {
+        Self { rom_sha1, snapshot }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let snapshot = &self.snapshot;
+        let mut out = MAGIC.to_vec();
+        out.extend([FORMAT_VERSION.0, FORMAT_VERSION.1]);
+
+        let mut section = |tag: &[u8; 4], data: Vec<u8>| {
+            out.extend(tag);
+            out.extend((data.len() as u32).to_le_bytes());
+            out.extend(data);
+        };
+
+        section(b"ROM ", self.rom_sha1.as_bytes().to_vec());
+        section(b"MEM ", snapshot.memory.clone());
+
+        let mut cpu = vec![];
+        cpu.extend((snapshot.pc as u32).to_le_bytes());
+        cpu.extend((snapshot.i as u32).to_le_bytes());
+        cpu.extend(snapshot.opcode.to_le_bytes());
+        cpu.extend(snapshot.v);
+        cpu.push(snapshot.wait_key as u8);
+        cpu.push(snapshot.halted as u8);
+        cpu.push(snapshot.vblank_wait as u8);
+        section(b"CPU ", cpu);
+
+        let mut stack = vec![snapshot.stack.stack().len() as u8];
+        for &address in snapshot.stack.stack() {
+            stack.extend((address as u32).to_le_bytes());
+        }
+        section(b"STCK", stack);
+
+        section(
+            b"TIME",
+            vec![snapshot.timers.delay(), snapshot.timers.sound()],
+        );
+
+        let screen = &snapshot.screen;
+        let mut scrn = vec![screen.is_hires() as u8, screen.planes()];
+        scrn.extend(screen.vram().iter().flatten());
+        section(b"SCRN", scrn);
+
+        section(b"RPL ", snapshot.rpl.to_vec());
+
+        let mut audio = vec![snapshot.pitch, snapshot.audio_pattern.is_some() as u8];
+        audio.extend(snapshot.audio_pattern.unwrap_or_default());
+        section(b"AUDI", audio);
+
+        section(b"RNG ", snapshot.rng.state().to_le_bytes().to_vec());
+
+        let quirks = Quirks::NAMES
+            .iter()
+            .enumerate()
+            .filter(|(_, name)| snapshot.quirks.get(name) == Some(true))
+            .fold(0u32, |bits, (bit, _)| bits | 1 << bit);
+        section(b"QURK", quirks.to_le_bytes().to_vec());
+
+        out
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, SaveStateError> {
+        let header = bytes.get(..6).ok_or(SaveStateError::NotASaveState)?;
+        if &header[..4] != MAGIC {
+            return Err(SaveStateError::NotASaveState);
+        }
+        let (major, minor) = (header[4], header[5]);
+        if major > FORMAT_VERSION.0 {
+            return Err(SaveStateError::UnsupportedVersion { major, minor });
+        }
+
+        let mut sections = HashMap::new();
+        let mut rest = &bytes[6..];
+        while !rest.is_empty() {
+            let mut header = Fields::new(*b"    ", rest);
+            let tag: [u8; 4] = header.bytes(4)?.try_into().unwrap();
+            let len = header.u32()? as usize;
+            let data = Fields::new(tag, header.data).bytes(len)?;
+            sections.insert(tag, data);
+            rest = &rest[8 + len..];
+        }
+        let section = |tag: &'static [u8; 4]| {
+            sections
+                .get(tag)
+                .map(|data| Fields::new(*tag, data))
+                .ok_or_else(|| Fields::new(*tag, &[]).corrupt())
+        };
+
+        let rom_sha1 = String::from_utf8_lossy(section(b"ROM ")?.data).into_owned();
+        let mem = section(b"MEM ")?;
+        if !(MEMORY_SIZE..=XO_MEMORY_SIZE).contains(&mem.data.len()) {
+            return Err(mem.corrupt());
+        }
+        let memory = mem.data.to_vec();
+
+        let mut cpu = section(b"CPU ")?;
+        let pc = cpu.u32()? as usize;
+        let i = cpu.u32()? as usize;
+        let opcode = cpu.u16()?;
+        let v = cpu.bytes(16)?.try_into().unwrap();
+        let wait_key = cpu.u8()? != 0;
+        let halted = cpu.u8()? != 0;
+        let vblank_wait = cpu.u8()? != 0;
+
+        let mut stack = Stack::new();
+        if let Ok(mut stck) = section(b"STCK") {
+            let depth = stck.u8()?;
+            if depth as usize > STACK_DEPTH {
+                return Err(stck.corrupt());
+            }
+            for _ in 0..depth {
+                stack.push(stck.u32()? as usize);
+            }
+        }
+
+        let mut timers = Timers::new();
+        if let Ok(mut time) = section(b"TIME") {
+            timers.set_delay(time.u8()?);
+            timers.set_sound(time.u8()?);
+        }
+
+        let mut scrn = section(b"SCRN")?;
+        let mut screen = Screen::new();
+        screen.set_hires(scrn.u8()? != 0);
+        screen.set_planes(scrn.u8()?);
+        let mut vram = [[0; HIRES_WIDTH]; HIRES_HEIGHT];
+        for row in &mut vram {
+            row.copy_from_slice(scrn.bytes(HIRES_WIDTH)?);
+        }
+        screen.set_vram(vram);
+
+        let rpl = match section(b"RPL ") {
+            Ok(mut rpl) => rpl.bytes(16)?.try_into().unwrap(),
+            Err(_) => [0; 16],
+        };
+
+        let (pitch, audio_pattern) = match section(b"AUDI") {
+            Ok(mut audio) => {
+                let pitch = audio.u8()?;
+                let has_pattern = audio.u8()? != 0;
+                let pattern: [u8; 16] = audio.bytes(16)?.try_into().unwrap();
+                (pitch, has_pattern.then_some(pattern))
+            }
+            Err(_) => (64, None),
+        };
+
+        // Older 1.1 files follow the state with a random mode byte, skipped now
+        let rng = match section(b"RNG ") {
+            Ok(mut rng) => Rng::from_state(rng.u64()?),
+            Err(_) => Rng::new(),
+        };
+
+        let mut quirks = Quirks::default();
+        if let Ok(mut qurk) = section(b"QURK") {
+            let bits = qurk.u32()?;
+            for (bit, name) in Quirks::NAMES.iter().enumerate() {
+                quirks.set(name, bits & (1 << bit) != 0).unwrap();
+            }
+        }
+
+        let snapshot = Snapshot {
+            memory,
+            pc,
+            opcode,
+            v,
+            i,
+            stack,
+            timers,
+            screen,
+            wait_key,
+            halted,
+            vblank_wait,
+            rpl,
+            audio_pattern,
+            pitch,
+            rng,
+            quirks,
+        };
+
+        Ok(Self { rom_sha1, snapshot })
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), SaveStateError> {
+        fs::write(path, self.encode())?;
+
+        Ok(())
+    }
+
+    pub fn read(path: &Path) -> Result<Self, SaveStateError> {
+        Self::decode(&fs::read(path)?)
+    }
+}
+
+/// Numbered save state files next to a ROM, `game.ch8` saves slot 1 to
+/// `game.state1`
+pub struct SaveSlots {
+    rom_path: PathBuf,
+    rom_sha1: String,
+}
+
+impl SaveSlots {
+    pub fn new(rom_path: &Path, rom_sha1: String) -> Self {
+        let rom_path = rom_path.to_path_buf();
+
+        Self { rom_path, rom_sha1 }
+    }
+
+    pub fn path(&self, slot: u8) -> PathBuf {
+        self.rom_path.with_extension(format!("state{slot}"))
+    }
+
+    pub fn save(&self, slot: u8, chip8: &Chip8) -> Result<(), SaveStateError> {
+        SaveState::new(self.rom_sha1.clone(), chip8.snapshot()).write(&self.path(slot))
+    }
+
+    /// Restore `chip8` from a slot saved for the same ROM
+    pub fn load(&self, slot: u8, chip8: &mut Chip8) -> Result<(), SaveStateError> {
+        let state = SaveState::read(&self.path(slot))?;
+        if state.rom_sha1 != self.rom_sha1 {
+            return Err(SaveStateError::RomMismatch {
+                expected: self.rom_sha1.clone(),
+                found: state.rom_sha1,
+            });
+        }
+        chip8.restore(&state.snapshot);
+
+        Ok(())
+    }
+}
+
+/// Fields read in order from one section
+struct Fields<'a> {
+    tag: [u8; 4],
+    data: &'a [u8],
+}
+
+impl<'a> Fields<'a> {
+    fn new(tag: [u8; 4], data: &'a [u8]) -> Self {
+        Self { tag, data }
+    }
+
+    fn corrupt(&self) -> SaveStateError {
+        SaveStateError::Corrupt {
+            section: String::from_utf8_lossy(&self.tag).trim_end().to_string(),
+        }
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], SaveStateError> {
+        if self.data.len() < len {
+            return Err(self.corrupt());
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, SaveStateError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, SaveStateError> {
+        Ok(u16::from_le_bytes(self.bytes(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, SaveStateError> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, SaveStateError> {
+        Ok(u64::from_le_bytes(self.bytes(8)?.try_into().unwrap()))
+    }
+}
+
+impl fmt::Display for SaveStateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "Failed to access save state: {err}"),
+            Self::NotASaveState => write!(f, "Not a save state file"),
+            Self::UnsupportedVersion { major, minor } => write!(
+                f,
+                "Save state format {major}.{minor} is newer than the supported {}.{}",
+                FORMAT_VERSION.0, FORMAT_VERSION.1
+            ),
+            Self::Corrupt { section } => write!(f, "Save state section `{section}` is corrupt"),
+            Self::RomMismatch { expected, found } => write!(
+                f,
+                "Save state belongs to ROM {found}, not to the running ROM {expected}"
+            ),
+        }
+    }
+}
+
+impl Error for SaveStateError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SaveStateError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
@@ -0,0 +1,152 @@
+#[cfg(test)]
+mod save_state {
+    use std::env;
+
+    use crate::{
+        chip8::{
+            Chip8,
+            Quirks,
+            STACK_DEPTH,
+            XO_MEMORY_SIZE,
+        },
+        save_state::{
+            SaveSlots,
+            SaveState,
+            SaveStateError,
+        },
+    };
+
+    /// Machine in the middle of a program, with a sprite drawn and a
+    /// subroutine called
+    fn running_chip8() -> Chip8 {
+        let mut chip8 = Chip8::new();
+        chip8.set_quirks(Quirks {
+            clip_sprites: true,
+            ..Quirks::default()
+        });
+        // call 0x206; jp 0x204; ld v0, 5; ld f, v0; drw v0, v0, 5; ld st, v0; rnd v1,
+        // 0xFF; jp 0x210
+        chip8
+            .load_rom(&[
+                0x22, 0x06, 0x12, 0x04, 0x00, 0x00, 0x60, 0x05, 0xF0, 0x29, 0xD0, 0x05, 0xF0, 0x18,
+                0xC1, 0xFF, 0x12, 0x10,
+            ])
+            .unwrap();
+        chip8.run_cycles(7).unwrap();
+        chip8
+    }
+
+    #[test]
+    fn round_trip() {
+        let chip8 = running_chip8();
+        let state = SaveState::new("abc".to_string(), chip8.snapshot());
+
+        let decoded = SaveState::decode(&state.encode()).unwrap();
+        assert_eq!(decoded, state);
+        assert_eq!(decoded.snapshot.stack.stack(), [0x202]);
+        assert!(decoded.snapshot.quirks.clip_sprites);
+    }
+
+    #[test]
+    fn restore_continues_identically() {
+        let mut chip8 = running_chip8();
+        let state =
+            SaveState::decode(&SaveState::new(String::new(), chip8.snapshot()).encode()).unwrap();
+
+        let mut restored = Chip8::new();
+        restored.restore(&state.snapshot);
+
+        // The random number generator continues with the same numbers
+        chip8.run_cycles(20).unwrap();
+        restored.run_cycles(20).unwrap();
+        assert_eq!(restored.snapshot(), chip8.snapshot());
+    }
+
+    #[test]
+    fn forward_compatible() {
+        let state = SaveState::new("abc".to_string(), running_chip8().snapshot());
+        let mut bytes = state.encode();
+
+        // A newer minor version with an unknown section still loads
+        bytes[5] = 7;
+        bytes.extend(b"NEW ");
+        bytes.extend(3u32.to_le_bytes());
+        bytes.extend([1, 2, 3]);
+        assert_eq!(SaveState::decode(&bytes).unwrap(), state);
+
+        bytes[4] = 2;
+        assert!(matches!(
+            SaveState::decode(&bytes),
+            Err(SaveStateError::UnsupportedVersion { major: 2, minor: 7 })
+        ));
+    }
+
+    #[test]
+    fn invalid_files() {
+        assert!(matches!(
+            SaveState::decode(b"PNG"),
+            Err(SaveStateError::NotASaveState)
+        ));
+
+        let bytes = SaveState::new(String::new(), Chip8::new().snapshot()).encode();
+        assert!(matches!(
+            SaveState::decode(&bytes[..bytes.len() - 1]),
+            Err(SaveStateError::Corrupt { .. })
+        ));
+    }
+
+    #[test]
+    fn memory_size_out_of_range() {
+        let mut snapshot = Chip8::new().snapshot();
+        for len in [0x80, XO_MEMORY_SIZE + 1] {
+            snapshot.memory = vec![0; len];
+            let bytes = SaveState::new(String::new(), snapshot.clone()).encode();
+            assert!(matches!(
+                SaveState::decode(&bytes),
+                Err(SaveStateError::Corrupt { section }) if section == "MEM"
+            ));
+        }
+    }
+
+    #[test]
+    fn stack_too_deep() {
+        let mut snapshot = Chip8::new().snapshot();
+        for address in 0..=STACK_DEPTH {
+            snapshot.stack.push(0x200 + address * 2);
+        }
+        let bytes = SaveState::new(String::new(), snapshot).encode();
+
+        assert!(matches!(
+            SaveState::decode(&bytes),
+            Err(SaveStateError::Corrupt { section }) if section == "STCK"
+        ));
+    }
+
+    #[test]
+    fn slots() {
+        let dir = env::temp_dir().join(format!("chip-8-slots-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let rom_path = dir.join("game.ch8");
+
+        let mut chip8 = running_chip8();
+        let slots = SaveSlots::new(&rom_path, "aaaa".to_string());
+        assert_eq!(slots.path(3), dir.join("game.state3"));
+        slots.save(3, &chip8).unwrap();
+
+        let mut restored = Chip8::new();
+        slots.load(3, &mut restored).unwrap();
+        assert_eq!(restored.snapshot(), chip8.snapshot());
+
+        let other = SaveSlots::new(&rom_path, "bbbb".to_string());
+        assert!(matches!(
+            other.load(3, &mut chip8),
+            Err(SaveStateError::RomMismatch { .. })
+        ));
+        assert!(matches!(
+            slots.load(4, &mut chip8),
+            Err(SaveStateError::Io(_))
+        ));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
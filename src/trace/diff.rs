@@ -0,0 +1,310 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+};
+
+use crate::{
+    chip8::{
+        Chip8,
+        Step,
+        PROGRAM_START,
+    },
+    movie::Movie,
+};
+
+use super::{
+    OpcodeClass,
+    TraceLine,
+};
+
+/// Finds the first instruction where two runs of a program disagree.
+///
+/// [`TraceDiff::lockstep`] runs two machines one instruction at a time,
+/// [`TraceDiff::against`] compares one machine with a recorded trace. PC,
+/// opcode, registers, `I`, stack depth and timers are compared after every
+/// instruction, program memory from 0x200 after instructions that access
+/// memory. The interpreter area below it holds fonts that differ between
+/// platforms.
+#[derive(Debug)]
+pub struct TraceDiff {
+    /// Keypad of every frame, fed to both runs
+    input: Option<Movie>,
+    /// Instructions compared before giving up
+    limit: u64,
+    /// Instructions kept before the divergence
+    context: usize,
+}
+
+/// Result of a [`TraceDiff`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DiffOutcome {
+    /// Both runs agreed for `cycles` instructions, until they stopped, the
+    /// trace ended or the limit was reached
+    Same {
+        cycles: u64,
+    },
+    Diverged(Box<Divergence>),
+}
+
+/// First instruction the runs disagree on
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    /// Instructions before it as the left run executed them
+    pub context: Vec<TraceLine>,
+    /// The instruction on each side, `None` for a side that stopped
+    pub left: Option<TraceLine>,
+    pub right: Option<TraceLine>,
+    pub differences: Vec<Difference>,
+}
+
+/// One value that differs between the left and the right run
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Difference {
+    Pc(usize, usize),
+    Opcode(u16, u16),
+    V(usize, u8, u8),
+    I(usize, usize),
+    Sp(usize, usize),
+    Dt(u8, u8),
+    St(u8, u8),
+    /// Byte at the address
+    Memory(usize, u8, u8),
+    /// Only one run stopped or they stopped differently, with the reasons
+    Stopped(String, String),
+}
+
+/// Machine of a comparison and its position
+struct Side<'a> {
+    chip8: &'a mut Chip8,
+    cycle: u64,
+    frame: u64,
+}
+
+impl TraceDiff {
+    pub fn new(limit: u64) -> Self {
+        let input = None;
+        let context = 10;
+
+        Self {
+            input,
+            limit,
+            context,
+        }
+    }
+
+    /// Feed the keypad of `movie` to both runs
+    pub fn set_input(&mut self, movie: Movie) {
+        self.input = Some(movie);
+    }
+
+    /// Instructions shown before the divergence
+    pub fn set_context(&mut self, context: usize) {
+        self.context = context;
+    }
+
+    /// Run `left` and `right` side by side
+    pub fn lockstep(&self, left: &mut Chip8, right: &mut Chip8) -> DiffOutcome {
+        let mut left = Side::new(left);
+        let mut right = Side::new(right);
+        let mut context = VecDeque::new();
+
+        while left.cycle < self.limit {
+            let (l, r) = (self.step(&mut left), self.step(&mut right));
+            let (l, r) = match (l, r) {
+                (Ok(l), Ok(r)) => (l, r),
+                (Err(l), Err(r)) if l == r => return DiffOutcome::Same { cycles: left.cycle },
+                (l, r) => {
+                    let reason = |result: &Result<_, String>| match result {
+                        Ok(_) => "running".to_string(),
+                        Err(reason) => reason.clone(),
+                    };
+                    let differences = vec![Difference::Stopped(reason(&l), reason(&r))];
+                    let (l, r) = (l.ok().map(|(_, l)| l), r.ok().map(|(_, r)| r));
+                    return self.diverged(context, l, r, differences);
+                }
+            };
+
+            let mut differences = differences(&l.1, &r.1);
+            if differences.is_empty() && (accesses_memory(&l.0) || accesses_memory(&r.0)) {
+                differences = memory_differences(left.chip8.memory(), right.chip8.memory());
+            }
+            if !differences.is_empty() {
+                return self.diverged(context, Some(l.1), Some(r.1), differences);
+            }
+            self.remember(&mut context, l.1);
+        }
+
+        DiffOutcome::Same { cycles: left.cycle }
+    }
+
+    /// Run `chip8` against `trace`, the right side.
+    ///
+    /// Lines are matched by their cycle so a trace may skip instructions.
+    pub fn against(
+        &self,
+        chip8: &mut Chip8,
+        trace: impl IntoIterator<Item = TraceLine>,
+    ) -> DiffOutcome {
+        let mut side = Side::new(chip8);
+        let mut context = VecDeque::new();
+
+        for expected in trace {
+            let actual = loop {
+                if side.cycle >= self.limit {
+                    return DiffOutcome::Same { cycles: side.cycle };
+                }
+                match self.step(&mut side) {
+                    Ok((_, line)) if line.cycle >= expected.cycle => break line,
+                    Ok((_, line)) => self.remember(&mut context, line),
+                    Err(reason) => {
+                        let differences = vec![Difference::Stopped(reason, "running".to_string())];
+                        return self.diverged(context, None, Some(expected), differences);
+                    }
+                }
+            };
+
+            let differences = differences(&actual, &expected);
+            if !differences.is_empty() {
+                return self.diverged(context, Some(actual), Some(expected), differences);
+            }
+            self.remember(&mut context, actual);
+        }
+
+        DiffOutcome::Same { cycles: side.cycle }
+    }
+
+    /// Run one instruction of `side`, `Err` with the reason it stopped
+    fn step(&self, side: &mut Side) -> Result<(Step, TraceLine), String> {
+        if side.chip8.is_halted() {
+            return Err("halted".to_string());
+        }
+        if side.chip8.at_frame_start() {
+            if let Some(input) = &self.input {
+                input.feed(side.frame as usize, side.chip8);
+            }
+        }
+
+        let step = side.chip8.step_in_frame().map_err(|err| err.to_string())?;
+        let line = TraceLine::new(side.cycle, side.frame, &step, side.chip8);
+        side.cycle += 1;
+        if side.chip8.at_frame_start() {
+            side.frame += 1;
+        }
+
+        Ok((step, line))
+    }
+
+    fn remember(&self, context: &mut VecDeque<TraceLine>, line: TraceLine) {
+        context.push_back(line);
+        if context.len() > self.context {
+            context.pop_front();
+        }
+    }
+
+    fn diverged(
+        &self,
+        context: VecDeque<TraceLine>,
+        left: Option<TraceLine>,
+        right: Option<TraceLine>,
+        differences: Vec<Difference>,
+    ) -> DiffOutcome {
+        DiffOutcome::Diverged(Box::new(Divergence {
+            context: context.into(),
+            left,
+            right,
+            differences,
+        }))
+    }
+}
+
+impl<'a> Side<'a> {
+    fn new(chip8: &'a mut Chip8) -> Self {
+        Self {
+            chip8,
+            cycle: 0,
+            frame: 0,
+        }
+    }
+}
+
+/// Differences in the state after two instructions
+pub fn differences(left: &TraceLine, right: &TraceLine) -> Vec<Difference> {
+    let mut differences = vec![];
+    if left.pc != right.pc {
+        differences.push(Difference::Pc(left.pc, right.pc));
+    }
+    if left.opcode != right.opcode {
+        differences.push(Difference::Opcode(left.opcode, right.opcode));
+    }
+    for (x, (&l, &r)) in left.v.iter().zip(&right.v).enumerate() {
+        if l != r {
+            differences.push(Difference::V(x, l, r));
+        }
+    }
+    if left.i != right.i {
+        differences.push(Difference::I(left.i, right.i));
+    }
+    if left.sp != right.sp {
+        differences.push(Difference::Sp(left.sp, right.sp));
+    }
+    if left.dt != right.dt {
+        differences.push(Difference::Dt(left.dt, right.dt));
+    }
+    if left.st != right.st {
+        differences.push(Difference::St(left.st, right.st));
+    }
+
+    differences
+}
+
+/// Differing bytes of program memory both machines have
+fn memory_differences(left: &[u8], right: &[u8]) -> Vec<Difference> {
+    left.iter()
+        .zip(right)
+        .enumerate()
+        .skip(PROGRAM_START)
+        .filter(|(_, (l, r))| l != r)
+        .map(|(address, (&l, &r))| Difference::Memory(address, l, r))
+        .collect()
+}
+
+fn accesses_memory(step: &Step) -> bool {
+    OpcodeClass::of(&step.instruction) == OpcodeClass::Memory
+}
+
+/// Context lines, the diverging lines marked `<` for left and `>` for right,
+/// then the differences
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.context {
+            writeln!(f, "  {line}")?;
+        }
+        if let Some(left) = &self.left {
+            writeln!(f, "< {left}")?;
+        }
+        if let Some(right) = &self.right {
+            writeln!(f, "> {right}")?;
+        }
+        for difference in &self.differences {
+            writeln!(f, "{difference}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pc(l, r) => write!(f, "PC: {l:03X} vs {r:03X}"),
+            Self::Opcode(l, r) => write!(f, "opcode: {l:04X} vs {r:04X}"),
+            Self::V(x, l, r) => write!(f, "V{x:X}: {l:02X} vs {r:02X}"),
+            Self::I(l, r) => write!(f, "I: {l:03X} vs {r:03X}"),
+            Self::Sp(l, r) => write!(f, "SP: {l} vs {r}"),
+            Self::Dt(l, r) => write!(f, "DT: {l:02X} vs {r:02X}"),
+            Self::St(l, r) => write!(f, "ST: {l:02X} vs {r:02X}"),
+            Self::Memory(address, l, r) => write!(f, "memory {address:03X}: {l:02X} vs {r:02X}"),
+            Self::Stopped(l, r) => write!(f, "stopped: {l} vs {r}"),
+        }
+    }
+}
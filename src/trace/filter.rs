@@ -0,0 +1,68 @@
+use std::ops::RangeInclusive;
+
+use {
+    super::OpcodeClass,
+    crate::chip8::Step,
+};
+
+/// Which executed instructions make it into a trace, everything by default
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TraceFilter {
+    /// Addresses of traced instructions
+    pub addresses: Option<RangeInclusive<usize>>,
+    /// Traced classes, all when empty
+    pub classes: Vec<OpcodeClass>,
+    /// Frames counted from 0 at the start
+    pub frames: Option<RangeInclusive<u64>>,
+}
+
+impl TraceFilter {
+    pub fn matches(&self, frame: u64, step: &Step) -> bool {
+        self.addresses
+            .as_ref()
+            .is_none_or(|addresses| addresses.contains(&step.address))
+            && self
+                .frames
+                .as_ref()
+                .is_none_or(|frames| frames.contains(&frame))
+            && (self.classes.is_empty()
+                || self.classes.contains(&OpcodeClass::of(&step.instruction)))
+    }
+
+    /// Whether no frame from `frame` on can match anymore
+    pub fn is_past(&self, frame: u64) -> bool {
+        self.frames
+            .as_ref()
+            .is_some_and(|frames| frame > *frames.end())
+    }
+}
+
+/// `START-END` range of hex addresses, both included
+pub fn parse_address_range(s: &str) -> Result<RangeInclusive<usize>, String> {
+    parse_range(s, 16).map(|range| *range.start() as usize..=*range.end() as usize)
+}
+
+/// `START-END` range of frames, both included
+pub fn parse_frame_range(s: &str) -> Result<RangeInclusive<u64>, String> {
+    parse_range(s, 10)
+}
+
+fn parse_range(s: &str, radix: u32) -> Result<RangeInclusive<u64>, String> {
+    let parse = |bound: &str| {
+        let digits = match radix {
+            16 => bound.trim_start_matches("0x"),
+            _ => bound,
+        };
+        u64::from_str_radix(digits, radix).map_err(|_| format!("invalid range bound `{bound}`"))
+    };
+
+    let (start, end) = s
+        .split_once('-')
+        .ok_or_else(|| format!("invalid range `{s}`, expected START-END"))?;
+    let (start, end) = (parse(start)?, parse(end)?);
+    if end < start {
+        return Err(format!("empty range `{s}`"));
+    }
+
+    Ok(start..=end)
+}
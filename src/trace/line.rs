@@ -0,0 +1,131 @@
+use std::{
+    fmt,
+    str::FromStr,
+};
+
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::chip8::{
+    Chip8,
+    Step,
+};
+
+/// One executed instruction and the machine state right after it
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceLine {
+    /// Instructions executed before this one
+    pub cycle: u64,
+    pub frame: u64,
+    /// Address the instruction was fetched from
+    pub pc: usize,
+    pub opcode: u16,
+    /// Disassembly in Cowgod syntax
+    pub instruction: String,
+    pub v: [u8; 16],
+    pub i: usize,
+    /// Stack depth
+    pub sp: usize,
+    pub dt: u8,
+    pub st: u8,
+}
+
+impl TraceLine {
+    pub fn new(cycle: u64, frame: u64, step: &Step, chip8: &Chip8) -> Self {
+        Self {
+            cycle,
+            frame,
+            pc: step.address,
+            opcode: step.opcode.code(),
+            instruction: step.instruction.to_string(),
+            v: *chip8.v(),
+            i: chip8.i(),
+            sp: chip8.stack().stack().len(),
+            dt: chip8.timers().delay(),
+            st: chip8.timers().sound(),
+        }
+    }
+}
+
+/// Text form: cycle, frame, PC, opcode, disassembly, then the registers
+impl fmt::Display for TraceLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:>8} {:>6} {:03X} {:04X} {:<20}",
+            self.cycle, self.frame, self.pc, self.opcode, self.instruction
+        )?;
+        for (x, vx) in self.v.iter().enumerate() {
+            write!(f, " V{x:X}={vx:02X}")?;
+        }
+        write!(
+            f,
+            " I={:03X} SP={} DT={:02X} ST={:02X}",
+            self.i, self.sp, self.dt, self.st
+        )
+    }
+}
+
+/// Either format of a trace file line, text or JSON
+impl FromStr for TraceLine {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.starts_with('{') {
+            return serde_json::from_str(s).map_err(|err| err.to_string());
+        }
+
+        let mut words = s.split_whitespace();
+        let mut next = |name: &str| words.next().ok_or_else(|| format!("missing {name}"));
+        let number = |name: &str, text: &str, radix: u32| {
+            u64::from_str_radix(text, radix).map_err(|_| format!("invalid {name} `{text}`"))
+        };
+        let cycle = number("cycle", next("cycle")?, 10)?;
+        let frame = number("frame", next("frame")?, 10)?;
+        let pc = number("PC", next("PC")?, 16)? as usize;
+        let opcode = number("opcode", next("opcode")?, 16)?;
+        let opcode = u16::try_from(opcode).map_err(|_| format!("invalid opcode `{opcode:X}`"))?;
+
+        let mut line = Self {
+            cycle,
+            frame,
+            pc,
+            opcode,
+            instruction: String::new(),
+            v: [0; 16],
+            i: 0,
+            sp: 0,
+            dt: 0,
+            st: 0,
+        };
+        let mut instruction = vec![];
+        for word in words {
+            let Some((name, value)) = word.split_once('=') else {
+                instruction.push(word);
+                continue;
+            };
+            let byte =
+                || u8::from_str_radix(value, 16).map_err(|_| format!("invalid {name} `{value}`"));
+            match name {
+                "I" => line.i = number(name, value, 16)? as usize,
+                "SP" => line.sp = number(name, value, 10)? as usize,
+                "DT" => line.dt = byte()?,
+                "ST" => line.st = byte()?,
+                _ => {
+                    let x = name
+                        .strip_prefix('V')
+                        .and_then(|x| usize::from_str_radix(x, 16).ok())
+                        .filter(|&x| x < 16)
+                        .ok_or_else(|| format!("unknown register `{name}`"))?;
+                    line.v[x] = byte()?;
+                }
+            }
+        }
+        line.instruction = instruction.join(" ");
+
+        Ok(line)
+    }
+}
@@ -0,0 +1,160 @@
+use std::{
+    fmt,
+    io::{
+        self,
+        Write,
+    },
+    str::FromStr,
+};
+
+use crate::chip8::{
+    Chip8,
+    Chip8Error,
+};
+
+pub use self::{
+    diff::{
+        differences,
+        DiffOutcome,
+        Difference,
+        Divergence,
+        TraceDiff,
+    },
+    filter::{
+        parse_address_range,
+        parse_frame_range,
+        TraceFilter,
+    },
+    line::TraceLine,
+    opcode_class::OpcodeClass,
+};
+
+mod diff;
+mod filter;
+mod line;
+mod opcode_class;
+mod tests;
+
+/// Writes a [`TraceLine`] for every executed instruction that passes a
+/// [`TraceFilter`].
+///
+/// Frontends run frames through [`Tracer::run_frame`] instead of
+/// [`Chip8::run_frame`]. Write errors don't stop the program, tracing stops
+/// and [`Tracer::finish`] returns the error.
+pub struct Tracer {
+    output: Box<dyn Write>,
+    format: TraceFormat,
+    filter: TraceFilter,
+    cycle: u64,
+    frame: u64,
+    error: Option<io::Error>,
+}
+
+/// Trace file format
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TraceFormat {
+    /// Aligned columns, see [`TraceLine`]'s `Display`
+    #[default]
+    Text,
+    /// One JSON object per line
+    Jsonl,
+}
+
+impl Tracer {
+    pub fn new(output: impl Write + 'static, format: TraceFormat, filter: TraceFilter) -> Self {
+        let output = Box::new(output);
+        let cycle = 0;
+        let frame = 0;
+        let error = None;
+
+        Self {
+            output,
+            format,
+            filter,
+            cycle,
+            frame,
+            error,
+        }
+    }
+
+    /// Frames run so far
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// [`Chip8::run_frame`] one instruction at a time, tracing each
+    pub fn run_frame(&mut self, chip8: &mut Chip8) -> Result<(), Chip8Error> {
+        if self.error.is_some() || self.filter.is_past(self.frame) {
+            chip8.run_frame()?;
+            self.frame += 1;
+            return Ok(());
+        }
+
+        loop {
+            let step = chip8.step_in_frame()?;
+            if self.filter.matches(self.frame, &step) {
+                let line = TraceLine::new(self.cycle, self.frame, &step, chip8);
+                if let Err(err) = self.write(&line) {
+                    self.error = Some(err);
+                }
+            }
+            self.cycle += 1;
+
+            if chip8.at_frame_start() {
+                break;
+            }
+        }
+        self.frame += 1;
+
+        // Nothing more to write, don't wait for the end of the run
+        if self.filter.is_past(self.frame) {
+            if let Err(err) = self.output.flush() {
+                self.error = Some(err);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Flush the output, returns the first write error
+    pub fn finish(mut self) -> io::Result<()> {
+        match self.error.take() {
+            Some(err) => Err(err),
+            None => self.output.flush(),
+        }
+    }
+
+    fn write(&mut self, line: &TraceLine) -> io::Result<()> {
+        match self.format {
+            TraceFormat::Text => writeln!(self.output, "{line}"),
+            TraceFormat::Jsonl => {
+                serde_json::to_writer(&mut self.output, line)?;
+                writeln!(self.output)
+            }
+        }
+    }
+}
+
+impl FromStr for TraceFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "jsonl" => Ok(Self::Jsonl),
+            _ => Err(format!(
+                "unknown trace format `{s}`, expected text or jsonl"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for TraceFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Text => "text",
+            Self::Jsonl => "jsonl",
+        };
+        write!(f, "{name}")
+    }
+}
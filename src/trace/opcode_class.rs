@@ -0,0 +1,135 @@
+use std::{
+    fmt,
+    str::FromStr,
+};
+
+use crate::chip8::Instruction;
+
+/// Group of instructions for trace filters
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OpcodeClass {
+    /// `1nnn`, `Bnnn`
+    Jump,
+    /// `2nnn`, `00EE`
+    Call,
+    /// `3xkk`, `4xkk`, `5xy0`, `9xy0`
+    Skip,
+    /// `6xkk`, `8xy0`, `Annn`, `F000 nnnn`, `Fx29`, `Fx30`
+    Load,
+    /// `7xkk`, `8xy1` to `8xyE`, `Cxkk`, `Fx1E`
+    Alu,
+    /// `Fx33`, `Fx55`, `Fx65`, `5xy2`, `5xy3`, `Fx75`, `Fx85`
+    Memory,
+    /// `00E0`, `Dxyn`, scrolling, `00FE`, `00FF`, `Fn01`
+    Display,
+    /// `Fx07`, `Fx15`, `Fx18`
+    Timer,
+    /// `Ex9E`, `ExA1`, `Fx0A`
+    Key,
+    /// `F002`, `Fx3A`
+    Audio,
+    /// `00FD`, `0nnn` and unknown opcodes
+    System,
+}
+
+impl OpcodeClass {
+    pub const NAMES: [&'static str; 11] = [
+        "jump", "call", "skip", "load", "alu", "memory", "display", "timer", "key", "audio",
+        "system",
+    ];
+
+    pub fn of(instruction: &Instruction) -> Self {
+        match instruction {
+            Instruction::Jp(_) | Instruction::JpV0(_) => Self::Jump,
+            Instruction::Call(_) | Instruction::Ret => Self::Call,
+            Instruction::SeByte(..)
+            | Instruction::SneByte(..)
+            | Instruction::SeReg(..)
+            | Instruction::SneReg(..) => Self::Skip,
+            Instruction::LdByte(..)
+            | Instruction::LdReg(..)
+            | Instruction::LdI(_)
+            | Instruction::LdILong
+            | Instruction::LdF(_)
+            | Instruction::LdHf(_) => Self::Load,
+            Instruction::AddByte(..)
+            | Instruction::Or(..)
+            | Instruction::And(..)
+            | Instruction::Xor(..)
+            | Instruction::AddReg(..)
+            | Instruction::Sub(..)
+            | Instruction::Shr(..)
+            | Instruction::Subn(..)
+            | Instruction::Shl(..)
+            | Instruction::Rnd(..)
+            | Instruction::AddI(_) => Self::Alu,
+            Instruction::LdB(_)
+            | Instruction::LdIVx(_)
+            | Instruction::LdVxI(_)
+            | Instruction::Save(..)
+            | Instruction::Load(..)
+            | Instruction::LdRVx(_)
+            | Instruction::LdVxR(_) => Self::Memory,
+            Instruction::Cls
+            | Instruction::Drw(..)
+            | Instruction::Scd(_)
+            | Instruction::Scu(_)
+            | Instruction::Scr
+            | Instruction::Scl
+            | Instruction::Low
+            | Instruction::High
+            | Instruction::Plane(_) => Self::Display,
+            Instruction::LdVxDt(_) | Instruction::LdDtVx(_) | Instruction::LdStVx(_) => Self::Timer,
+            Instruction::Skp(_) | Instruction::Sknp(_) | Instruction::LdVxK(_) => Self::Key,
+            Instruction::Audio | Instruction::LdPitch(_) => Self::Audio,
+            Instruction::Exit | Instruction::Sys(_) | Instruction::Unknown(_) => Self::System,
+        }
+    }
+}
+
+impl FromStr for OpcodeClass {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let class = match s {
+            "jump" => Self::Jump,
+            "call" => Self::Call,
+            "skip" => Self::Skip,
+            "load" => Self::Load,
+            "alu" => Self::Alu,
+            "memory" => Self::Memory,
+            "display" => Self::Display,
+            "timer" => Self::Timer,
+            "key" => Self::Key,
+            "audio" => Self::Audio,
+            "system" => Self::System,
+            _ => {
+                return Err(format!(
+                    "unknown opcode class `{s}`, expected one of: {}",
+                    Self::NAMES.join(", ")
+                ))
+            }
+        };
+
+        Ok(class)
+    }
+}
+
+impl fmt::Display for OpcodeClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Jump => "jump",
+            Self::Call => "call",
+            Self::Skip => "skip",
+            Self::Load => "load",
+            Self::Alu => "alu",
+            Self::Memory => "memory",
+            Self::Display => "display",
+            Self::Timer => "timer",
+            Self::Key => "key",
+            Self::Audio => "audio",
+            Self::System => "system",
+        };
+        write!(f, "{name}")
+    }
+}
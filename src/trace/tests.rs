@@ -0,0 +1,251 @@
+#[cfg(test)]
+mod tracer {
+    use std::{
+        cell::RefCell,
+        io::{
+            self,
+            Write,
+        },
+        rc::Rc,
+    };
+
+    use crate::{
+        chip8::Chip8,
+        trace::{
+            parse_address_range,
+            parse_frame_range,
+            OpcodeClass,
+            TraceFilter,
+            TraceFormat,
+            TraceLine,
+            Tracer,
+        },
+    };
+
+    /// 0x200: ld v0, 0x01, 0x202: add v0, 0x01, 0x204: jp 0x202
+    const ROM: [u8; 6] = [0x60, 0x01, 0x70, 0x01, 0x12, 0x02];
+
+    /// Output the test reads back after the tracer wrote it
+    #[derive(Clone, Default)]
+    struct Buffer(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn trace(format: TraceFormat, filter: TraceFilter, frames: usize) -> Vec<String> {
+        let mut chip8 = Chip8::new();
+        chip8.set_instructions_per_frame(4);
+        chip8.load_rom(&ROM).unwrap();
+
+        let buffer = Buffer::default();
+        let mut tracer = Tracer::new(buffer.clone(), format, filter);
+        for _ in 0..frames {
+            tracer.run_frame(&mut chip8).unwrap();
+        }
+        tracer.finish().unwrap();
+
+        let text = String::from_utf8(buffer.0.take()).unwrap();
+        text.lines().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn text() {
+        let lines = trace(TraceFormat::Text, TraceFilter::default(), 2);
+
+        assert_eq!(lines.len(), 8);
+        assert!(lines[0].starts_with("       0      0 200 6001 ld v0, 0x01"));
+        assert!(lines[0].contains(" V0=01 V1=00 "));
+        assert!(lines[0].ends_with(" VF=00 I=000 SP=0 DT=00 ST=00"));
+        assert!(lines[5].starts_with("       5      1 202 7001 add v0, 0x01"));
+        assert!(lines[5].contains(" V0=04 "));
+    }
+
+    #[test]
+    fn jsonl() {
+        let lines = trace(TraceFormat::Jsonl, TraceFilter::default(), 1);
+
+        let line: TraceLine = serde_json::from_str(&lines[1]).unwrap();
+        assert_eq!(line.cycle, 1);
+        assert_eq!(line.pc, 0x202);
+        assert_eq!(line.opcode, 0x7001);
+        assert_eq!(line.v[0], 2);
+    }
+
+    #[test]
+    fn filters() {
+        let addresses = TraceFilter {
+            addresses: Some(0x204..=0x204),
+            ..TraceFilter::default()
+        };
+        let lines = trace(TraceFormat::Text, addresses, 2);
+        assert_eq!(lines.len(), 3);
+        assert!(lines.iter().all(|line| line.contains(" 204 1202 ")));
+
+        let classes = TraceFilter {
+            classes: vec![OpcodeClass::Load],
+            ..TraceFilter::default()
+        };
+        assert_eq!(trace(TraceFormat::Text, classes, 2).len(), 1);
+
+        let frames = TraceFilter {
+            frames: Some(1..=1),
+            ..TraceFilter::default()
+        };
+        let lines = trace(TraceFormat::Text, frames, 3);
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("       4      1 "));
+    }
+
+    #[test]
+    fn ranges() {
+        assert_eq!(parse_address_range("200-2FF"), Ok(0x200..=0x2FF));
+        assert_eq!(parse_address_range("0x200-0x202"), Ok(0x200..=0x202));
+        assert_eq!(parse_frame_range("10-20"), Ok(10..=20));
+        assert!(parse_frame_range("20-10").is_err());
+        assert!(parse_frame_range("10").is_err());
+        assert!(parse_address_range("200-xyz").is_err());
+    }
+
+    #[test]
+    fn classes() {
+        for name in OpcodeClass::NAMES {
+            let class: OpcodeClass = name.parse().unwrap();
+            assert_eq!(class.to_string(), name);
+        }
+        assert!("nope".parse::<OpcodeClass>().is_err());
+        assert_eq!("jsonl".parse(), Ok(TraceFormat::Jsonl));
+    }
+}
+
+#[cfg(test)]
+mod diff {
+    use crate::{
+        chip8::{
+            Chip8,
+            Platform,
+        },
+        trace::{
+            DiffOutcome,
+            Difference,
+            Divergence,
+            TraceDiff,
+            TraceLine,
+        },
+    };
+
+    /// 0x200: ld v0, 0x81, 0x202: ld v1, 0x03, 0x204: shr v0, v1,
+    /// 0x206: ld i, 0x20C, 0x208: ld [i], v0, 0x20A: jp 0x20A, 0x20C: data
+    const ROM: [u8; 14] = [
+        0x60, 0x81, 0x61, 0x03, 0x80, 0x16, 0xA2, 0x0C, 0xF0, 0x55, 0x12, 0x0A, 0x00, 0x00,
+    ];
+
+    fn chip8(platform: Platform, rom: &[u8]) -> Chip8 {
+        let mut chip8 = Chip8::with_platform(platform);
+        chip8.load_rom(rom).unwrap();
+        chip8
+    }
+
+    fn diverged(outcome: DiffOutcome) -> Box<Divergence> {
+        match outcome {
+            DiffOutcome::Diverged(divergence) => divergence,
+            DiffOutcome::Same { cycles } => panic!("no difference in {cycles} instructions"),
+        }
+    }
+
+    #[test]
+    fn same() {
+        let mut left = chip8(Platform::CosmacVip, &ROM);
+        let mut right = chip8(Platform::CosmacVip, &ROM);
+
+        let outcome = TraceDiff::new(100).lockstep(&mut left, &mut right);
+        assert_eq!(outcome, DiffOutcome::Same { cycles: 100 });
+    }
+
+    #[test]
+    fn registers() {
+        let mut left = chip8(Platform::CosmacVip, &ROM);
+        let mut right = chip8(Platform::SuperChip11, &ROM);
+        let mut diff = TraceDiff::new(100);
+        diff.set_context(1);
+
+        let divergence = diverged(diff.lockstep(&mut left, &mut right));
+        assert_eq!(divergence.context.len(), 1);
+        assert_eq!(divergence.context[0].pc, 0x202);
+        assert_eq!(divergence.left.as_ref().unwrap().cycle, 2);
+        assert_eq!(divergence.differences, [Difference::V(0, 0x01, 0x40)]);
+        assert!(divergence.to_string().ends_with("\nV0: 01 vs 40\n"));
+    }
+
+    #[test]
+    fn memory() {
+        let mut other = ROM;
+        other[13] = 0xFF;
+        let mut left = chip8(Platform::CosmacVip, &ROM);
+        let mut right = chip8(Platform::CosmacVip, &other);
+
+        let divergence = diverged(TraceDiff::new(100).lockstep(&mut left, &mut right));
+        assert_eq!(divergence.left.unwrap().pc, 0x208);
+        assert_eq!(
+            divergence.differences,
+            [Difference::Memory(0x20D, 0x00, 0xFF)]
+        );
+    }
+
+    #[test]
+    fn stopped() {
+        // 0x200: exit, only SUPER-CHIP has it
+        let rom = [0x00, 0xFD, 0x12, 0x00];
+        let mut left = chip8(Platform::SuperChip11, &rom);
+        let mut right = chip8(Platform::CosmacVip, &rom);
+
+        let divergence = diverged(TraceDiff::new(100).lockstep(&mut left, &mut right));
+        assert_eq!(
+            divergence.differences,
+            [Difference::Stopped(
+                "halted".to_string(),
+                "running".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn against_trace() {
+        let text = "\
+       0      0 200 6081 ld v0, 0x81          V0=81 V1=00 I=000 SP=0 DT=00 ST=00
+       2      0 204 8016 shr v0, v1           V0=40 V1=03 VF=01 I=000 SP=0 DT=00 ST=00
+";
+        let trace: Vec<TraceLine> = text.lines().map(|line| line.parse().unwrap()).collect();
+        assert_eq!(trace[1].instruction, "shr v0, v1");
+        assert_eq!(trace[1].v[0xF], 1);
+
+        let mut chip8 = self::chip8(Platform::SuperChip11, &ROM);
+        let outcome = TraceDiff::new(100).against(&mut chip8, trace.clone());
+        assert_eq!(outcome, DiffOutcome::Same { cycles: 3 });
+
+        let mut chip8 = self::chip8(Platform::CosmacVip, &ROM);
+        let divergence = diverged(TraceDiff::new(100).against(&mut chip8, trace));
+        assert_eq!(divergence.context.len(), 2);
+        assert_eq!(divergence.differences, [Difference::V(0, 0x01, 0x40)]);
+    }
+
+    #[test]
+    fn parse_lines() {
+        let mut chip8 = chip8(Platform::CosmacVip, &ROM);
+        let step = chip8.step_in_frame().unwrap();
+        let line = TraceLine::new(0, 0, &step, &chip8);
+
+        assert_eq!(line.to_string().parse(), Ok(line.clone()));
+        assert_eq!(serde_json::to_string(&line).unwrap().parse(), Ok(line));
+        assert!("0 0 200".parse::<TraceLine>().is_err());
+        assert!("0 0 200 6081 ld v0, 0x81 VG=00"
+            .parse::<TraceLine>()
+            .is_err());
+    }
+}